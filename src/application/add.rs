@@ -39,9 +39,11 @@ use crate::infrastructure::*;
 ///
 /// ## Staging Strategies:
 /// - `git add file.txt` - Stage specific file
-/// - `git add dir/` - Stage all files in directory
-/// - `git add .` - Stage all files in current directory and subdirectories
-/// - `git add --all` or `git add -A` - Stage all changes (including deletions)
+/// - `git add dir/` - Stage all changes in directory, deletions included
+/// - `git add .` - Stage all changes in current directory and subdirectories
+/// - `git add --all` or `git add -A` - Stage all changes in the repository
+/// - `git add --update` or `git add -u` - Stage changes to tracked files only
+/// - `git add --force` or `git add -f` - Also stage ignored files
 pub struct AddCommand;
 
 impl AddCommand {
@@ -60,12 +62,26 @@ impl AddCommand {
         file_paths: &[String],
         options: AddOptions,
     ) -> crate::Result<AddResult> {
-        let repo_path = repo_path.as_ref();
-        let mut repo = GitRepository::new(repo_path);
+        Self::add_with_compat(repo_path, file_paths, options, GitCompatMode::Educational)
+    }
+
+    /// Add files to the staging area, with the repository layout chosen by
+    /// `git_compat`
+    pub fn add_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        file_paths: &[String],
+        options: AddOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<AddResult> {
+        let mut repo = GitRepository::new_with_compat(repo_path, git_compat);
 
         // Verify this is a Git repository
         if !repo.is_repository() {
-            return Err("Not a git repository (or any of the parent directories): .git".into());
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
         }
 
         println!("🔍 Adding files to staging area...");
@@ -73,44 +89,61 @@ impl AddCommand {
         // Load existing repository state
         Self::load_repository_state(&mut repo)?;
 
-        // Resolve file paths to actual files
-        let resolved_files = Self::resolve_file_paths(&repo, file_paths, &options)?;
-
-        if resolved_files.is_empty() {
-            println!("⚠️  No files to add");
-            return Ok(AddResult::new());
-        }
-
         // Initialize stores
-        let object_store = ObjectStore::new(repo.objects_dir());
+        let object_store = ObjectStore::for_repository(&repo);
         let index_store = IndexStore::new(repo.index_path());
 
+        // Work out what changes, before anything is written
+        let plan = Self::plan(&mut repo, &object_store, file_paths, &options)?;
         let mut result = AddResult::new();
 
+        if plan.stage.is_empty() && plan.remove.is_empty() {
+            println!("⚠️  No files to add");
+            if plan.index_refreshed {
+                index_store.update_index_if_able(&repo.index);
+            }
+            Self::check_ignored(&plan.ignored)?;
+            return Ok(result);
+        }
+
         // Process each file
-        for file_path in resolved_files {
+        for relative_path in plan.stage {
+            let file_path = repo.root_path().join(&relative_path);
             match Self::stage_file(&repo, &object_store, &file_path) {
                 Ok(entry) => {
-                    println!("   ✓ Staged: {}", file_path.display());
+                    println!("   ✓ Staged: {}", relative_path.display());
                     repo.index.add_entry(entry.clone());
                     result.staged_files.push(entry);
                 }
                 Err(e) => {
-                    println!("   ✗ Failed to stage {}: {}", file_path.display(), e);
-                    result.failed_files.push((file_path, e.to_string()));
+                    println!("   ✗ Failed to stage {}: {}", relative_path.display(), e);
+                    result.failed_files.push((relative_path, e.to_string()));
                 }
             }
         }
 
+        // Tracked files that are gone from disk leave the index too
+        for relative_path in plan.remove {
+            println!("   ✓ Removed: {}", relative_path.display());
+            repo.index.remove_entry(&relative_path);
+            result.removed_files.push(relative_path);
+        }
+
         // Save updated index
         index_store.save_index(&repo.index)?;
 
         println!("📊 Staging Summary:");
         println!("   Staged: {} files", result.staged_files.len());
+        if !result.removed_files.is_empty() {
+            println!("   Removed: {} files", result.removed_files.len());
+        }
         if !result.failed_files.is_empty() {
             println!("   Failed: {} files", result.failed_files.len());
         }
 
+        // Like Git, everything else is still added before reporting these
+        Self::check_ignored(&plan.ignored)?;
+
         Ok(result)
     }
 
@@ -127,94 +160,196 @@ impl AddCommand {
         Ok(())
     }
 
-    /// Resolve file paths based on add options
-    fn resolve_file_paths(
-        repo: &GitRepository,
+    /// Decide which paths to stage and which to remove from the index
+    ///
+    /// ```text
+    ///                          tracked, changed   tracked, deleted   untracked
+    /// git-rs add <path>...           stage            remove           stage
+    /// git-rs add -u [<path>...]      stage            remove             -
+    /// git-rs add -A [<path>...]      stage            remove           stage
+    /// ```
+    ///
+    /// `-u` and `-A` without paths cover the whole repository. Ignored files
+    /// are skipped inside directories and refused when named directly,
+    /// unless `force` is set; tracked files are never considered ignored.
+    fn plan(
+        repo: &mut GitRepository,
+        object_store: &ObjectStore,
         file_paths: &[String],
         options: &AddOptions,
-    ) -> crate::Result<Vec<PathBuf>> {
-        let mut resolved = Vec::new();
-
-        for file_path in file_paths {
-            let path = Path::new(file_path);
-
-            // Convert to absolute path
-            let abs_path = if path.is_absolute() {
-                path.to_path_buf()
-            } else {
-                repo.root_path.join(path)
-            };
-
-            // Check if path exists
-            if !abs_path.exists() {
-                if !options.ignore_missing {
-                    return Err(format!("pathspec '{}' did not match any files", file_path).into());
+    ) -> crate::Result<AddPlan> {
+        let pathspecs: Vec<PathBuf> = if file_paths.is_empty() {
+            if !options.all && !options.update {
+                return Err(
+                    "Nothing specified, nothing added.\nhint: Maybe you wanted to say 'git-rs add .'?"
+                        .into(),
+                );
+            }
+            vec![PathBuf::new()]
+        } else {
+            file_paths
+                .iter()
+                .map(|path| repo.normalize_path(path))
+                .collect::<crate::Result<_>>()?
+        };
+        let mut matched = vec![false; pathspecs.len()];
+        let mut matches = |path: &Path| {
+            let mut any = false;
+            for (spec, matched) in pathspecs.iter().zip(matched.iter_mut()) {
+                if path.starts_with(spec) {
+                    *matched = true;
+                    any = true;
                 }
+            }
+            any
+        };
+
+        // Tracked paths: only those whose content differs are read again
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+        let refresh = working_tree.refresh_index(object_store, &mut repo.index)?;
+        let mut plan = AddPlan {
+            index_refreshed: refresh.changed,
+            ..AddPlan::default()
+        };
+
+        let mut tracked: Vec<&PathBuf> = repo
+            .index
+            .entries
+            .keys()
+            .chain(repo.index.conflicts.keys())
+            .collect();
+        tracked.sort();
+        for path in tracked {
+            if !matches(path) {
                 continue;
             }
+            match (refresh.files.get(path), repo.index.get_entry(path)) {
+                (Some(current), Some(entry)) => {
+                    if *current != (entry.mode, entry.hash) {
+                        plan.stage.push(path.clone());
+                    }
+                }
+                (None, Some(_)) => plan.remove.push(path.clone()),
+                // Unmerged paths are not refreshed; adding one resolves it
+                (_, None) => match working_tree.file_mode(path)? {
+                    Some(FileMode::Directory) | None => plan.remove.push(path.clone()),
+                    Some(_) => plan.stage.push(path.clone()),
+                },
+            }
+        }
+
+        // Untracked paths
+        if !options.update {
+            for spec in &pathspecs {
+                match working_tree.file_mode(spec)? {
+                    Some(FileMode::Directory) => {
+                        if options.recursive || spec.as_os_str().is_empty() {
+                            Self::collect_untracked_files(
+                                repo,
+                                &working_tree,
+                                spec,
+                                options,
+                                &mut plan.stage,
+                            )?;
+                        }
+                    }
+                    Some(_) if repo.index.is_staged(spec) => {}
+                    Some(_) if !options.force && repo.is_ignored(spec) => {
+                        plan.ignored.push(spec.clone())
+                    }
+                    Some(_) => plan.stage.push(spec.clone()),
+                    None => continue,
+                }
+                matches(spec);
+            }
+        }
 
-            if abs_path.is_file() {
-                // Single file
-                resolved.push(abs_path);
-            } else if abs_path.is_dir() {
-                // Directory - recursively add files
-                Self::collect_files_from_directory(repo, &abs_path, &mut resolved, options)?;
+        if !options.ignore_missing {
+            if let Some((path, _)) = file_paths.iter().zip(&matched).find(|(_, m)| !**m) {
+                return Err(format!("pathspec '{}' did not match any files", path).into());
             }
         }
 
         // Remove duplicates and sort
-        resolved.sort();
-        resolved.dedup();
+        plan.stage.sort();
+        plan.stage.dedup();
+        plan.ignored.sort();
+        plan.ignored.dedup();
 
-        Ok(resolved)
+        Ok(plan)
     }
 
-    /// Recursively collect files from a directory
-    fn collect_files_from_directory(
+    /// Recursively collect the untracked files in a directory
+    fn collect_untracked_files(
         repo: &GitRepository,
+        working_tree: &WorkingTree,
         dir_path: &Path,
-        files: &mut Vec<PathBuf>,
         options: &AddOptions,
+        files: &mut Vec<PathBuf>,
     ) -> crate::Result<()> {
-        for entry in fs::read_dir(dir_path)? {
+        for entry in fs::read_dir(repo.root_path().join(dir_path))? {
             let entry = entry?;
-            let path = entry.path();
+            let path = dir_path.join(entry.file_name());
+
+            // Never add the repository's own data, even when forced
+            if entry.file_name() == ".git" || entry.path() == repo.git_dir() {
+                continue;
+            }
 
             // Skip if ignored
-            if repo.is_ignored(&path) {
+            if !options.force && repo.is_ignored(&path) {
                 continue;
             }
 
-            if path.is_file() {
-                files.push(path);
-            } else if path.is_dir() && options.recursive {
-                Self::collect_files_from_directory(repo, &path, files, options)?;
+            // Symlinks are never followed; nested repositories are staged
+            // as a single gitlink instead of being recursed into
+            match working_tree.file_mode(&path)? {
+                Some(FileMode::Directory) if options.recursive => {
+                    Self::collect_untracked_files(repo, working_tree, &path, options, files)?;
+                }
+                Some(FileMode::Directory) => {}
+                Some(_) if repo.index.is_staged(&path) => {}
+                Some(_) => files.push(path),
+                None => {}
             }
         }
 
         Ok(())
     }
 
+    /// Refuse ignored paths that were named explicitly
+    fn check_ignored(ignored: &[PathBuf]) -> crate::Result<()> {
+        if ignored.is_empty() {
+            return Ok(());
+        }
+        let paths: Vec<String> = ignored.iter().map(|p| p.display().to_string()).collect();
+        Err(format!(
+            "The following paths are ignored by one of your .gitignore files:\n{}\nhint: Use -f if you really want to add them.",
+            paths.join("\n")
+        )
+        .into())
+    }
+
     /// Stage a single file
     fn stage_file(
         repo: &GitRepository,
         object_store: &ObjectStore,
         file_path: &Path,
     ) -> crate::Result<IndexEntry> {
-        // Read file content
-        let content = fs::read(file_path)?;
-        let metadata = fs::metadata(file_path)?;
-
-        // Create blob object
-        let blob = BlobObject::new(content);
-        let blob_object = GitObject::Blob(blob);
-        let blob_hash = object_store.store_object(&blob_object)?;
+        let metadata = fs::symlink_metadata(file_path)?;
 
         // Convert to relative path within repository
         let relative_path = repo.to_relative_path(file_path)?;
 
+        // File content is streamed into a blob, so large files are never
+        // held in memory as a whole; symlinks store their target and
+        // nested repositories their HEAD commit
+        let working_tree = WorkingTree::new(repo.root_path.clone());
+        let (mode, hash) = working_tree.store_path(object_store, &relative_path)?;
+
         // Create index entry
-        let entry = IndexEntry::from_file_metadata(relative_path, blob_hash, &metadata);
+        let mut entry = IndexEntry::from_file_metadata(relative_path, hash, &metadata);
+        entry.mode = mode;
 
         Ok(entry)
     }
@@ -225,33 +360,54 @@ impl AddCommand {
         file_paths: &[String],
         options: AddOptions,
     ) -> crate::Result<Vec<PathBuf>> {
-        let repo = GitRepository::new(repo_path);
+        let mut repo = GitRepository::new(repo_path);
 
         if !repo.is_repository() {
             return Err("Not a git repository".into());
         }
 
-        let resolved_files = Self::resolve_file_paths(&repo, file_paths, &options)?;
+        Self::load_repository_state(&mut repo)?;
+        let object_store = ObjectStore::for_repository(&repo);
+        let plan = Self::plan(&mut repo, &object_store, file_paths, &options)?;
 
         println!("📋 Files that would be added:");
-        for file in &resolved_files {
-            if let Ok(rel_path) = repo.to_relative_path(file) {
-                println!("   {}", rel_path.display());
-            }
+        for path in &plan.stage {
+            println!("   {}", path.display());
+        }
+        for path in &plan.remove {
+            println!("   remove {}", path.display());
         }
 
-        Ok(resolved_files)
+        Ok(plan
+            .stage
+            .iter()
+            .map(|path| repo.root_path().join(path))
+            .collect())
     }
 }
 
+/// What an add changes in the index, relative to the repository root
+#[derive(Debug, Default)]
+struct AddPlan {
+    stage: Vec<PathBuf>,
+    remove: Vec<PathBuf>,
+    /// Ignored paths named explicitly without `force`
+    ignored: Vec<PathBuf>,
+    /// Whether refreshing updated stat data worth saving
+    index_refreshed: bool,
+}
+
 /// Options for the add command
 #[derive(Debug, Clone)]
 pub struct AddOptions {
     /// Add files recursively from directories
     pub recursive: bool,
-    /// Include all files (including deleted ones)
+    /// Stage new files, modifications and deletions; the whole
+    /// repository when no paths are given (`-A`)
     pub all: bool,
-    /// Force add ignored files
+    /// Only stage modifications and deletions of tracked files (`-u`)
+    pub update: bool,
+    /// Add ignored files too (`-f`)
     pub force: bool,
     /// Don't error on missing files
     pub ignore_missing: bool,
@@ -264,6 +420,7 @@ impl Default for AddOptions {
         Self {
             recursive: true,
             all: false,
+            update: false,
             force: false,
             ignore_missing: false,
             dry_run: false,
@@ -275,6 +432,8 @@ impl Default for AddOptions {
 #[derive(Debug, Clone)]
 pub struct AddResult {
     pub staged_files: Vec<IndexEntry>,
+    /// Tracked paths removed from the index because they were deleted
+    pub removed_files: Vec<PathBuf>,
     pub failed_files: Vec<(PathBuf, String)>,
 }
 
@@ -282,6 +441,7 @@ impl AddResult {
     pub fn new() -> Self {
         Self {
             staged_files: Vec::new(),
+            removed_files: Vec::new(),
             failed_files: Vec::new(),
         }
     }
@@ -424,6 +584,101 @@ mod tests {
             .contains("Not a git repository"));
     }
 
+    fn add(repo_path: &Path, paths: &[&str], options: AddOptions) -> crate::Result<AddResult> {
+        let paths: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+        AddCommand::add(repo_path, &paths, options)
+    }
+
+    fn staged_paths(repo_path: &Path) -> Vec<PathBuf> {
+        let index = IndexStore::new(GitRepository::new(repo_path).index_path())
+            .load_index()
+            .unwrap();
+        let mut paths: Vec<PathBuf> = index.entries.into_keys().collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_add_stages_deletions() {
+        let (temp_dir, _repo) = create_test_repo().unwrap();
+        let repo_path = temp_dir.path();
+        fs::create_dir(repo_path.join("src")).unwrap();
+        create_test_file(repo_path, "kept.txt", "kept").unwrap();
+        create_test_file(repo_path, "src/gone.rs", "gone").unwrap();
+        add(repo_path, &["."], AddOptions::default()).unwrap();
+
+        // `add -u` picks up modifications and deletions, but no new files
+        fs::remove_file(repo_path.join("src/gone.rs")).unwrap();
+        create_test_file(repo_path, "kept.txt", "changed").unwrap();
+        create_test_file(repo_path, "new.txt", "new").unwrap();
+        let options = AddOptions {
+            update: true,
+            ..AddOptions::default()
+        };
+        let result = add(repo_path, &[], options).unwrap();
+        assert_eq!(result.total_staged(), 1);
+        assert_eq!(result.removed_files, vec![PathBuf::from("src/gone.rs")]);
+        assert_eq!(staged_paths(repo_path), vec![PathBuf::from("kept.txt")]);
+
+        // Unchanged files are not staged again
+        let result = add(repo_path, &["kept.txt"], AddOptions::default()).unwrap();
+        assert_eq!(result.total_staged(), 0);
+
+        // A plain path stages a deletion under it too, and `-A` new files
+        create_test_file(repo_path, "src/other.rs", "other").unwrap();
+        add(repo_path, &["src"], AddOptions::default()).unwrap();
+        fs::remove_file(repo_path.join("src/other.rs")).unwrap();
+        let options = AddOptions {
+            all: true,
+            ..AddOptions::default()
+        };
+        let result = add(repo_path, &[], options).unwrap();
+        assert_eq!(result.removed_files, vec![PathBuf::from("src/other.rs")]);
+        assert_eq!(
+            staged_paths(repo_path),
+            vec![PathBuf::from("kept.txt"), PathBuf::from("new.txt")]
+        );
+
+        // Without paths, only `-u` and `-A` know what to add
+        let error = add(repo_path, &[], AddOptions::default()).unwrap_err();
+        assert!(error.to_string().starts_with("Nothing specified"));
+    }
+
+    #[test]
+    fn test_add_ignored_files() {
+        let (temp_dir, _repo) = create_test_repo().unwrap();
+        let repo_path = temp_dir.path();
+        fs::create_dir(repo_path.join("build")).unwrap();
+        create_test_file(repo_path, ".gitignore", "*.log\nbuild/\n").unwrap();
+        create_test_file(repo_path, "debug.log", "log").unwrap();
+        create_test_file(repo_path, "build/out.bin", "bin").unwrap();
+        create_test_file(repo_path, "main.rs", "fn main() {}").unwrap();
+
+        // Skipped inside directories, .gitignore itself is not ignored
+        add(repo_path, &["."], AddOptions::default()).unwrap();
+        assert_eq!(
+            staged_paths(repo_path),
+            vec![PathBuf::from(".gitignore"), PathBuf::from("main.rs")]
+        );
+
+        // Refused when named, unless forced
+        let error = add(repo_path, &["debug.log"], AddOptions::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("ignored by one of your .gitignore files"));
+        let options = AddOptions {
+            force: true,
+            ..AddOptions::default()
+        };
+        add(repo_path, &["."], options).unwrap();
+        assert_eq!(staged_paths(repo_path).len(), 4);
+
+        // Once tracked, changes are staged whatever the ignore rules say
+        create_test_file(repo_path, "debug.log", "more log").unwrap();
+        let result = add(repo_path, &["debug.log"], AddOptions::default()).unwrap();
+        assert_eq!(result.total_staged(), 1);
+    }
+
     #[test]
     fn test_dry_run() {
         let (temp_dir, _repo) = create_test_repo().unwrap();
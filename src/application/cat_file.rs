@@ -0,0 +1,355 @@
+use std::io::{BufRead, Read, Write};
+use std::path::Path;
+
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+use crate::infrastructure::revision::RevisionResolver;
+
+/// Git Cat-File Use Case
+///
+/// This implements the `git cat-file` plumbing command: reading objects
+/// straight out of the object database.
+///
+/// ## Modes:
+/// - `-t <object>`: print the object's type
+/// - `-s <object>`: print the object's size in bytes
+/// - `-e <object>`: print nothing; succeed only if the object exists
+/// - `-p <object>`: pretty-print the content (trees as `mode type id\tname`)
+/// - `--batch-check`: for each name on stdin print `<id> <type> <size>`
+/// - `--batch`: like `--batch-check`, followed by the raw content
+///
+/// ## Batch Output:
+/// ```text
+/// $ printf 'HEAD\nnope\n' | git-rs cat-file --batch
+/// 3b18e512dba79e4c8300dd08aeb37f8e728b8dad commit 183
+/// tree 9bdbc4a4...                          ◀── 183 bytes of content
+/// ...
+///                                           ◀── extra newline after content
+/// nope missing
+/// ```
+///
+/// ## Educational Insights:
+/// - **Plumbing vs Porcelain**: Output is stable and free of decoration so
+///   that scripts can parse it
+/// - **One Process, Many Objects**: Batch mode keeps the object database open
+///   and answers one query per input line
+/// - **Header Only**: Type and size come from the object header, so `-t`,
+///   `-s` and `--batch-check` never inflate a loose object's content
+pub struct CatFileCommand;
+
+/// What to print for a single object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatFileMode {
+    /// `-t`
+    Type,
+    /// `-s`
+    Size,
+    /// `-e`
+    Exists,
+    /// `-p`
+    Pretty,
+}
+
+/// What to print for each object named on the batch input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchMode {
+    /// `--batch`: header line followed by the content
+    Contents,
+    /// `--batch-check`: header line only
+    Check,
+}
+
+/// The object database and refs of one repository, opened once
+struct OpenRepository {
+    object_store: ObjectStore,
+    ref_store: RefStore,
+}
+
+impl CatFileCommand {
+    /// Print information about one object to `out`
+    ///
+    /// # Arguments
+    /// * `repo_path` - Path to the repository root
+    /// * `object` - Revision naming the object (e.g. `HEAD`, `v1.0^{tree}`, `HEAD:README`)
+    /// * `mode` - What to print
+    /// * `git_compat` - Git compatibility mode
+    /// * `out` - Where to write the output
+    ///
+    /// # Returns
+    /// * `Ok(true)` - The object was found and printed
+    /// * `Ok(false)` - `-e` only: the object does not exist
+    /// * `Err(...)` - If the object does not exist or cannot be read
+    pub fn cat_file_with_compat<P: AsRef<Path>, W: Write>(
+        repo_path: P,
+        object: &str,
+        mode: CatFileMode,
+        git_compat: GitCompatMode,
+        out: &mut W,
+    ) -> crate::Result<bool> {
+        let database = Self::open(repo_path.as_ref(), git_compat)?;
+
+        let hash = match database.resolve(object) {
+            Ok(Some(hash)) => hash,
+            Ok(None) | Err(_) if mode == CatFileMode::Exists => return Ok(false),
+            Ok(None) => return Err(format!("Not a valid object name {}", object).into()),
+            Err(e) => return Err(e),
+        };
+
+        let mut reader = database.object_store.open_object(&hash)?;
+        match mode {
+            CatFileMode::Type => writeln!(out, "{}", reader.object_type())?,
+            CatFileMode::Size => writeln!(out, "{}", reader.size())?,
+            CatFileMode::Exists => {}
+            CatFileMode::Pretty if reader.object_type() == &GitObjectType::Tree => {
+                let mut content = Vec::new();
+                reader.read_to_end(&mut content)?;
+                let tree = database
+                    .object_store
+                    .parse_object_content(&GitObjectType::Tree, &content)?;
+                Self::print_tree(tree.as_tree().ok_or("not a tree")?, out)?;
+            }
+            CatFileMode::Pretty => {
+                std::io::copy(&mut reader, out)?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Answer one query per line of `input`, returning how many were found
+    ///
+    /// Unknown names produce `<name> missing` (or `<name> ambiguous`) and
+    /// processing continues with the next line.
+    pub fn batch_with_compat<P: AsRef<Path>, R: BufRead, W: Write>(
+        repo_path: P,
+        mode: BatchMode,
+        git_compat: GitCompatMode,
+        input: R,
+        out: &mut W,
+    ) -> crate::Result<usize> {
+        let database = Self::open(repo_path.as_ref(), git_compat)?;
+        let mut found = 0;
+
+        for line in input.lines() {
+            let line = line?;
+            let name = line.trim();
+            if name.is_empty() {
+                continue;
+            }
+
+            let hash = match database.resolve(name) {
+                Ok(Some(hash)) => hash,
+                Ok(None) => {
+                    writeln!(out, "{} missing", name)?;
+                    out.flush()?;
+                    continue;
+                }
+                Err(_) => {
+                    writeln!(out, "{} ambiguous", name)?;
+                    out.flush()?;
+                    continue;
+                }
+            };
+
+            let mut reader = database.object_store.open_object(&hash)?;
+            writeln!(out, "{} {} {}", hash, reader.object_type(), reader.size())?;
+            if mode == BatchMode::Contents {
+                std::io::copy(&mut reader, out)?;
+                writeln!(out)?;
+            }
+            // Let a script waiting on a pipe see each answer right away
+            out.flush()?;
+            found += 1;
+        }
+
+        Ok(found)
+    }
+
+    fn open(repo_path: &Path, git_compat: GitCompatMode) -> crate::Result<OpenRepository> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        Ok(OpenRepository {
+            object_store: ObjectStore::for_repository(&repo),
+            ref_store: RefStore::new(repo.git_dir().to_path_buf()),
+        })
+    }
+
+    /// `<mode> <type> <id>\t<name>`, one line per entry
+    fn print_tree<W: Write>(tree: &TreeObject, out: &mut W) -> std::io::Result<()> {
+        for entry in &tree.entries {
+            let object_type = match entry.mode {
+                FileMode::Directory => "tree",
+                FileMode::Gitlink => "commit",
+                _ => "blob",
+            };
+            writeln!(
+                out,
+                "{} {} {}\t{}",
+                entry.mode.as_octal(),
+                object_type,
+                entry.hash,
+                entry.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl OpenRepository {
+    fn resolve(&self, name: &str) -> crate::Result<Option<ObjectHash>> {
+        RevisionResolver::new(&self.object_store, &self.ref_store).resolve(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{AddCommand, AddOptions, CommitCommand, CommitOptions, InitCommand};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_repo() -> (TempDir, ObjectHash) {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("hello.txt"), "Hello World\n").unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        AddCommand::add(temp_dir.path(), &[".".to_string()], AddOptions::default()).unwrap();
+        let commit =
+            CommitCommand::commit(temp_dir.path(), "Initial", CommitOptions::default()).unwrap();
+
+        (temp_dir, commit.commit_hash)
+    }
+
+    fn cat(temp_dir: &TempDir, object: &str, mode: CatFileMode) -> crate::Result<String> {
+        let mut out = Vec::new();
+        CatFileCommand::cat_file_with_compat(
+            temp_dir.path(),
+            object,
+            mode,
+            GitCompatMode::Educational,
+            &mut out,
+        )?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_cat_file_type_size_and_exists() {
+        let (temp_dir, commit) = setup_repo();
+        let blob = "557db03de997c86a4a028e1ebd3a1ceb225be238";
+
+        assert_eq!(cat(&temp_dir, blob, CatFileMode::Type).unwrap(), "blob\n");
+        assert_eq!(cat(&temp_dir, blob, CatFileMode::Size).unwrap(), "12\n");
+        assert_eq!(
+            cat(&temp_dir, "HEAD", CatFileMode::Type).unwrap(),
+            "commit\n"
+        );
+        assert_eq!(
+            cat(&temp_dir, &commit.to_short_hex(7), CatFileMode::Type).unwrap(),
+            "commit\n"
+        );
+
+        let exists = |name: &str| {
+            CatFileCommand::cat_file_with_compat(
+                temp_dir.path(),
+                name,
+                CatFileMode::Exists,
+                GitCompatMode::Educational,
+                &mut Vec::new(),
+            )
+            .unwrap()
+        };
+        assert!(exists(blob));
+        assert!(!exists("0000000000000000000000000000000000000000"));
+        assert!(!exists("no-such-branch"));
+
+        assert!(cat(&temp_dir, "no-such-branch", CatFileMode::Type).is_err());
+    }
+
+    #[test]
+    fn test_cat_file_pretty_prints_trees() {
+        let (temp_dir, _) = setup_repo();
+
+        let commit = cat(&temp_dir, "HEAD", CatFileMode::Pretty).unwrap();
+        let tree = commit
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("tree "))
+            .unwrap();
+        assert!(commit.ends_with("\nInitial\n"));
+
+        let listing = cat(&temp_dir, tree, CatFileMode::Pretty).unwrap();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(
+            lines[0],
+            "100644 blob 557db03de997c86a4a028e1ebd3a1ceb225be238\thello.txt"
+        );
+        assert!(lines[1].starts_with("040000 tree "));
+        assert!(lines[1].ends_with("\tsrc"));
+
+        assert_eq!(
+            cat(
+                &temp_dir,
+                "557db03de997c86a4a028e1ebd3a1ceb225be238",
+                CatFileMode::Pretty
+            )
+            .unwrap(),
+            "Hello World\n"
+        );
+    }
+
+    #[test]
+    fn test_cat_file_batch_modes() {
+        let (temp_dir, commit) = setup_repo();
+        let input = "557db03de997c86a4a028e1ebd3a1ceb225be238\nmissing-name\n\nHEAD\n";
+
+        let mut out = Vec::new();
+        let found = CatFileCommand::batch_with_compat(
+            temp_dir.path(),
+            BatchMode::Check,
+            GitCompatMode::Educational,
+            input.as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(found, 2);
+        let commit_size = ObjectStore::new(temp_dir.path().join(".git-rs/objects"))
+            .load_raw_object(&commit)
+            .unwrap()
+            .1
+            .len();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "557db03de997c86a4a028e1ebd3a1ceb225be238 blob 12\n\
+                 missing-name missing\n\
+                 {} commit {}\n",
+                commit, commit_size
+            )
+        );
+
+        let mut out = Vec::new();
+        CatFileCommand::batch_with_compat(
+            temp_dir.path(),
+            BatchMode::Contents,
+            GitCompatMode::Educational,
+            "557db03de997c86a4a028e1ebd3a1ceb225be238\nnope\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "557db03de997c86a4a028e1ebd3a1ceb225be238 blob 12\nHello World\n\nnope missing\n"
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use crate::domain::{GitRef, GitRepository, HeadRef, ObjectHash, RefType, RemoteRepository};
-use crate::infrastructure::{RefStore, RemoteClient};
+use crate::domain::{
+    GitCompatMode, GitIndex, GitObject, GitRef, GitRepository, HeadRef, IndexEntry, ObjectFormat,
+    ObjectHash, RefType, RemoteRepository,
+};
+use crate::infrastructure::{IndexStore, ObjectStore, RefStore, RemoteClient, WorkingTree};
 use anyhow::{anyhow, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -16,6 +19,12 @@ pub struct CloneOptions {
     pub depth: Option<u32>,
     /// Whether to show progress
     pub progress: bool,
+    /// Borrow objects from this local repository instead of copying them
+    /// (`--reference`)
+    pub reference: Option<PathBuf>,
+    /// Borrow all objects from the local source repository instead of
+    /// copying them (`--shared`)
+    pub shared: bool,
 }
 
 impl Default for CloneOptions {
@@ -25,6 +34,8 @@ impl Default for CloneOptions {
             bare: false,
             depth: None,
             progress: true,
+            reference: None,
+            shared: false,
         }
     }
 }
@@ -49,6 +60,19 @@ pub struct CloneResult {
 /// - All branches and tags
 /// - Working directory with the default branch checked out
 /// - Remote configuration for future pulls/pushes
+///
+/// ## Sharing Objects Between Clones:
+/// ```text
+/// clone --reference /srv/mirror URL dir     clone --shared /srv/mirror dir
+///
+/// dir/.git-rs/objects/info/alternates:      dir/.git-rs/objects/info/alternates:
+///     /srv/mirror/.git-rs/objects               /srv/mirror/.git-rs/objects
+/// only objects the mirror lacks are copied  nothing is copied
+/// ```
+///
+/// A clone that borrows objects breaks if the repository it borrows from
+/// loses them (e.g. by deleting a branch and repacking), so reference
+/// repositories should only ever grow.
 pub struct CloneCommand;
 
 impl CloneCommand {
@@ -94,8 +118,21 @@ impl CloneCommand {
             println!("🌀 Cloning repository from {}", url);
         }
 
-        // 1. Parse and validate URL
-        let repo_url = Url::parse(url).map_err(|e| anyhow!("Invalid URL '{}': {}", url, e))?;
+        // 1. Parse and validate URL; plain paths and file:// URLs name a local repository
+        let local_source = Self::local_source(url)?;
+        let repo_url = match &local_source {
+            Some(path) => {
+                Url::from_file_path(path).map_err(|_| anyhow!("Invalid path '{}'", url))?
+            }
+            None => Url::parse(url).map_err(|e| anyhow!("Invalid URL '{}': {}", url, e))?,
+        };
+        if options.shared && local_source.is_none() {
+            return Err(anyhow!("--shared only works with a local repository"));
+        }
+        let source_store = local_source
+            .as_deref()
+            .map(Self::open_local_source)
+            .transpose()?;
 
         // 2. Determine local directory name
         let local_dir = match directory {
@@ -128,26 +165,84 @@ impl CloneCommand {
         }
 
         // 4. Initialize local repository
-        let repo = Self::initialize_repository(&local_dir, options.bare)?;
+        let format = source_store
+            .as_ref()
+            .map(|store| store.format())
+            .unwrap_or_default();
+        let repo = Self::initialize_repository(&local_dir, options.bare, format)?;
+
+        // 5. Borrow objects through alternates instead of copying them
+        let mut object_store = ObjectStore::for_repository(&repo);
+        if let Some(reference) = &options.reference {
+            let reference_repo = GitRepository::new(reference);
+            if !reference_repo.is_repository() {
+                return Err(anyhow!(
+                    "reference repository '{}' is not a local repository",
+                    reference.display()
+                ));
+            }
+            Self::borrow_objects(&mut object_store, &reference_repo.objects_dir())?;
+        }
+        if let (true, Some(source)) = (options.shared, &source_store) {
+            Self::borrow_objects(&mut object_store, source.objects_dir())?;
+        }
 
-        // 5. Discover remote references
-        let client = RemoteClient::new()?;
-        let remote = client.discover_refs(&repo_url)?;
+        let (remote, objects_received, target_branch) = match (&local_source, &source_store) {
+            (Some(source_path), Some(source_store)) => {
+                // 6. Read the source repository's references
+                let source_refs =
+                    RefStore::new(GitRepository::new(source_path).git_dir().to_path_buf());
+                let mut remote = RemoteRepository::new(repo_url.clone(), "origin".to_string());
+                for (name, hash) in source_refs
+                    .list_all_refs()
+                    .map_err(|e| anyhow!("Failed to read references: {}", e))?
+                {
+                    remote.add_ref(name, hash.to_hex());
+                }
+                if remote.refs.is_empty() {
+                    return Err(anyhow!("Remote repository has no references"));
+                }
 
-        if remote.refs.is_empty() {
-            return Err(anyhow!("Remote repository has no references"));
-        }
+                // 7. The branch HEAD points at is checked out unless -b says otherwise
+                let target_branch = match source_refs.get_current_branch() {
+                    Ok(Some(branch))
+                        if options.branch.is_none()
+                            && remote.refs.contains_key(&format!("refs/heads/{}", branch)) =>
+                    {
+                        branch
+                    }
+                    _ => Self::determine_target_branch(&remote, &options)?,
+                };
+
+                // 8. Copy the objects this repository cannot borrow
+                let objects_received = Self::copy_objects(source_store, &object_store)?;
+                (remote, objects_received, target_branch)
+            }
+            _ => {
+                // 6. Discover remote references
+                let client = RemoteClient::new()?;
+                let remote = client.discover_refs(&repo_url)?;
+
+                if remote.refs.is_empty() {
+                    return Err(anyhow!("Remote repository has no references"));
+                }
 
-        // 6. Determine which branch to clone
-        let target_branch = Self::determine_target_branch(&remote, &options)?;
+                // 7. Determine which branch to clone
+                let target_branch = Self::determine_target_branch(&remote, &options)?;
 
-        // 7. Fetch objects from remote
-        let objects_received = Self::fetch_objects(&client, &repo_url, &remote, &repo)?;
+                // 8. Fetch objects from remote
+                let objects_received = Self::fetch_objects(&client, &repo_url, &remote, &repo)?;
+                (remote, objects_received, target_branch)
+            }
+        };
 
-        // 8. Set up remote configuration
+        // 9. Set up remote configuration
         Self::setup_remote_config(&repo, &remote)?;
+        if local_source.is_some() {
+            Self::create_local_refs(&repo, &remote, &target_branch)?;
+        }
 
-        // 9. Checkout working directory (if not bare)
+        // 10. Checkout working directory (if not bare)
         let checked_out_branch = if !options.bare {
             Self::checkout_branch(&repo, &target_branch)?
         } else {
@@ -171,10 +266,109 @@ impl CloneCommand {
     }
 
     /// Initialize local repository structure
-    fn initialize_repository(path: &Path, _bare: bool) -> Result<GitRepository> {
+    fn initialize_repository(
+        path: &Path,
+        _bare: bool,
+        format: ObjectFormat,
+    ) -> Result<GitRepository> {
         // Use our existing init command
-        crate::application::init::InitCommand::init(Some(path))
-            .map_err(|e| anyhow!("Failed to initialize repository: {}", e))
+        crate::application::init::InitCommand::init_with_format(
+            Some(path),
+            GitCompatMode::Educational,
+            format,
+        )
+        .map_err(|e| anyhow!("Failed to initialize repository: {}", e))
+    }
+
+    /// The path of a local source repository, or `None` for a network URL
+    ///
+    /// Anything that does not parse as a URL is taken as a path, so both
+    /// `/srv/repo` and `file:///srv/repo` are local.
+    fn local_source(url: &str) -> Result<Option<PathBuf>> {
+        let path = match Url::parse(url) {
+            Ok(parsed) if parsed.scheme() == "file" => parsed
+                .to_file_path()
+                .map_err(|_| anyhow!("Invalid file URL '{}'", url))?,
+            Ok(_) => return Ok(None),
+            Err(_) => PathBuf::from(url),
+        };
+
+        let path = path
+            .canonicalize()
+            .map_err(|_| anyhow!("repository '{}' does not exist", url))?;
+        Ok(Some(path))
+    }
+
+    /// Open the object store of a local source repository
+    fn open_local_source(path: &Path) -> Result<ObjectStore> {
+        let repo = GitRepository::new(path);
+        if !repo.is_repository() {
+            return Err(anyhow!(
+                "'{}' does not appear to be a git-rs repository",
+                path.display()
+            ));
+        }
+        Ok(ObjectStore::for_repository(&repo))
+    }
+
+    /// Add an objects directory to the new repository's alternates
+    fn borrow_objects(object_store: &mut ObjectStore, objects_dir: &Path) -> Result<()> {
+        object_store
+            .add_alternate(objects_dir)
+            .map_err(|e| anyhow!("Failed to set up alternates: {}", e))?;
+        println!("🔗 Borrowing objects from {}", objects_dir.display());
+        Ok(())
+    }
+
+    /// Copy every object of the source that the new repository cannot already
+    /// read (through its alternates), returning how many were copied
+    fn copy_objects(source: &ObjectStore, target: &ObjectStore) -> Result<u32> {
+        println!("📦 Copying objects...");
+
+        let mut copied = 0;
+        for hash in source
+            .list_objects()
+            .map_err(|e| anyhow!("Failed to list objects: {}", e))?
+        {
+            if target.object_exists(&hash) {
+                continue;
+            }
+            let (object_type, content) = source
+                .load_raw_object(&hash)
+                .map_err(|e| anyhow!("Failed to read object {}: {}", hash, e))?;
+            target
+                .store_raw_object(&object_type, &content)
+                .map_err(|e| anyhow!("Failed to write object {}: {}", hash, e))?;
+            copied += 1;
+        }
+
+        Ok(copied)
+    }
+
+    /// Create the checked-out branch and copy tags, as a local clone has
+    /// everything they point at
+    fn create_local_refs(
+        repo: &GitRepository,
+        remote: &RemoteRepository,
+        branch: &str,
+    ) -> Result<()> {
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        for (ref_name, hash) in &remote.refs {
+            let is_target = ref_name
+                .strip_prefix("refs/heads/")
+                .is_some_and(|name| name == branch);
+            if !is_target && !ref_name.starts_with("refs/tags/") {
+                continue;
+            }
+
+            let hash = ObjectHash::from_hex(hash)
+                .map_err(|e| anyhow!("Invalid object id for {}: {}", ref_name, e))?;
+            ref_store
+                .write_ref(ref_name, &hash)
+                .map_err(|e| anyhow!("Failed to write {}: {}", ref_name, e))?;
+        }
+
+        Ok(())
     }
 
     /// Determine which branch to clone/checkout
@@ -240,7 +434,8 @@ impl CloneCommand {
                 let remote_ref_path = format!("refs/remotes/origin/{}", branch_name);
 
                 // Create GitRef for remote tracking branch
-                let object_hash = ObjectHash::new(hash.clone());
+                let object_hash = ObjectHash::from_hex(hash)
+                    .map_err(|e| anyhow!("Invalid object id for {}: {}", ref_name, e))?;
                 let remote_ref = GitRef::new(
                     format!("origin/{}", branch_name),
                     object_hash,
@@ -264,15 +459,25 @@ impl CloneCommand {
     fn checkout_branch(repo: &GitRepository, branch: &str) -> Result<Option<String>> {
         println!("🌿 Checking out branch: {}", branch);
 
-        // In a real implementation, this would:
         // 1. Find the commit object for the branch
         // 2. Load the tree object from the commit
         // 3. Recursively extract all files to working directory
         // 4. Update HEAD to point to the branch
         // 5. Update index with checked out files
-
-        // For now, we'll just set up the basic reference structure
         let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let object_store = ObjectStore::for_repository(repo);
+        let branch_commit = ref_store
+            .load_ref(branch, RefType::Branch)
+            .map_err(|e| anyhow!("Failed to read branch {}: {}", branch, e))?
+            .map(|git_ref| git_ref.hash)
+            .filter(|hash| object_store.object_exists(hash));
+
+        if let Some(commit_hash) = branch_commit {
+            let files = Self::checkout_commit(repo, &object_store, &commit_hash)
+                .map_err(|e| anyhow!("Failed to check out {}: {}", commit_hash, e))?;
+            println!("   📄 Checked out {} files", files);
+        }
+
         let branch_ref = format!("refs/heads/{}", branch);
 
         // Create HeadRef pointing to the branch
@@ -287,6 +492,32 @@ impl CloneCommand {
 
         Ok(Some(branch.to_string()))
     }
+
+    /// Write a commit's tree to the working directory and the index
+    ///
+    /// Symlinks and exec bits are recreated from the tree entry modes.
+    fn checkout_commit(
+        repo: &GitRepository,
+        object_store: &ObjectStore,
+        commit_hash: &ObjectHash,
+    ) -> crate::Result<usize> {
+        let tree_hash = match object_store.load_object(commit_hash)? {
+            GitObject::Commit(commit) => commit.tree,
+            _ => return Err(format!("{} is not a commit", commit_hash).into()),
+        };
+
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+        let count = working_tree.checkout_tree(object_store, &tree_hash)?;
+
+        let mut index = GitIndex::new();
+        for (path, entry) in object_store.flatten_tree(&tree_hash)? {
+            let size = fs::symlink_metadata(repo.root_path().join(&path))?.len();
+            index.add_entry(IndexEntry::new(path, entry.hash, size, entry.mode));
+        }
+        IndexStore::new(repo.index_path()).save_index(&index)?;
+
+        Ok(count)
+    }
 }
 
 impl CloneResult {
@@ -385,4 +616,108 @@ mod tests {
         assert!(summary.contains("42"));
         assert!(summary.contains("main"));
     }
+
+    /// A source repository with one commit on `main` and a tag
+    fn setup_source(root: &Path) -> PathBuf {
+        let source = root.join("source");
+        fs::create_dir_all(&source).unwrap();
+        crate::application::InitCommand::init(Some(&source)).unwrap();
+        fs::write(source.join("README"), "hello\n").unwrap();
+        crate::application::AddCommand::add(&source, &["README".to_string()], Default::default())
+            .unwrap();
+        let commit =
+            crate::application::CommitCommand::commit(&source, "Initial", Default::default())
+                .unwrap()
+                .commit_hash;
+        RefStore::new(source.join(".git-rs"))
+            .write_ref("refs/tags/v1.0", &commit)
+            .unwrap();
+        source
+    }
+
+    fn quiet() -> CloneOptions {
+        CloneOptions {
+            progress: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clone_local_repository() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = setup_source(temp_dir.path());
+        let target = temp_dir.path().join("copy");
+
+        let result =
+            CloneCommand::clone(source.to_str().unwrap(), target.to_str(), quiet()).unwrap();
+        assert_eq!(result.checked_out_branch.as_deref(), Some("main"));
+        assert_eq!(result.objects_received, 3);
+        assert_eq!(
+            fs::read_to_string(target.join("README")).unwrap(),
+            "hello\n"
+        );
+
+        let refs = RefStore::new(target.join(".git-rs"));
+        let main = refs.read_ref("refs/heads/main").unwrap().unwrap();
+        assert_eq!(
+            refs.read_ref("refs/remotes/origin/main").unwrap(),
+            Some(main)
+        );
+        assert_eq!(refs.read_ref("refs/tags/v1.0").unwrap(), Some(main));
+        assert!(ObjectStore::new(target.join(".git-rs/objects"))
+            .alternates()
+            .is_empty());
+
+        assert!(CloneCommand::clone(
+            temp_dir.path().join("nowhere").to_str().unwrap(),
+            None,
+            quiet()
+        )
+        .is_err());
+        let shared_url = CloneOptions {
+            shared: true,
+            ..quiet()
+        };
+        assert!(CloneCommand::clone("https://example.com/repo.git", None, shared_url).is_err());
+    }
+
+    #[test]
+    fn test_clone_shared_and_reference_borrow_objects() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = setup_source(temp_dir.path());
+        let source_objects = source.join(".git-rs/objects").canonicalize().unwrap();
+
+        let shared = temp_dir.path().join("shared");
+        let options = CloneOptions {
+            shared: true,
+            ..quiet()
+        };
+        let result =
+            CloneCommand::clone(source.to_str().unwrap(), shared.to_str(), options).unwrap();
+        assert_eq!(result.objects_received, 0);
+        assert_eq!(
+            fs::read_to_string(shared.join("README")).unwrap(),
+            "hello\n"
+        );
+        let store = ObjectStore::new(shared.join(".git-rs/objects"));
+        assert_eq!(store.alternates(), std::slice::from_ref(&source_objects));
+        assert!(store.list_loose_objects().unwrap().is_empty());
+
+        // Borrowed objects are checked by fsck but never reported as dangling
+        let fsck = crate::application::FsckCommand::fsck(&shared, Default::default()).unwrap();
+        assert!(fsck.issues.is_empty(), "{:?}", fsck.issues);
+
+        // --reference borrows from a different repository than the source
+        let referencing = temp_dir.path().join("referencing");
+        let options = CloneOptions {
+            reference: Some(source.clone()),
+            ..quiet()
+        };
+        let result =
+            CloneCommand::clone(shared.to_str().unwrap(), referencing.to_str(), options).unwrap();
+        assert_eq!(result.objects_received, 0);
+        let store = ObjectStore::new(referencing.join(".git-rs/objects"));
+        assert_eq!(store.alternates(), [source_objects]);
+        assert!(store.list_loose_objects().unwrap().is_empty());
+    }
 }
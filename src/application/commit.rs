@@ -1,11 +1,13 @@
 use crate::domain::index::GitIndex;
 use crate::domain::objects::{
-    CommitObject, GitObject, ObjectHash, Signature, TreeEntry, TreeObject,
+    CommitObject, FileMode, GitObject, ObjectHash, Signature, TreeEntry, TreeObject,
 };
 use crate::domain::references::GitRef;
+use crate::domain::repository::{GitCompatMode, GitRepository};
 use crate::infrastructure::index_store::IndexStore;
 use crate::infrastructure::object_store::ObjectStore;
 use crate::infrastructure::ref_store::RefStore;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Options for commit command
@@ -34,19 +36,26 @@ impl CommitResult {
         if self.is_root_commit {
             format!(
                 "🎉 Root commit created: {} ({} files)",
-                &self.commit_hash.as_str()[..8],
+                self.commit_hash.to_short_hex(8),
                 self.files_committed
             )
         } else {
             format!(
                 "✅ Commit created: {} ({} files)",
-                &self.commit_hash.as_str()[..8],
+                self.commit_hash.to_short_hex(8),
                 self.files_committed
             )
         }
     }
 }
 
+/// One directory of the index while trees are being built
+#[derive(Default)]
+struct DirectoryNode {
+    files: BTreeMap<String, (FileMode, ObjectHash)>,
+    directories: BTreeMap<String, DirectoryNode>,
+}
+
 /// Commit command implementation
 pub struct CommitCommand;
 
@@ -57,16 +66,43 @@ impl CommitCommand {
         message: &str,
         options: CommitOptions,
     ) -> crate::Result<CommitResult> {
-        let git_dir = repo_path.join(".git-rs");
+        Self::commit_with_compat(repo_path, message, options, GitCompatMode::Educational)
+    }
+
+    /// Create a new commit, with the repository layout chosen by `git_compat`
+    pub fn commit_with_compat(
+        repo_path: &Path,
+        message: &str,
+        options: CommitOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<CommitResult> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
 
         // Initialize stores
-        let object_store = ObjectStore::new(git_dir.clone());
-        let index_store = IndexStore::new(git_dir.join("git-rs-index"));
-        let ref_store = RefStore::new(git_dir.clone());
+        let object_store = ObjectStore::for_repository(&repo);
+        let index_store = IndexStore::new(repo.index_path());
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
 
         // Load the current index
         let index = index_store.load_index()?;
 
+        // A tree has one version of each path, so every conflict must be resolved first
+        if index.has_conflicts() {
+            return Err(
+                "Committing is not possible because you have unmerged files.\n\
+                 Fix them up in the work tree, and then use 'git-rs add <file>' \
+                 to mark resolution and make a commit."
+                    .into(),
+            );
+        }
+
         // Check if there are staged changes
         if index.entries.is_empty() && !options.allow_empty {
             return Err(
@@ -108,7 +144,12 @@ impl CommitCommand {
             vec![]
         };
 
-        let commit_obj = CommitObject::new(tree_hash.clone(), parents, author, message.to_string());
+        // Like Git, the stored message always ends with a newline
+        let mut commit_message = message.to_string();
+        if !commit_message.ends_with('\n') {
+            commit_message.push('\n');
+        }
+        let commit_obj = CommitObject::new(tree_hash, parents, author, commit_message);
 
         let is_root_commit = commit_obj.is_root_commit();
 
@@ -119,7 +160,7 @@ impl CommitCommand {
         let current_branch = ref_store.get_current_branch()?;
         let branch_name = current_branch.unwrap_or_else(|| "main".to_string());
 
-        let branch_ref = GitRef::branch(branch_name, commit_hash.clone());
+        let branch_ref = GitRef::branch(branch_name, commit_hash);
         ref_store.store_ref(&branch_ref)?;
 
         Ok(CommitResult {
@@ -131,27 +172,76 @@ impl CommitCommand {
         })
     }
 
-    /// Create tree object from index entries
-    fn create_tree_from_index(
+    /// Create tree objects from index entries, one per directory
+    ///
+    /// The index is flat (`src/main.rs`), but Git trees are nested: every
+    /// directory becomes its own tree object, referenced from its parent by
+    /// a mode 040000 entry:
+    ///
+    /// ```text
+    /// index                 root tree             tree for src/
+    /// README.md      ──▶    100644 README.md      100644 lib.rs
+    /// src/lib.rs            040000 src  ───────▶  100644 main.rs
+    /// src/main.rs
+    /// ```
+    ///
+    /// Trees are built bottom-up because a parent needs its children's hashes.
+    /// A subtree whose contents did not change hashes to the id it already
+    /// has, so it is reused as-is instead of being written again.
+    pub(crate) fn create_tree_from_index(
         object_store: &ObjectStore,
         index: &GitIndex,
     ) -> crate::Result<ObjectHash> {
+        let mut root = DirectoryNode::default();
+
+        // Paths added with `add -N` have no content staged, so no tree lists them
+        for (path, entry) in index.entries.iter().filter(|(_, e)| !e.intent_to_add) {
+            let components: Vec<String> = path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            let (file_name, dirs) = components
+                .split_last()
+                .ok_or_else(|| format!("Invalid path in index: {}", path.display()))?;
+
+            let mut node = &mut root;
+            for dir in dirs {
+                node = node.directories.entry(dir.clone()).or_default();
+            }
+            node.files
+                .insert(file_name.clone(), (entry.mode, entry.hash));
+        }
+
+        Self::write_tree(object_store, &root)
+    }
+
+    /// Store the tree for one directory, after storing its subdirectories
+    fn write_tree(object_store: &ObjectStore, node: &DirectoryNode) -> crate::Result<ObjectHash> {
         let mut entries = Vec::new();
 
-        for (path, entry) in &index.entries {
-            let tree_entry = TreeEntry {
-                mode: entry.mode,
-                name: path.to_string_lossy().to_string(),
-                hash: entry.hash.clone(),
-            };
-            entries.push(tree_entry);
+        for (name, child) in &node.directories {
+            let child_hash = Self::write_tree(object_store, child)?;
+            entries.push(TreeEntry::new(
+                FileMode::Directory,
+                name.clone(),
+                child_hash,
+            ));
+        }
+        for (name, (mode, hash)) in &node.files {
+            entries.push(TreeEntry::new(*mode, name.clone(), *hash));
         }
 
-        // Sort entries by name (Git requirement)
-        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        // Git requires directories to sort as if their name ended in '/'
+        let mut tree = TreeObject { entries };
+        tree.sort_entries();
 
-        let tree_obj = TreeObject { entries };
-        object_store.store_object(&GitObject::Tree(tree_obj))
+        let tree_obj = GitObject::Tree(tree);
+        let tree_hash = object_store.hash_object(&tree_obj)?;
+        if object_store.object_exists(&tree_hash) {
+            // Unchanged subtree: reuse the stored tree
+            return Ok(tree_hash);
+        }
+        object_store.store_object(&tree_obj)
     }
 
     /// Create author and committer signatures
@@ -169,7 +259,7 @@ impl CommitCommand {
     }
 
     /// Get git configuration for user name and email
-    fn get_git_config() -> crate::Result<(String, String)> {
+    pub(crate) fn get_git_config() -> crate::Result<(String, String)> {
         // For now, use environment variables or defaults
         // In a full implementation, this would read from .git/config
         let name = std::env::var("GIT_AUTHOR_NAME")
@@ -201,6 +291,7 @@ mod tests {
     use super::*;
     use crate::application::add::{AddCommand, AddOptions};
     use crate::application::init::InitCommand;
+    use crate::domain::objects::BlobObject;
     use tempfile::TempDir;
 
     #[test]
@@ -236,4 +327,217 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_commit_refuses_unmerged_paths() -> crate::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_path = temp_dir.path();
+        InitCommand::init(Some(repo_path))?;
+        std::fs::write(repo_path.join("test.txt"), "ours\n")?;
+        AddCommand::add(repo_path, &["test.txt".to_string()], AddOptions::default())?;
+
+        let object_store = ObjectStore::new(repo_path.join(".git-rs/objects"));
+        let index_store = IndexStore::new(repo_path.join(".git-rs/git-rs-index"));
+        let mut index = index_store.load_index()?;
+        let mut ours = index.entries[Path::new("test.txt")].clone();
+        ours.stage = 2;
+        let mut theirs = ours.clone();
+        theirs.stage = 3;
+        theirs.hash =
+            object_store.hash_object(&GitObject::Blob(BlobObject::new(b"theirs\n".to_vec())))?;
+        index.add_entry(ours);
+        index.add_entry(theirs);
+        index_store.save_index(&index)?;
+
+        let error =
+            CommitCommand::commit(repo_path, "Merge", CommitOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("unmerged files"), "{}", error);
+        let error = crate::application::WriteTreeCommand::write_tree_with_compat(
+            repo_path,
+            crate::domain::repository::GitCompatMode::Educational,
+        )
+        .unwrap_err();
+        assert!(
+            error.to_string().contains("test.txt: unmerged"),
+            "{}",
+            error
+        );
+
+        // Resolving the conflict makes the index committable again
+        AddCommand::add(repo_path, &["test.txt".to_string()], AddOptions::default())?;
+        let result = CommitCommand::commit(repo_path, "Merge", CommitOptions::default())?;
+        assert_eq!(result.files_committed, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_builds_nested_trees() -> crate::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_path = temp_dir.path();
+        InitCommand::init(Some(repo_path))?;
+
+        std::fs::create_dir_all(repo_path.join("src/bin"))?;
+        std::fs::create_dir_all(repo_path.join("docs"))?;
+        std::fs::write(repo_path.join("README.md"), "readme")?;
+        std::fs::write(repo_path.join("src/lib.rs"), "lib")?;
+        std::fs::write(repo_path.join("src/bin/main.rs"), "main")?;
+        std::fs::write(repo_path.join("docs/guide.md"), "guide")?;
+
+        let files: Vec<String> = [
+            "README.md",
+            "src/lib.rs",
+            "src/bin/main.rs",
+            "docs/guide.md",
+        ]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+        AddCommand::add(repo_path, &files, AddOptions::default())?;
+        let first = CommitCommand::commit(repo_path, "Initial commit", CommitOptions::default())?;
+
+        let store = ObjectStore::new(repo_path.join(".git-rs/objects"));
+        let root = store.load_object(&first.tree_hash)?;
+        let root = root.as_tree().unwrap();
+        let names: Vec<&str> = root.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["README.md", "docs", "src"]);
+        assert!(root.entries.iter().all(|e| !e.name.contains('/')));
+        assert_eq!(root.find_entry("src").unwrap().mode, FileMode::Directory);
+
+        let flattened = store.flatten_tree(&first.tree_hash)?;
+        let paths: Vec<String> = flattened
+            .keys()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "README.md",
+                "docs/guide.md",
+                "src/bin/main.rs",
+                "src/lib.rs"
+            ]
+        );
+
+        // Changing one file only changes the trees on its path
+        std::fs::write(repo_path.join("src/lib.rs"), "lib v2")?;
+        AddCommand::add(
+            repo_path,
+            &["src/lib.rs".to_string()],
+            AddOptions::default(),
+        )?;
+        let second = CommitCommand::commit(repo_path, "Update lib", CommitOptions::default())?;
+
+        let old_root = store.load_object(&first.tree_hash)?;
+        let new_root = store.load_object(&second.tree_hash)?;
+        let (old_root, new_root) = (old_root.as_tree().unwrap(), new_root.as_tree().unwrap());
+        assert_eq!(old_root.find_entry("docs"), new_root.find_entry("docs"));
+        assert_ne!(old_root.find_entry("src"), new_root.find_entry("src"));
+
+        let old_src = store.load_object(&old_root.find_entry("src").unwrap().hash)?;
+        let new_src = store.load_object(&new_root.find_entry("src").unwrap().hash)?;
+        assert_eq!(
+            old_src.as_tree().unwrap().find_entry("bin"),
+            new_src.as_tree().unwrap().find_entry("bin")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tree_matches_git_write_tree() -> crate::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo_path = temp_dir.path();
+        InitCommand::init(Some(repo_path))?;
+
+        // A file and a directory that share a prefix
+        std::fs::create_dir_all(repo_path.join("foo"))?;
+        std::fs::create_dir_all(repo_path.join("a"))?;
+        std::fs::write(repo_path.join("foo/inner"), "x\n")?;
+        std::fs::write(repo_path.join("foo.txt"), "y\n")?;
+        std::fs::write(repo_path.join("foo-bar"), "z\n")?;
+        std::fs::write(repo_path.join("foo0"), "w\n")?;
+        std::fs::write(repo_path.join("a/b"), "v\n")?;
+
+        let files: Vec<String> = ["foo", "foo.txt", "foo-bar", "foo0", "a"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        AddCommand::add(repo_path, &files, AddOptions::default())?;
+        let result = CommitCommand::commit(repo_path, "Initial commit", CommitOptions::default())?;
+
+        // `git add . && git write-tree` on the same files
+        assert_eq!(
+            result.tree_hash.to_hex(),
+            "b9d56ce7bf20dd095b5ad080e52aba7d14c76dca"
+        );
+
+        let store = ObjectStore::new(repo_path.join(".git-rs/objects"));
+        let root = store.load_object(&result.tree_hash)?;
+        let names: Vec<&str> = root
+            .as_tree()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "foo-bar", "foo.txt", "foo", "foo0"]);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_keeps_symlinks_exec_bits_and_gitlinks() -> crate::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new()?;
+        let repo_path = temp_dir.path();
+        InitCommand::init(Some(repo_path))?;
+
+        std::fs::write(repo_path.join("run.sh"), "#!/bin/sh\n")?;
+        std::fs::set_permissions(
+            repo_path.join("run.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )?;
+        std::os::unix::fs::symlink("run.sh", repo_path.join("link"))?;
+
+        // A nested repository is recorded as a gitlink to its HEAD commit
+        let submodule_head = "0123456789abcdef0123456789abcdef01234567";
+        let sub_git = repo_path.join("vendor/lib/.git");
+        std::fs::create_dir_all(sub_git.join("refs/heads"))?;
+        std::fs::write(sub_git.join("HEAD"), "ref: refs/heads/main\n")?;
+        std::fs::write(
+            sub_git.join("refs/heads/main"),
+            format!("{}\n", submodule_head),
+        )?;
+        std::fs::write(repo_path.join("vendor/lib/inner.txt"), "not ours")?;
+
+        AddCommand::add(repo_path, &[".".to_string()], AddOptions::default())?;
+        let commit = CommitCommand::commit(repo_path, "Modes", CommitOptions::default())?;
+
+        let store = ObjectStore::new(repo_path.join(".git-rs/objects"));
+        let files = store.flatten_tree(&commit.tree_hash)?;
+        let modes: Vec<(String, String)> = files
+            .iter()
+            .map(|(path, entry)| (path.to_string_lossy().to_string(), entry.mode.as_octal()))
+            .collect();
+        assert_eq!(
+            modes,
+            vec![
+                ("link".to_string(), "120000".to_string()),
+                ("run.sh".to_string(), "100755".to_string()),
+                ("vendor/lib".to_string(), "160000".to_string()),
+            ]
+        );
+        assert_eq!(
+            files[Path::new("vendor/lib")].hash,
+            ObjectHash::from_hex(submodule_head).unwrap()
+        );
+
+        let link = store.load_object(&files[Path::new("link")].hash)?;
+        assert_eq!(link.as_blob().unwrap().content, b"run.sh");
+
+        Ok(())
+    }
 }
@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use crate::application::commit::CommitCommand;
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+use crate::infrastructure::revision::RevisionResolver;
+
+/// Git Commit-Tree Use Case
+///
+/// This implements the `git commit-tree` plumbing command: creating a commit
+/// object for an existing tree, with any parents, without touching refs.
+///
+/// ## Building History by Hand:
+/// ```text
+/// $ tree=$(git-rs write-tree)
+/// $ commit=$(git-rs commit-tree $tree -p HEAD -m "Message")
+/// $ git-rs update-ref refs/heads/main $commit
+/// ```
+///
+/// ## Educational Insights:
+/// - **Commits Are Just Objects**: A commit names a tree, zero or more
+///   parents, who wrote it and why; nothing more
+/// - **Merges Are Commits With Two Parents**: Passing `-p` twice is all a
+///   merge commit takes
+/// - **Nothing Moves**: The new commit is unreachable until a ref is pointed
+///   at it with `update-ref`
+pub struct CommitTreeCommand;
+
+impl CommitTreeCommand {
+    /// Create a commit for `tree` and return its id
+    ///
+    /// # Arguments
+    /// * `repo_path` - Path to the repository root
+    /// * `tree` - Name of the tree to commit
+    /// * `parents` - Names of the parent commits (`-p`), in order
+    /// * `messages` - Message paragraphs (`-m`), joined by blank lines
+    /// * `git_compat` - Git compatibility mode
+    pub fn commit_tree_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        tree: &str,
+        parents: &[String],
+        messages: &[String],
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectHash> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        let object_store = ObjectStore::for_repository(&repo);
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let resolver = RevisionResolver::new(&object_store, &ref_store);
+
+        let tree_hash = resolver.require(tree)?;
+        let (tree_type, _) = object_store.load_raw_object(&tree_hash)?;
+        if tree_type != GitObjectType::Tree {
+            return Err(format!("{} is not a valid 'tree' object", tree).into());
+        }
+
+        let mut parent_hashes: Vec<ObjectHash> = Vec::new();
+        for parent in parents {
+            let hash = object_store
+                .peel_to_commit(&resolver.require(parent)?)
+                .map_err(|_| format!("{} is not a valid 'commit' object", parent))?;
+            // Like Git, a parent given twice is only recorded once
+            if !parent_hashes.contains(&hash) {
+                parent_hashes.push(hash);
+            }
+        }
+
+        if messages.is_empty() {
+            return Err("commit message required (-m)".into());
+        }
+        let mut message = messages.join("\n\n");
+        if !message.ends_with('\n') {
+            message.push('\n');
+        }
+
+        let (name, email) = CommitCommand::get_git_config()?;
+        let author = Signature::new(name, email);
+        let commit = CommitObject::new(tree_hash, parent_hashes, author, message);
+
+        object_store.store_object(&GitObject::Commit(commit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::InitCommand;
+    use tempfile::TempDir;
+
+    const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    #[test]
+    fn test_commit_tree_builds_history() {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+        store
+            .store_object(&GitObject::Tree(TreeObject::default()))
+            .unwrap();
+
+        let commit_tree = |parents: &[String], messages: &[&str]| {
+            let messages: Vec<String> = messages.iter().map(|m| m.to_string()).collect();
+            CommitTreeCommand::commit_tree_with_compat(
+                temp_dir.path(),
+                EMPTY_TREE,
+                parents,
+                &messages,
+                GitCompatMode::Educational,
+            )
+        };
+
+        let root = commit_tree(&[], &["Root", "Second paragraph"]).unwrap();
+        let root_commit = store.load_object(&root).unwrap();
+        let root_commit = root_commit.as_commit().unwrap();
+        assert!(root_commit.parents.is_empty());
+        assert_eq!(root_commit.tree.to_hex(), EMPTY_TREE);
+        assert_eq!(root_commit.message, "Root\n\nSecond paragraph\n");
+
+        let abbreviated = root.to_short_hex(7);
+        let child = commit_tree(&[abbreviated.clone(), abbreviated], &["Child"]).unwrap();
+        let child_commit = store.load_object(&child).unwrap();
+        assert_eq!(child_commit.as_commit().unwrap().parents, vec![root]);
+
+        // The tree must be a tree and parents must be commits
+        assert!(commit_tree(&[EMPTY_TREE.to_string()], &["Bad parent"]).is_err());
+        assert!(CommitTreeCommand::commit_tree_with_compat(
+            temp_dir.path(),
+            &root.to_hex(),
+            &[],
+            &["Bad tree".to_string()],
+            GitCompatMode::Educational,
+        )
+        .is_err());
+        assert!(commit_tree(&[], &[]).is_err());
+    }
+}
@@ -90,17 +90,39 @@ pub struct FileDiff {
     /// Type of change
     pub change_type: FileChangeType,
     /// Hash of old version (if exists)
-    pub old_hash: Option<String>,
+    pub old_hash: Option<ObjectHash>,
     /// Hash of new version (if exists)
-    pub new_hash: Option<String>,
-    /// File mode
+    pub new_hash: Option<ObjectHash>,
+    /// File mode (of the new version, or the old one for deletions)
     pub mode: String,
+    /// Mode of old version (if exists)
+    pub old_mode: Option<String>,
+    /// Mode of new version (if exists)
+    pub new_mode: Option<String>,
     /// Diff chunks
     pub chunks: Vec<DiffChunk>,
     /// Whether this is a binary file
     pub is_binary: bool,
 }
 
+/// One side of a file diff
+#[derive(Debug)]
+enum DiffContent {
+    /// The whole content, read into memory
+    Loaded(Vec<u8>),
+    /// Above `core.bigFileThreshold`; only the hash is compared
+    TooLarge,
+}
+
+impl DiffContent {
+    fn to_text(&self) -> String {
+        match self {
+            DiffContent::Loaded(bytes) => String::from_utf8_lossy(bytes).to_string(),
+            DiffContent::TooLarge => String::new(),
+        }
+    }
+}
+
 /// Type of file change
 #[derive(Debug, PartialEq)]
 pub enum FileChangeType {
@@ -158,27 +180,36 @@ impl DiffCommand {
     /// * `Ok(DiffResult)` - The diff information
     /// * `Err(...)` - If diff failed
     pub fn diff<P: AsRef<Path>>(repo_path: P, options: DiffOptions) -> crate::Result<DiffResult> {
-        let repo_path = repo_path.as_ref();
-        let repo = GitRepository::new(repo_path);
+        Self::diff_with_compat(repo_path, options, GitCompatMode::Educational)
+    }
+
+    /// Show differences, with the repository layout chosen by `git_compat`
+    pub fn diff_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        options: DiffOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<DiffResult> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
 
         // Verify this is a Git repository
         if !repo.is_repository() {
             return Err(format!(
                 "Not a git repository (or any of the parent directories): {}",
-                repo_path.display()
+                repo.git_dir().display()
             )
             .into());
         }
 
+        // One store for the whole diff, so its object cache is shared
+        let object_store = ObjectStore::for_repository(&repo);
+
         let mut file_diffs = Vec::new();
         let mut lines_added = 0;
         let mut lines_removed = 0;
 
-        let git_dir = repo_path.join(".git-rs");
-
         if options.cached {
             // Compare staged vs committed (git diff --cached)
-            let file_diffs_result = Self::diff_staged_vs_committed(&git_dir)?;
+            let file_diffs_result = Self::diff_staged_vs_committed(&repo, &object_store)?;
             for diff in file_diffs_result {
                 lines_added += diff
                     .chunks
@@ -204,7 +235,7 @@ impl DiffCommand {
             }
         } else {
             // Compare working vs staged (git diff)
-            let file_diffs_result = Self::diff_working_vs_staged(repo_path, &git_dir)?;
+            let file_diffs_result = Self::diff_working_vs_staged(&repo, &object_store)?;
             for diff in file_diffs_result {
                 lines_added += diff
                     .chunks
@@ -241,67 +272,93 @@ impl DiffCommand {
     }
 
     /// Compare working directory vs staged files
-    fn diff_working_vs_staged(repo_path: &Path, git_dir: &Path) -> crate::Result<Vec<FileDiff>> {
+    fn diff_working_vs_staged(
+        repo: &GitRepository,
+        object_store: &ObjectStore,
+    ) -> crate::Result<Vec<FileDiff>> {
         let mut diffs = Vec::new();
 
         // Load index
-        let index_store = IndexStore::new(git_dir.join("git-rs-index"));
-        let index = index_store.load_index()?;
+        let index_store = IndexStore::new(repo.index_path());
+        let mut index = index_store.load_index()?;
+        let threshold = ConfigStore::new(repo.config_path()).big_file_threshold()?;
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
 
         // Get working directory files
-        let working_files = Self::get_working_directory_files(repo_path)?;
+        let working_files = Self::get_working_directory_files(repo.root_path())?;
+
+        // Only files whose stat data changed since they were staged are read
+        let refresh = working_tree.refresh_index(object_store, &mut index)?;
+        if refresh.changed {
+            index_store.update_index_if_able(&index);
+        }
 
         // Compare each staged file with working directory version
         for (path, entry) in &index.entries {
-            let working_path = repo_path.join(path);
-
-            if working_path.exists() {
-                // File exists in both working directory and index
-                let working_content = fs::read(&working_path)?;
-                let working_hash = Self::calculate_content_hash(&working_content);
-
-                if working_hash != entry.hash.to_string() {
-                    // File is modified
+            match refresh.files.get(path) {
+                Some(&(working_mode, working_hash)) => {
+                    // File exists in both working directory and index
+                    if working_hash != entry.hash || working_mode != entry.mode {
+                        // File content or mode is modified
+                        let staged_content = Self::get_object_content(
+                            object_store,
+                            entry.mode,
+                            &entry.hash,
+                            threshold,
+                        )?;
+                        let working_content = Self::read_working_file(
+                            &working_tree,
+                            path,
+                            working_mode,
+                            &working_hash,
+                            threshold,
+                        )?;
+                        let diff = Self::create_file_diff(
+                            path.clone(),
+                            Some(staged_content),
+                            Some(working_content),
+                            Some((entry.mode, entry.hash)),
+                            Some((working_mode, working_hash)),
+                            FileChangeType::Modified,
+                        )?;
+                        diffs.push(diff);
+                    }
+                }
+                _ => {
+                    // File was deleted from working directory
                     let staged_content =
-                        Self::get_object_content(git_dir, &entry.hash.to_string())?;
+                        Self::get_object_content(object_store, entry.mode, &entry.hash, threshold)?;
                     let diff = Self::create_file_diff(
                         path.clone(),
                         Some(staged_content),
-                        Some(working_content),
-                        Some(entry.hash.to_string()),
-                        Some(working_hash),
-                        FileChangeType::Modified,
+                        None,
+                        Some((entry.mode, entry.hash)),
+                        None,
+                        FileChangeType::Deleted,
                     )?;
                     diffs.push(diff);
                 }
-            } else {
-                // File was deleted from working directory
-                let staged_content = Self::get_object_content(git_dir, &entry.hash.to_string())?;
-                let diff = Self::create_file_diff(
-                    path.clone(),
-                    Some(staged_content),
-                    None,
-                    Some(entry.hash.to_string()),
-                    None,
-                    FileChangeType::Deleted,
-                )?;
-                diffs.push(diff);
             }
         }
 
         // Check for untracked files (exist in working directory but not in index)
         for (path, _) in working_files {
-            if !index.entries.contains_key(&path) {
-                let working_path = repo_path.join(&path);
-                let working_content = fs::read(&working_path)?;
-                let working_hash = Self::calculate_content_hash(&working_content);
+            if !index.is_staged(&path) {
+                let (working_mode, working_hash) = working_tree.hash_path(object_store, &path)?;
+                let working_content = Self::read_working_file(
+                    &working_tree,
+                    &path,
+                    working_mode,
+                    &working_hash,
+                    threshold,
+                )?;
 
                 let diff = Self::create_file_diff(
                     path,
                     None,
                     Some(working_content),
                     None,
-                    Some(working_hash),
+                    Some((working_mode, working_hash)),
                     FileChangeType::Added,
                 )?;
                 diffs.push(diff);
@@ -312,43 +369,49 @@ impl DiffCommand {
     }
 
     /// Compare staged files vs committed files
-    fn diff_staged_vs_committed(git_dir: &Path) -> crate::Result<Vec<FileDiff>> {
+    fn diff_staged_vs_committed(
+        repo: &GitRepository,
+        object_store: &ObjectStore,
+    ) -> crate::Result<Vec<FileDiff>> {
         let mut diffs = Vec::new();
 
         // Load index
-        let index_store = IndexStore::new(git_dir.join("git-rs-index"));
+        let index_store = IndexStore::new(repo.index_path());
         let index = index_store.load_index()?;
+        let threshold = ConfigStore::new(repo.config_path()).big_file_threshold()?;
 
         // Get HEAD commit files
-        let head_files = Self::get_head_commit_files(git_dir)?;
+        let head_files = Self::get_head_commit_files(repo, object_store)?;
 
         // Compare each staged file with committed version
         for (path, entry) in &index.entries {
-            if let Some(head_hash) = head_files.get(path) {
-                if entry.hash.to_string() != *head_hash {
+            if let Some(&(head_mode, head_hash)) = head_files.get(path) {
+                if entry.hash != head_hash || entry.mode != head_mode {
                     // File is modified in staging
                     let staged_content =
-                        Self::get_object_content(git_dir, &entry.hash.to_string())?;
-                    let committed_content = Self::get_object_content(git_dir, head_hash)?;
+                        Self::get_object_content(object_store, entry.mode, &entry.hash, threshold)?;
+                    let committed_content =
+                        Self::get_object_content(object_store, head_mode, &head_hash, threshold)?;
                     let diff = Self::create_file_diff(
                         path.clone(),
                         Some(committed_content),
                         Some(staged_content),
-                        Some(head_hash.clone()),
-                        Some(entry.hash.to_string()),
+                        Some((head_mode, head_hash)),
+                        Some((entry.mode, entry.hash)),
                         FileChangeType::Modified,
                     )?;
                     diffs.push(diff);
                 }
             } else {
                 // File is new in staging (not in HEAD)
-                let staged_content = Self::get_object_content(git_dir, &entry.hash.to_string())?;
+                let staged_content =
+                    Self::get_object_content(object_store, entry.mode, &entry.hash, threshold)?;
                 let diff = Self::create_file_diff(
                     path.clone(),
                     None,
                     Some(staged_content),
                     None,
-                    Some(entry.hash.to_string()),
+                    Some((entry.mode, entry.hash)),
                     FileChangeType::Added,
                 )?;
                 diffs.push(diff);
@@ -356,14 +419,15 @@ impl DiffCommand {
         }
 
         // Check for files deleted from staging (in HEAD but not in index)
-        for (path, head_hash) in head_files {
-            if !index.entries.contains_key(&path) {
-                let committed_content = Self::get_object_content(git_dir, &head_hash)?;
+        for (path, (head_mode, head_hash)) in head_files {
+            if !index.is_staged(&path) {
+                let committed_content =
+                    Self::get_object_content(object_store, head_mode, &head_hash, threshold)?;
                 let diff = Self::create_file_diff(
                     path,
                     Some(committed_content),
                     None,
-                    Some(head_hash),
+                    Some((head_mode, head_hash)),
                     None,
                     FileChangeType::Deleted,
                 )?;
@@ -374,27 +438,20 @@ impl DiffCommand {
         Ok(diffs)
     }
 
-    /// Calculate hash for content (simple SHA-1 of blob format)
-    fn calculate_content_hash(content: &[u8]) -> String {
-        use sha1::{Digest, Sha1};
-
-        let header = format!("blob {}{}", content.len(), '\0');
-        let mut hasher = Sha1::new();
-        hasher.update(header.as_bytes());
-        hasher.update(content);
-        let result = hasher.finalize();
-        hex::encode(result)
-    }
-
     /// Get all files in the working directory
+    ///
+    /// Symlinks are listed without being followed, and a nested repository
+    /// is listed as a single entry (its gitlink) rather than scanned.
     fn get_working_directory_files(work_dir: &Path) -> crate::Result<HashMap<PathBuf, ()>> {
         let mut files = HashMap::new();
-        Self::scan_directory_recursive(work_dir, work_dir, &mut files)?;
+        let working_tree = WorkingTree::new(work_dir.to_path_buf());
+        Self::scan_directory_recursive(&working_tree, work_dir, work_dir, &mut files)?;
         Ok(files)
     }
 
     /// Recursively scan directory for files
     fn scan_directory_recursive(
+        working_tree: &WorkingTree,
         dir: &Path,
         repo_root: &Path,
         files: &mut HashMap<PathBuf, ()>,
@@ -403,43 +460,48 @@ impl DiffCommand {
             let entry = entry?;
             let path = entry.path();
 
-            // Skip git-rs directory
-            if path.file_name().is_some_and(|name| name == ".git-rs") {
+            // Skip the repository directory, in either layout
+            if path
+                .file_name()
+                .is_some_and(|name| name == ".git-rs" || name == ".git")
+            {
                 continue;
             }
 
-            if path.is_file() {
-                // Convert to relative path from repo root
-                let relative_path = path
-                    .strip_prefix(repo_root)
-                    .map_err(|_| format!("Path not within repository: {}", path.display()))?;
-                files.insert(relative_path.to_path_buf(), ());
-            } else if path.is_dir() {
-                Self::scan_directory_recursive(&path, repo_root, files)?;
+            // Convert to relative path from repo root
+            let relative_path = path
+                .strip_prefix(repo_root)
+                .map_err(|_| format!("Path not within repository: {}", path.display()))?;
+
+            match working_tree.file_mode(relative_path)? {
+                Some(FileMode::Directory) => {
+                    Self::scan_directory_recursive(working_tree, &path, repo_root, files)?;
+                }
+                Some(_) => {
+                    files.insert(relative_path.to_path_buf(), ());
+                }
+                None => {}
             }
         }
         Ok(())
     }
 
-    /// Get all files from HEAD commit
-    fn get_head_commit_files(git_dir: &Path) -> crate::Result<HashMap<PathBuf, String>> {
+    /// Get all files from HEAD commit with their modes and hashes
+    fn get_head_commit_files(
+        repo: &GitRepository,
+        object_store: &ObjectStore,
+    ) -> crate::Result<HashMap<PathBuf, (FileMode, ObjectHash)>> {
         let mut files = HashMap::new();
 
         // Read HEAD reference
-        let ref_store = RefStore::new(git_dir.to_path_buf());
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
         if let Some(head_commit_hash) = ref_store.get_head()? {
             // Get commit object
-            let object_store = ObjectStore::new(git_dir.join("objects"));
             let commit_obj = object_store.load_object(&head_commit_hash)?;
 
             if let GitObject::Commit(commit) = commit_obj {
                 // Get tree object and extract files
-                Self::extract_tree_files(
-                    &object_store,
-                    &commit.tree.to_string(),
-                    &mut files,
-                    &PathBuf::new(),
-                )?;
+                Self::extract_tree_files(object_store, &commit.tree, &mut files, &PathBuf::new())?;
             }
         }
 
@@ -447,64 +509,116 @@ impl DiffCommand {
     }
 
     /// Extract all files from a tree object recursively
+    ///
+    /// Subdirectories are separate tree objects (mode 040000 entries), so
+    /// `src/main.rs` is found by following `src` into its own tree.
     fn extract_tree_files(
         object_store: &ObjectStore,
-        tree_hash: &str,
-        files: &mut HashMap<PathBuf, String>,
+        tree_hash: &ObjectHash,
+        files: &mut HashMap<PathBuf, (FileMode, ObjectHash)>,
         current_path: &Path,
     ) -> crate::Result<()> {
-        let tree_hash_obj = ObjectHash::new(tree_hash.to_string());
-        let tree_obj = object_store.load_object(&tree_hash_obj)?;
-
-        if let GitObject::Tree(tree) = tree_obj {
-            for entry in &tree.entries {
-                let entry_path = current_path.join(&entry.name);
-
-                match entry.mode {
-                    FileMode::Regular | FileMode::Executable => {
-                        // It's a file
-                        files.insert(entry_path, entry.hash.to_string());
-                    }
-                    FileMode::Directory => {
-                        // It's a directory (tree)
-                        Self::extract_tree_files(
-                            object_store,
-                            &entry.hash.to_string(),
-                            files,
-                            &entry_path,
-                        )?;
-                    }
-                    _ => {
-                        // Handle other types if needed (symlinks, etc.)
-                    }
-                }
-            }
+        for (path, entry) in object_store.flatten_tree(tree_hash)? {
+            files.insert(current_path.join(path), (entry.mode, entry.hash));
         }
 
         Ok(())
     }
 
-    /// Get content of an object by hash
-    fn get_object_content(git_dir: &Path, hash: &str) -> crate::Result<Vec<u8>> {
-        let object_store = ObjectStore::new(git_dir.join("objects"));
-        let hash_obj = ObjectHash::new(hash.to_string());
-        let obj = object_store.load_object(&hash_obj)?;
+    /// Get content of a blob, unless it is above the big file threshold
+    ///
+    /// A gitlink points at a commit in another repository, so it is shown
+    /// the way Git shows it: as a one-line "Subproject commit <id>".
+    fn get_object_content(
+        object_store: &ObjectStore,
+        mode: FileMode,
+        hash: &ObjectHash,
+        threshold: u64,
+    ) -> crate::Result<DiffContent> {
+        if mode == FileMode::Gitlink {
+            return Ok(Self::subproject_content(hash));
+        }
+
+        // Opening only reads the header; the content is loaded through the
+        // object cache, so a blob shown by several diffs is inflated once
+        let reader = object_store.open_object(hash)?;
+        if reader.object_type() != &GitObjectType::Blob {
+            return Err(format!("Object {} is not a blob", hash).into());
+        }
+        if reader.size() > threshold {
+            return Ok(DiffContent::TooLarge);
+        }
+        drop(reader);
 
-        match obj {
-            GitObject::Blob(blob) => Ok(blob.content),
+        match object_store.load_object(hash)? {
+            GitObject::Blob(blob) => Ok(DiffContent::Loaded(blob.content)),
             _ => Err(format!("Object {} is not a blob", hash).into()),
         }
     }
 
+    /// Read a working directory entry, unless it is above the big file threshold
+    ///
+    /// For a symlink this is its target, for a nested repository its HEAD.
+    fn read_working_file(
+        working_tree: &WorkingTree,
+        path: &Path,
+        mode: FileMode,
+        hash: &ObjectHash,
+        threshold: u64,
+    ) -> crate::Result<DiffContent> {
+        match mode {
+            FileMode::Symlink => Ok(DiffContent::Loaded(working_tree.link_target(path)?)),
+            FileMode::Gitlink => Ok(Self::subproject_content(hash)),
+            _ => {
+                let full_path = working_tree.root().join(path);
+                if fs::metadata(&full_path)?.len() > threshold {
+                    return Ok(DiffContent::TooLarge);
+                }
+                Ok(DiffContent::Loaded(fs::read(&full_path)?))
+            }
+        }
+    }
+
+    fn subproject_content(hash: &ObjectHash) -> DiffContent {
+        DiffContent::Loaded(format!("Subproject commit {}\n", hash).into_bytes())
+    }
+
     /// Create a file diff from old and new content
+    ///
+    /// `old` and `new` are the (mode, hash) of each side. When only the
+    /// mode differs there is no content to compare.
     fn create_file_diff(
         path: PathBuf,
-        old_content: Option<Vec<u8>>,
-        new_content: Option<Vec<u8>>,
-        old_hash: Option<String>,
-        new_hash: Option<String>,
+        old_content: Option<DiffContent>,
+        new_content: Option<DiffContent>,
+        old: Option<(FileMode, ObjectHash)>,
+        new: Option<(FileMode, ObjectHash)>,
         change_type: FileChangeType,
     ) -> crate::Result<FileDiff> {
+        let old_mode = old.as_ref().map(|(mode, _)| mode.as_octal());
+        let new_mode = new.as_ref().map(|(mode, _)| mode.as_octal());
+        let old_hash = old.map(|(_, hash)| hash);
+        let new_hash = new.map(|(_, hash)| hash);
+        let mode = new_mode
+            .clone()
+            .or_else(|| old_mode.clone())
+            .unwrap_or_default();
+
+        if old_hash.is_some() && old_hash == new_hash {
+            // Mode change only
+            return Ok(FileDiff {
+                path,
+                change_type,
+                old_hash,
+                new_hash,
+                mode,
+                old_mode,
+                new_mode,
+                chunks: vec![],
+                is_binary: false,
+            });
+        }
+
         // Check if files are binary
         let is_binary =
             Self::is_binary_content(&old_content) || Self::is_binary_content(&new_content);
@@ -514,12 +628,8 @@ impl DiffCommand {
             vec![]
         } else {
             // Convert to text and create diff
-            let old_text = old_content
-                .map(|c| String::from_utf8_lossy(&c).to_string())
-                .unwrap_or_default();
-            let new_text = new_content
-                .map(|c| String::from_utf8_lossy(&c).to_string())
-                .unwrap_or_default();
+            let old_text = old_content.map(|c| c.to_text()).unwrap_or_default();
+            let new_text = new_content.map(|c| c.to_text()).unwrap_or_default();
 
             Self::create_unified_diff(&old_text, &new_text)?
         };
@@ -529,20 +639,25 @@ impl DiffCommand {
             change_type,
             old_hash,
             new_hash,
-            mode: "100644".to_string(), // TODO: Get actual file mode
+            mode,
+            old_mode,
+            new_mode,
             chunks,
             is_binary,
         })
     }
 
     /// Check if content is binary
-    fn is_binary_content(content: &Option<Vec<u8>>) -> bool {
-        if let Some(bytes) = content {
-            // Simple heuristic: if we find null bytes in first 8KB, consider it binary
-            let check_size = std::cmp::min(bytes.len(), 8192);
-            bytes[..check_size].contains(&0)
-        } else {
-            false
+    fn is_binary_content(content: &Option<DiffContent>) -> bool {
+        match content {
+            Some(DiffContent::Loaded(bytes)) => {
+                // Simple heuristic: if we find null bytes in first 8KB, consider it binary
+                let check_size = std::cmp::min(bytes.len(), 8192);
+                bytes[..check_size].contains(&0)
+            }
+            // Files too large to load are always reported as binary, like Git does
+            Some(DiffContent::TooLarge) => true,
+            None => false,
         }
     }
 
@@ -693,7 +808,7 @@ impl DiffResult {
                 );
                 println!("new file mode {}", file_diff.mode);
                 if let Some(hash) = &file_diff.new_hash {
-                    println!("index 0000000..{} {}", &hash[..7], file_diff.mode);
+                    println!("index 0000000..{} {}", hash.to_short_hex(7), file_diff.mode);
                 }
                 println!("--- /dev/null");
                 println!("+++ b/{}", file_diff.path.display());
@@ -706,7 +821,7 @@ impl DiffResult {
                 );
                 println!("deleted file mode {}", file_diff.mode);
                 if let Some(hash) = &file_diff.old_hash {
-                    println!("index {}..0000000 {}", &hash[..7], file_diff.mode);
+                    println!("index {}..0000000 {}", hash.to_short_hex(7), file_diff.mode);
                 }
                 println!("--- a/{}", file_diff.path.display());
                 println!("+++ /dev/null");
@@ -717,14 +832,33 @@ impl DiffResult {
                     file_diff.path.display(),
                     file_diff.path.display()
                 );
+                let mode_changed = file_diff.old_mode != file_diff.new_mode;
+                if let (true, Some(old_mode), Some(new_mode)) =
+                    (mode_changed, &file_diff.old_mode, &file_diff.new_mode)
+                {
+                    println!("old mode {}", old_mode);
+                    println!("new mode {}", new_mode);
+                }
                 if let (Some(old_hash), Some(new_hash)) = (&file_diff.old_hash, &file_diff.new_hash)
                 {
-                    println!(
-                        "index {}..{} {}",
-                        &old_hash[..7],
-                        &new_hash[..7],
-                        file_diff.mode
-                    );
+                    if old_hash == new_hash {
+                        // Nothing but the mode changed
+                        return;
+                    }
+                    if mode_changed {
+                        println!(
+                            "index {}..{}",
+                            old_hash.to_short_hex(7),
+                            new_hash.to_short_hex(7)
+                        );
+                    } else {
+                        println!(
+                            "index {}..{} {}",
+                            old_hash.to_short_hex(7),
+                            new_hash.to_short_hex(7),
+                            file_diff.mode
+                        );
+                    }
                 }
                 println!("--- a/{}", file_diff.path.display());
                 println!("+++ b/{}", file_diff.path.display());
@@ -856,16 +990,144 @@ mod tests {
 
     #[test]
     fn test_hash_calculation() {
-        let content = b"Hello World\n";
-        let hash = DiffCommand::calculate_content_hash(content);
+        let (_temp_dir, repo_path) = setup_test_repo();
+        let file_path = repo_path.join("hello.txt");
+        fs::write(&file_path, b"Hello World\n").unwrap();
+
+        let objects_dir = repo_path.join(".git-rs/objects");
+        let store = ObjectStore::with_format(objects_dir.clone(), ObjectFormat::Sha1);
+        let hash = store.hash_file(&file_path).unwrap().to_string();
 
         // This should match git's blob hash calculation
-        // echo -n "Hello World" | git hash-object --stdin
-        // But we need to account for the blob header format: "blob <size>\0<content>"
-        assert_eq!(hash.len(), 40); // SHA-1 is 40 hex characters
+        // echo "Hello World" | git hash-object --stdin
+        assert_eq!(hash, "557db03de997c86a4a028e1ebd3a1ceb225be238");
+
+        // SHA-256 repositories use 64 hex characters
+        let store = ObjectStore::with_format(objects_dir, ObjectFormat::Sha256);
+        let hash = store.hash_file(&file_path).unwrap().to_string();
+        assert_eq!(hash.len(), 64);
         assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
     }
 
+    #[test]
+    fn test_diff_large_file_is_not_loaded() {
+        let (_temp_dir, repo_path) = setup_test_repo();
+        let git_dir = repo_path.join(".git-rs");
+        let config = fs::read_to_string(git_dir.join("config")).unwrap();
+        fs::write(
+            git_dir.join("config"),
+            config.replace("[core]\n", "[core]\n\tbigFileThreshold = 1k\n"),
+        )
+        .unwrap();
+
+        let large_text = "a text line\n".repeat(200);
+        fs::write(repo_path.join("large.txt"), &large_text).unwrap();
+        fs::write(repo_path.join("small.txt"), "small\n").unwrap();
+
+        let result = DiffCommand::diff(&repo_path, DiffOptions::default()).unwrap();
+        assert_eq!(result.files_changed, 2);
+
+        let large = result
+            .file_diffs
+            .iter()
+            .find(|d| d.path == Path::new("large.txt"))
+            .unwrap();
+        assert!(large.is_binary);
+        assert!(large.chunks.is_empty());
+
+        let small = result
+            .file_diffs
+            .iter()
+            .find(|d| d.path == Path::new("small.txt"))
+            .unwrap();
+        assert!(!small.is_binary);
+    }
+
+    #[test]
+    fn test_diff_cached_with_nested_directories() {
+        let (_temp_dir, repo_path) = setup_test_repo();
+
+        fs::create_dir_all(repo_path.join("src/lib")).unwrap();
+        fs::write(repo_path.join("README.md"), "# Test\n").unwrap();
+        fs::write(repo_path.join("src/lib/mod.rs"), "// module\n").unwrap();
+
+        let files = vec!["README.md".to_string(), "src".to_string()];
+        crate::application::AddCommand::add(
+            &repo_path,
+            &files,
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+        crate::application::CommitCommand::commit(
+            &repo_path,
+            "Initial commit",
+            crate::application::CommitOptions::default(),
+        )
+        .unwrap();
+
+        let cached = DiffOptions {
+            cached: true,
+            ..Default::default()
+        };
+        let result = DiffCommand::diff(&repo_path, cached.clone()).unwrap();
+        assert_eq!(result.files_changed, 0);
+
+        fs::write(repo_path.join("src/lib/mod.rs"), "// changed module\n").unwrap();
+        crate::application::AddCommand::add(
+            &repo_path,
+            &["src/lib/mod.rs".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+
+        let result = DiffCommand::diff(&repo_path, cached).unwrap();
+        assert_eq!(result.files_changed, 1);
+        assert_eq!(result.file_diffs[0].path, PathBuf::from("src/lib/mod.rs"));
+        assert_eq!(result.file_diffs[0].change_type, FileChangeType::Modified);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diff_shows_mode_changes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_temp_dir, repo_path) = setup_test_repo();
+        fs::write(repo_path.join("run.sh"), "echo hi\n").unwrap();
+        std::os::unix::fs::symlink("run.sh", repo_path.join("link")).unwrap();
+        crate::application::AddCommand::add(
+            &repo_path,
+            &[".".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+
+        let result = DiffCommand::diff(&repo_path, DiffOptions::default()).unwrap();
+        assert_eq!(result.files_changed, 0);
+
+        fs::set_permissions(repo_path.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        let result = DiffCommand::diff(&repo_path, DiffOptions::default()).unwrap();
+        assert_eq!(result.files_changed, 1);
+        let file_diff = &result.file_diffs[0];
+        assert_eq!(file_diff.change_type, FileChangeType::Modified);
+        assert_eq!(file_diff.old_mode.as_deref(), Some("100644"));
+        assert_eq!(file_diff.new_mode.as_deref(), Some("100755"));
+        assert_eq!(file_diff.old_hash, file_diff.new_hash);
+        assert!(file_diff.chunks.is_empty());
+
+        // Retargeting a symlink diffs the target path, not the file behind it
+        fs::remove_file(repo_path.join("link")).unwrap();
+        std::os::unix::fs::symlink("other.sh", repo_path.join("link")).unwrap();
+        let result = DiffCommand::diff(&repo_path, DiffOptions::default()).unwrap();
+        let link = result
+            .file_diffs
+            .iter()
+            .find(|d| d.path == Path::new("link"))
+            .unwrap();
+        assert_eq!(link.mode, "120000");
+        assert_eq!(link.chunks[0].lines[0].content, "run.sh");
+        assert_eq!(link.chunks[0].lines[1].content, "other.sh");
+    }
+
     #[test]
     fn test_directory_scanning() {
         let (_temp_dir, repo_path) = setup_test_repo();
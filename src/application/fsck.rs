@@ -0,0 +1,923 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::domain::objects::*;
+use crate::domain::references::HeadRef;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::index_store::IndexStore;
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::pack::PackReader;
+use crate::infrastructure::ref_store::RefStore;
+
+/// Git Fsck Use Case
+///
+/// This implements the `git fsck` command: checking that the object
+/// database is intact and that everything the references need is there.
+///
+/// ## What `git fsck` does:
+/// 1. Re-hashes every loose and packed object and compares the result with
+///    the object's name (and each pack with its trailing checksum)
+/// 2. Validates object syntax: tree modes, names and order, commit and tag
+///    headers
+/// 3. Walks from HEAD, every ref and the index through commits, trees and
+///    tags, reporting anything that is referenced but missing
+/// 4. Reports objects nothing points at as "dangling"
+///
+/// ## Visual Guide - Reachability:
+/// ```text
+/// refs/heads/main ──▶ commit C2 ──▶ commit C1 ──▶ tree ──▶ blob   (reachable)
+///
+///                     commit C3 ──▶ tree'                         (C3 dangling,
+///                                                                  tree' unreachable)
+/// ```
+///
+/// ## Educational Insights:
+/// - **Content Addressing Pays Off**: Because names are hashes of content,
+///   corruption is detected just by hashing again
+/// - **Dangling Is Not Broken**: An amended or reset commit stays in the
+///   database until garbage collection; `--lost-found` keeps such commits
+///   reachable by writing them to `refs/lost-found/<id>`
+pub struct FsckCommand;
+
+/// Options for the fsck command
+#[derive(Debug, Clone, Default)]
+pub struct FsckOptions {
+    /// Write dangling commits to `refs/lost-found/` (`--lost-found`)
+    pub lost_found: bool,
+}
+
+/// A problem found by fsck
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsckIssue {
+    /// The object cannot be read, or its content does not hash to its name
+    Corrupt { hash: ObjectHash, reason: String },
+    /// A pack file whose content does not match its checksum
+    CorruptPack { path: PathBuf, reason: String },
+    /// The object reads fine but is not well formed
+    Invalid {
+        hash: ObjectHash,
+        object_type: GitObjectType,
+        reason: String,
+    },
+    /// Referenced by `referrer` but not in the object database
+    Missing {
+        hash: ObjectHash,
+        object_type: GitObjectType,
+        referrer: String,
+    },
+    /// A reference that does not point at a usable object
+    BadRef { name: String, reason: String },
+    /// Not reachable from any reference, and no other object points at it
+    Dangling {
+        hash: ObjectHash,
+        object_type: GitObjectType,
+    },
+}
+
+impl FsckIssue {
+    /// Dangling objects are normal; everything else means damage
+    pub fn is_error(&self) -> bool {
+        !matches!(self, FsckIssue::Dangling { .. })
+    }
+}
+
+impl std::fmt::Display for FsckIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FsckIssue::Corrupt { hash, reason } => write!(f, "error: {}: {}", hash, reason),
+            FsckIssue::CorruptPack { path, reason } => {
+                write!(f, "error: {}: {}", path.display(), reason)
+            }
+            FsckIssue::Invalid {
+                hash,
+                object_type,
+                reason,
+            } => write!(f, "error in {} {}: {}", object_type, hash, reason),
+            FsckIssue::Missing {
+                hash,
+                object_type,
+                referrer,
+            } => write!(f, "missing {} {} (from {})", object_type, hash, referrer),
+            FsckIssue::BadRef { name, reason } => write!(f, "error: {}: {}", name, reason),
+            FsckIssue::Dangling { hash, object_type } => {
+                write!(f, "dangling {} {}", object_type, hash)
+            }
+        }
+    }
+}
+
+/// Result of an fsck run
+#[derive(Debug, Clone, Default)]
+pub struct FsckResult {
+    /// Number of objects read and re-hashed (loose and packed)
+    pub objects_checked: usize,
+    /// Number of pack files whose checksum was verified
+    pub packs_checked: usize,
+    /// Everything found, in the order it was found
+    pub issues: Vec<FsckIssue>,
+    /// Dangling commits written to `refs/lost-found/`
+    pub lost_found: Vec<ObjectHash>,
+}
+
+impl FsckResult {
+    /// True when nothing is missing or corrupt (dangling objects are fine)
+    pub fn is_ok(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    /// Number of issues that indicate damage
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.is_error()).count()
+    }
+
+    /// Get a summary of the fsck run
+    pub fn summary(&self) -> String {
+        let dangling = self.issues.len() - self.error_count();
+        let mut summary = format!(
+            "🔍 Checked {} object(s) in {} pack(s) and loose storage\n",
+            self.objects_checked, self.packs_checked
+        );
+        if self.is_ok() {
+            summary.push_str("✅ No missing or corrupt objects");
+        } else {
+            summary.push_str(&format!("❌ {} problem(s) found", self.error_count()));
+        }
+        if dangling > 0 {
+            summary.push_str(&format!("\n🪢 {} dangling object(s)", dangling));
+        }
+        if !self.lost_found.is_empty() {
+            summary.push_str(&format!(
+                "\n📥 Wrote {} commit(s) to refs/lost-found/",
+                self.lost_found.len()
+            ));
+        }
+        summary
+    }
+}
+
+/// What the scan learned about each readable object
+#[derive(Default)]
+struct ObjectGraph {
+    /// Type of every object that could be read and hashed correctly
+    types: HashMap<ObjectHash, GitObjectType>,
+    /// Objects each object points at, with the type it expects them to have
+    links: HashMap<ObjectHash, Vec<(ObjectHash, GitObjectType)>>,
+    /// Objects that exist but could not be read or failed the hash check
+    corrupt: HashSet<ObjectHash>,
+}
+
+/// Allowed tree entry modes, exactly as Git writes them
+const TREE_MODES: [&str; 5] = ["100644", "100755", "120000", "40000", "160000"];
+
+impl FsckCommand {
+    /// Check the repository at `repo_path`
+    pub fn fsck<P: AsRef<Path>>(repo_path: P, options: FsckOptions) -> crate::Result<FsckResult> {
+        Self::fsck_with_compat(repo_path, options, GitCompatMode::Educational)
+    }
+
+    /// Check the repository with git compatibility mode
+    ///
+    /// # Arguments
+    /// * `repo_path` - Path to the repository root
+    /// * `options` - Fsck options
+    /// * `git_compat` - Git compatibility mode
+    ///
+    /// # Returns
+    /// * `Ok(FsckResult)` - Everything found (an unhealthy repository is
+    ///   still `Ok`; see `FsckResult::is_ok`)
+    /// * `Err(...)` - If the repository could not be examined at all
+    pub fn fsck_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        options: FsckOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<FsckResult> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        let object_store = ObjectStore::for_repository(&repo);
+        let format = object_store.format();
+        let mut result = FsckResult::default();
+        let mut graph = ObjectGraph::default();
+
+        println!("🔍 Checking objects...");
+
+        // 1. Loose objects
+        let mut loose = object_store.list_loose_objects()?;
+        loose.sort();
+        for hash in loose {
+            result.objects_checked += 1;
+            if hash.as_bytes().len() != format.raw_len() {
+                result.issues.push(FsckIssue::Corrupt {
+                    reason: "file name is not a valid object id".to_string(),
+                    hash,
+                });
+                continue;
+            }
+            match object_store.load_raw_object(&hash) {
+                Ok((object_type, content)) => Self::check_object(
+                    format,
+                    &hash,
+                    object_type,
+                    &content,
+                    &mut graph,
+                    &mut result.issues,
+                ),
+                Err(e) => {
+                    result.issues.push(FsckIssue::Corrupt {
+                        hash,
+                        reason: format!("cannot read loose object: {}", e),
+                    });
+                    graph.corrupt.insert(hash);
+                }
+            }
+        }
+
+        // 2. Packed objects
+        for pack in object_store.packs()? {
+            result.packs_checked += 1;
+            if let Err(reason) = Self::verify_pack_checksum(pack, format) {
+                result.issues.push(FsckIssue::CorruptPack {
+                    path: pack.pack_path().to_path_buf(),
+                    reason,
+                });
+            }
+
+            for hash in pack.index().hashes() {
+                result.objects_checked += 1;
+                let object = pack.read_object(&hash).and_then(|object| {
+                    let (pack_type, content) = object.ok_or("missing from pack")?;
+                    let object_type = pack_type
+                        .type_name()
+                        .ok_or("unresolved delta")?
+                        .parse::<GitObjectType>()?;
+                    Ok((object_type, content))
+                });
+                match object {
+                    Ok((object_type, content)) => Self::check_object(
+                        format,
+                        &hash,
+                        object_type,
+                        &content,
+                        &mut graph,
+                        &mut result.issues,
+                    ),
+                    Err(e) => {
+                        result.issues.push(FsckIssue::Corrupt {
+                            hash,
+                            reason: format!("cannot read packed object: {}", e),
+                        });
+                        graph.corrupt.insert(hash);
+                    }
+                }
+            }
+        }
+
+        // 3. Objects borrowed from alternates are checked too, so connectivity
+        //    through them holds, but other repositories may still use them
+        let mut borrowed = HashSet::new();
+        if !object_store.alternates().is_empty() {
+            for hash in object_store.list_objects()? {
+                if graph.types.contains_key(&hash) || graph.corrupt.contains(&hash) {
+                    continue;
+                }
+                result.objects_checked += 1;
+                borrowed.insert(hash);
+                match object_store.load_raw_object(&hash) {
+                    Ok((object_type, content)) => Self::check_object(
+                        format,
+                        &hash,
+                        object_type,
+                        &content,
+                        &mut graph,
+                        &mut result.issues,
+                    ),
+                    Err(e) => {
+                        result.issues.push(FsckIssue::Corrupt {
+                            hash,
+                            reason: format!("cannot read alternate object: {}", e),
+                        });
+                        graph.corrupt.insert(hash);
+                    }
+                }
+            }
+        }
+
+        // 4. Connectivity from HEAD, refs and the index
+        println!("🔗 Checking connectivity...");
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let reachable = Self::walk_reachable(&repo, &ref_store, format, &graph, &mut result)?;
+
+        // 5. Dangling objects: unreachable, and nothing else points at them
+        let referenced: HashSet<&ObjectHash> = graph
+            .links
+            .values()
+            .flatten()
+            .map(|(hash, _)| hash)
+            .collect();
+        let mut dangling: Vec<(&ObjectHash, &GitObjectType)> = graph
+            .types
+            .iter()
+            .filter(|(hash, _)| {
+                !reachable.contains(*hash)
+                    && !referenced.contains(hash)
+                    && !borrowed.contains(*hash)
+            })
+            .collect();
+        dangling.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (hash, object_type) in dangling {
+            if options.lost_found && *object_type == GitObjectType::Commit {
+                let lost_found_dir = repo.git_dir().join("refs").join("lost-found");
+                fs::create_dir_all(&lost_found_dir)?;
+                fs::write(lost_found_dir.join(hash.to_hex()), format!("{}\n", hash))?;
+                result.lost_found.push(*hash);
+            }
+            result.issues.push(FsckIssue::Dangling {
+                hash: *hash,
+                object_type: object_type.clone(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Re-hash one object, validate its syntax and record its links
+    fn check_object(
+        format: ObjectFormat,
+        hash: &ObjectHash,
+        object_type: GitObjectType,
+        content: &[u8],
+        graph: &mut ObjectGraph,
+        issues: &mut Vec<FsckIssue>,
+    ) {
+        let actual = format.hash_object(&object_type.to_string(), content);
+        if actual != *hash {
+            issues.push(FsckIssue::Corrupt {
+                hash: *hash,
+                reason: format!("hash mismatch, content hashes to {}", actual),
+            });
+            graph.corrupt.insert(*hash);
+            return;
+        }
+
+        let links = match Self::parse_links(format, &object_type, content) {
+            Ok(links) => links,
+            Err(reason) => {
+                issues.push(FsckIssue::Invalid {
+                    hash: *hash,
+                    object_type: object_type.clone(),
+                    reason,
+                });
+                Vec::new()
+            }
+        };
+
+        graph.links.insert(*hash, links);
+        graph.types.insert(*hash, object_type);
+    }
+
+    /// Validate an object's syntax, returning the objects it points at
+    fn parse_links(
+        format: ObjectFormat,
+        object_type: &GitObjectType,
+        content: &[u8],
+    ) -> Result<Vec<(ObjectHash, GitObjectType)>, String> {
+        match object_type {
+            GitObjectType::Blob => Ok(Vec::new()),
+            GitObjectType::Tree => Self::parse_tree_links(format, content),
+            GitObjectType::Commit => Self::parse_commit_links(format, content),
+            GitObjectType::Tag => Self::parse_tag_links(format, content),
+        }
+    }
+
+    /// Tree entries: `<mode> <name>\0<raw id>`, sorted the way Git sorts them
+    fn parse_tree_links(
+        format: ObjectFormat,
+        content: &[u8],
+    ) -> Result<Vec<(ObjectHash, GitObjectType)>, String> {
+        let mut links = Vec::new();
+        let mut previous: Option<TreeEntry> = None;
+        let mut pos = 0;
+
+        while pos < content.len() {
+            let space = content[pos..]
+                .iter()
+                .position(|&b| b == b' ')
+                .ok_or("truncated entry: no space after mode")?;
+            let mode = String::from_utf8_lossy(&content[pos..pos + space]).to_string();
+            pos += space + 1;
+
+            let null = content[pos..]
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or("truncated entry: no null after name")?;
+            let name = String::from_utf8_lossy(&content[pos..pos + null]).to_string();
+            pos += null + 1;
+
+            if pos + format.raw_len() > content.len() {
+                return Err(format!("truncated object id for '{}'", name));
+            }
+            let hash = ObjectHash::from_bytes(&content[pos..pos + format.raw_len()])
+                .map_err(|e| e.to_string())?;
+            pos += format.raw_len();
+
+            if !TREE_MODES.contains(&mode.as_str()) {
+                return Err(format!("bad file mode {} for '{}'", mode, name));
+            }
+            if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+                return Err(format!("bad entry name '{}'", name));
+            }
+            if name == ".git" {
+                return Err("contains '.git'".to_string());
+            }
+
+            let file_mode = u32::from_str_radix(&mode, 8)
+                .ok()
+                .and_then(FileMode::from_u32)
+                .ok_or_else(|| format!("bad file mode {} for '{}'", mode, name))?;
+            let entry = TreeEntry::new(file_mode, name, hash);
+            if let Some(previous) = &previous {
+                if previous.name == entry.name {
+                    return Err(format!("duplicate entry '{}'", entry.name));
+                }
+                if previous.git_cmp(&entry) != std::cmp::Ordering::Less {
+                    return Err(format!("entries not sorted ('{}')", entry.name));
+                }
+            }
+
+            match file_mode {
+                FileMode::Directory => links.push((hash, GitObjectType::Tree)),
+                // Submodule commits live in another repository
+                FileMode::Gitlink => {}
+                _ => links.push((hash, GitObjectType::Blob)),
+            }
+            previous = Some(entry);
+        }
+
+        Ok(links)
+    }
+
+    /// Commit headers: `tree`, any number of `parent`, `author`, `committer`
+    fn parse_commit_links(
+        format: ObjectFormat,
+        content: &[u8],
+    ) -> Result<Vec<(ObjectHash, GitObjectType)>, String> {
+        let headers = Self::header_lines(content)?;
+        let mut lines = headers.iter().map(String::as_str).peekable();
+        let mut links = Vec::new();
+
+        let tree = lines
+            .next()
+            .and_then(|line| line.strip_prefix("tree "))
+            .ok_or("missing tree header")?;
+        links.push((Self::parse_id(format, tree)?, GitObjectType::Tree));
+
+        while let Some(parent) = lines.peek().and_then(|line| line.strip_prefix("parent ")) {
+            links.push((Self::parse_id(format, parent)?, GitObjectType::Commit));
+            lines.next();
+        }
+
+        let author = lines
+            .next()
+            .and_then(|line| line.strip_prefix("author "))
+            .ok_or("missing author header")?;
+        Self::check_signature(author).map_err(|e| format!("bad author: {}", e))?;
+
+        let committer = lines
+            .next()
+            .and_then(|line| line.strip_prefix("committer "))
+            .ok_or("missing committer header")?;
+        Self::check_signature(committer).map_err(|e| format!("bad committer: {}", e))?;
+
+        Ok(links)
+    }
+
+    /// Tag headers: `object`, `type`, `tag` and an optional `tagger`
+    fn parse_tag_links(
+        format: ObjectFormat,
+        content: &[u8],
+    ) -> Result<Vec<(ObjectHash, GitObjectType)>, String> {
+        let headers = Self::header_lines(content)?;
+        let mut lines = headers.iter().map(String::as_str);
+
+        let object = lines
+            .next()
+            .and_then(|line| line.strip_prefix("object "))
+            .ok_or("missing object header")?;
+        let object = Self::parse_id(format, object)?;
+
+        let object_type: GitObjectType = lines
+            .next()
+            .and_then(|line| line.strip_prefix("type "))
+            .ok_or("missing type header")?
+            .parse()?;
+
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix("tag "))
+            .filter(|name| !name.is_empty())
+            .ok_or("missing tag header")?;
+
+        if let Some(tagger) = lines.next().and_then(|line| line.strip_prefix("tagger ")) {
+            Self::check_signature(tagger).map_err(|e| format!("bad tagger: {}", e))?;
+        }
+
+        Ok(vec![(object, object_type)])
+    }
+
+    /// Header lines of a commit or tag, up to the blank line before the message
+    fn header_lines(content: &[u8]) -> Result<Vec<String>, String> {
+        let end = content
+            .windows(2)
+            .position(|pair| pair == b"\n\n")
+            .map(|pos| pos + 1)
+            .unwrap_or(content.len());
+        if end == 0 || content[end - 1] != b'\n' {
+            return Err("unterminated header".to_string());
+        }
+
+        Ok(content[..end - 1]
+            .split(|&b| b == b'\n')
+            .map(|line| String::from_utf8_lossy(line).to_string())
+            .collect())
+    }
+
+    /// `Name <email> <seconds> <+hhmm>`
+    fn check_signature(value: &str) -> Result<(), String> {
+        let open = value.find('<').ok_or("missing '<'")?;
+        let close = value[open..]
+            .find('>')
+            .map(|pos| open + pos)
+            .ok_or("missing '>'")?;
+
+        let mut when = value[close + 1..].split_whitespace();
+        let seconds = when.next().ok_or("missing timestamp")?;
+        let zone = when.next().ok_or("missing time zone")?;
+        if when.next().is_some() || !seconds.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("bad date '{}'", value[close + 1..].trim()));
+        }
+        let zone_ok = zone.len() == 5
+            && (zone.starts_with('+') || zone.starts_with('-'))
+            && zone[1..].bytes().all(|b| b.is_ascii_digit());
+        if !zone_ok {
+            return Err(format!("bad time zone '{}'", zone));
+        }
+
+        Ok(())
+    }
+
+    fn parse_id(format: ObjectFormat, value: &str) -> Result<ObjectHash, String> {
+        if Self::is_object_id(format, value) {
+            ObjectHash::from_hex(value).map_err(|e| e.to_string())
+        } else {
+            Err(format!("invalid object id '{}'", value))
+        }
+    }
+
+    /// Lowercase hex of the right length for the repository's object format
+    fn is_object_id(format: ObjectFormat, value: &str) -> bool {
+        value.len() == format.hex_len()
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    }
+
+    /// Follow links from HEAD, all refs and the index, reporting what is
+    /// missing, and return the set of reachable objects
+    fn walk_reachable(
+        repo: &GitRepository,
+        ref_store: &RefStore,
+        format: ObjectFormat,
+        graph: &ObjectGraph,
+        result: &mut FsckResult,
+    ) -> crate::Result<HashSet<ObjectHash>> {
+        let mut roots = Vec::new();
+        if let Some(HeadRef::Direct(hash)) = ref_store.load_head()? {
+            roots.push(("HEAD".to_string(), hash.to_hex()));
+        }
+        roots.extend(ref_store.list_raw_refs()?);
+
+        // Objects to visit: (id, expected type, who points at it)
+        let mut pending: Vec<(ObjectHash, Option<GitObjectType>, String)> = Vec::new();
+        for (name, value) in roots {
+            let Ok(hash) = Self::parse_id(format, &value) else {
+                result.issues.push(FsckIssue::BadRef {
+                    name,
+                    reason: format!("invalid object id '{}'", value),
+                });
+                continue;
+            };
+            if !graph.types.contains_key(&hash) && !graph.corrupt.contains(&hash) {
+                result.issues.push(FsckIssue::BadRef {
+                    name,
+                    reason: format!("points at missing object {}", hash),
+                });
+            } else {
+                pending.push((hash, None, name));
+            }
+        }
+
+        // Staged blobs are in use even before they are committed. The index
+        // only adds roots, so one that cannot be read is skipped.
+        if let Ok(index) = IndexStore::new(repo.index_path()).load_index() {
+            for entry in index.get_sorted_entries() {
+                if entry.mode != FileMode::Gitlink {
+                    pending.push((
+                        entry.hash,
+                        Some(GitObjectType::Blob),
+                        format!("index entry {}", entry.path.display()),
+                    ));
+                }
+            }
+        }
+
+        let mut reachable = HashSet::new();
+        while let Some((hash, expected, referrer)) = pending.pop() {
+            if graph.corrupt.contains(&hash) {
+                // Already reported while scanning
+                continue;
+            }
+
+            let Some(object_type) = graph.types.get(&hash) else {
+                if reachable.insert(hash) {
+                    result.issues.push(FsckIssue::Missing {
+                        hash,
+                        object_type: expected.unwrap_or(GitObjectType::Commit),
+                        referrer,
+                    });
+                }
+                continue;
+            };
+
+            if let Some(expected) = expected {
+                if expected != *object_type {
+                    result.issues.push(FsckIssue::Invalid {
+                        hash,
+                        object_type: object_type.clone(),
+                        reason: format!("{} expects a {}", referrer, expected),
+                    });
+                }
+            }
+
+            if !reachable.insert(hash) {
+                continue;
+            }
+            for (target, target_type) in graph.links.get(&hash).into_iter().flatten() {
+                pending.push((
+                    *target,
+                    Some(target_type.clone()),
+                    format!("{} {}", object_type, hash),
+                ));
+            }
+        }
+
+        Ok(reachable)
+    }
+
+    /// Check that a pack file hashes to its trailing checksum and its index
+    fn verify_pack_checksum(pack: &PackReader, format: ObjectFormat) -> Result<(), String> {
+        let mut file = fs::File::open(pack.pack_path()).map_err(|e| e.to_string())?;
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        let body_len = len
+            .checked_sub(format.raw_len() as u64)
+            .ok_or("pack file is truncated")?;
+
+        let mut hasher = format.hasher();
+        let mut remaining = body_len;
+        let mut buffer = vec![0u8; 64 * 1024];
+        while remaining > 0 {
+            let chunk = remaining.min(buffer.len() as u64) as usize;
+            file.read_exact(&mut buffer[..chunk])
+                .map_err(|e| e.to_string())?;
+            hasher.update(&buffer[..chunk]);
+            remaining -= chunk as u64;
+        }
+
+        let mut trailer = vec![0u8; format.raw_len()];
+        file.read_exact(&mut trailer).map_err(|e| e.to_string())?;
+
+        let actual = hasher.finish();
+        if actual.as_bytes() != trailer.as_slice() {
+            return Err(format!(
+                "pack checksum mismatch (content hashes to {})",
+                actual
+            ));
+        }
+        if trailer != pack.index().pack_checksum() {
+            return Err("pack checksum does not match its index".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        AddCommand, AddOptions, CommitCommand, CommitOptions, InitCommand, RepackCommand,
+        RepackOptions,
+    };
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn repo_with_commits(count: usize) -> (TempDir, Vec<ObjectHash>) {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        let mut commits = Vec::new();
+        for i in 0..count {
+            fs::write(temp_dir.path().join("file.txt"), format!("version {}\n", i)).unwrap();
+            AddCommand::add(
+                temp_dir.path(),
+                &["file.txt".to_string()],
+                AddOptions::default(),
+            )
+            .unwrap();
+            let commit = CommitCommand::commit(
+                temp_dir.path(),
+                &format!("Commit {}", i),
+                CommitOptions::default(),
+            )
+            .unwrap();
+            commits.push(commit.commit_hash);
+        }
+        (temp_dir, commits)
+    }
+
+    fn object_store(temp_dir: &TempDir) -> ObjectStore {
+        ObjectStore::new(temp_dir.path().join(".git-rs/objects"))
+    }
+
+    /// Write a loose object with arbitrary content, bypassing serialization
+    fn write_raw_object(store: &ObjectStore, object_type: &str, content: &[u8]) -> ObjectHash {
+        let hash = ObjectFormat::Sha1.hash_object(object_type, content);
+        let mut data = format!("{} {}\0", object_type, content.len()).into_bytes();
+        data.extend_from_slice(content);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).unwrap();
+
+        let path = store.get_object_path(&hash);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, encoder.finish().unwrap()).unwrap();
+        hash
+    }
+
+    #[test]
+    fn test_fsck_clean_repository() {
+        let (temp_dir, _) = repo_with_commits(2);
+
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        assert!(result.is_ok(), "{:?}", result.issues);
+        assert!(result.issues.is_empty());
+        // 2 commits, 2 trees, 2 blobs
+        assert_eq!(result.objects_checked, 6);
+
+        // Packed objects are checked the same way
+        RepackCommand::repack(temp_dir.path(), RepackOptions::gc()).unwrap();
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        assert!(result.issues.is_empty(), "{:?}", result.issues);
+        assert_eq!(result.packs_checked, 1);
+        assert_eq!(result.objects_checked, 6);
+    }
+
+    #[test]
+    fn test_fsck_reports_missing_and_corrupt_objects() {
+        let (temp_dir, commits) = repo_with_commits(2);
+        let store = object_store(&temp_dir);
+
+        let first_tree = store.load_object(&commits[0]).unwrap();
+        let first_tree = first_tree.as_commit().unwrap().tree;
+        let first_blob = store
+            .load_object(&first_tree)
+            .unwrap()
+            .as_tree()
+            .unwrap()
+            .entries[0]
+            .hash;
+
+        // Remove the first blob, and replace the second one's content
+        let second_blob = ObjectFormat::Sha1.hash_object("blob", b"version 1\n");
+        fs::remove_file(store.get_object_path(&first_blob)).unwrap();
+        fs::remove_file(store.get_object_path(&second_blob)).unwrap();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"blob 3\0bad").unwrap();
+        fs::write(
+            store.get_object_path(&second_blob),
+            encoder.finish().unwrap(),
+        )
+        .unwrap();
+
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        assert_eq!(result.error_count(), 2, "{:?}", result.issues);
+        assert!(result.issues.contains(&FsckIssue::Missing {
+            hash: first_blob,
+            object_type: GitObjectType::Blob,
+            referrer: format!("tree {}", first_tree),
+        }));
+        assert!(result.issues.iter().any(|issue| matches!(
+            issue,
+            FsckIssue::Corrupt { hash, reason }
+                if *hash == second_blob && reason.starts_with("hash mismatch")
+        )));
+    }
+
+    #[test]
+    fn test_fsck_validates_object_syntax() {
+        let (temp_dir, commits) = repo_with_commits(1);
+        let store = object_store(&temp_dir);
+        let blob = ObjectFormat::Sha1.hash_object("blob", b"version 0\n");
+        let raw_blob = blob.as_bytes().to_vec();
+
+        let mut bad_mode = b"100664 file.txt\0".to_vec();
+        bad_mode.extend_from_slice(&raw_blob);
+        let bad_mode = write_raw_object(&store, "tree", &bad_mode);
+
+        let mut unsorted = b"100644 b\0".to_vec();
+        unsorted.extend_from_slice(&raw_blob);
+        unsorted.extend_from_slice(b"100644 a\0");
+        unsorted.extend_from_slice(&raw_blob);
+        let unsorted = write_raw_object(&store, "tree", &unsorted);
+
+        let no_author = format!("tree {}\nparent {}\n\nmessage\n", bad_mode, commits[0]);
+        let no_author = write_raw_object(&store, "commit", no_author.as_bytes());
+
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        let invalid: Vec<(ObjectHash, String)> = result
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                FsckIssue::Invalid { hash, reason, .. } => Some((*hash, reason.clone())),
+                _ => None,
+            })
+            .collect();
+        assert!(invalid.contains(&(bad_mode, "bad file mode 100664 for 'file.txt'".to_string())));
+        assert!(invalid.contains(&(unsorted, "entries not sorted ('a')".to_string())));
+        assert!(invalid.contains(&(no_author, "missing author header".to_string())));
+    }
+
+    #[test]
+    fn test_fsck_dangling_commit_and_lost_found() {
+        let (temp_dir, commits) = repo_with_commits(2);
+
+        // Move the branch back, leaving the second commit behind
+        let git_dir = temp_dir.path().join(".git-rs");
+        let ref_store = RefStore::new(git_dir.clone());
+        let branch = ref_store.get_current_branch().unwrap().unwrap();
+        fs::write(
+            git_dir.join("refs/heads").join(&branch),
+            format!("{}\n", commits[0]),
+        )
+        .unwrap();
+
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        assert!(result.is_ok());
+        // Only the commit is dangling; its tree and blob are merely unreachable
+        assert_eq!(
+            result.issues,
+            vec![FsckIssue::Dangling {
+                hash: commits[1],
+                object_type: GitObjectType::Commit,
+            }]
+        );
+        assert!(result.lost_found.is_empty());
+
+        let options = FsckOptions { lost_found: true };
+        let result = FsckCommand::fsck(temp_dir.path(), options).unwrap();
+        assert_eq!(result.lost_found, vec![commits[1]]);
+        let lost_ref = git_dir.join("refs/lost-found").join(commits[1].to_hex());
+        assert_eq!(
+            fs::read_to_string(lost_ref).unwrap(),
+            format!("{}\n", commits[1])
+        );
+
+        // Now it is reachable again
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        assert!(result.issues.is_empty(), "{:?}", result.issues);
+    }
+
+    #[test]
+    fn test_fsck_reports_broken_refs() {
+        let (temp_dir, _) = repo_with_commits(1);
+        let git_dir = temp_dir.path().join(".git-rs");
+        let missing = "0123456789abcdef0123456789abcdef01234567";
+        fs::write(git_dir.join("refs/tags/broken"), format!("{}\n", missing)).unwrap();
+        fs::write(git_dir.join("refs/tags/garbage"), "not-a-hash\n").unwrap();
+
+        let result = FsckCommand::fsck(temp_dir.path(), FsckOptions::default()).unwrap();
+        assert_eq!(result.error_count(), 2);
+        assert!(result.issues.contains(&FsckIssue::BadRef {
+            name: "refs/tags/broken".to_string(),
+            reason: format!("points at missing object {}", missing),
+        }));
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::object_store::ObjectStore;
+
+/// Git Hash-Object Use Case
+///
+/// This implements the `git hash-object` plumbing command: computing the id
+/// a piece of content gets as an object, and optionally storing it.
+///
+/// ## Usage:
+/// ```text
+/// $ echo 'Hello World' | git-rs hash-object --stdin
+/// 557db03de997c86a4a028e1ebd3a1ceb225be238
+/// $ git-rs hash-object -w -t tree raw-tree-bytes
+/// ```
+///
+/// ## Educational Insights:
+/// - **Id = Hash(Header + Content)**: The id is the hash of
+///   `<type> <size>\0<content>`, so the same bytes get different ids as a
+///   blob and as, say, a commit
+/// - **No Working Tree Needed**: Without `-w` nothing is written, so hashing
+///   works even outside a repository
+/// - **Validated Types**: Trees, commits and tags must parse before they are
+///   hashed, so a script cannot create objects Git would reject
+pub struct HashObjectCommand;
+
+/// Options for the hash-object command
+#[derive(Debug, Clone)]
+pub struct HashObjectOptions {
+    /// Type of the object to create (`-t`)
+    pub object_type: GitObjectType,
+    /// Store the object in the database (`-w`)
+    pub write: bool,
+}
+
+impl Default for HashObjectOptions {
+    fn default() -> Self {
+        Self {
+            object_type: GitObjectType::Blob,
+            write: false,
+        }
+    }
+}
+
+impl HashObjectCommand {
+    /// Hash (and with `-w`, store) the content of a file
+    ///
+    /// Blobs are streamed, so the file is never held in memory as a whole.
+    pub fn hash_file_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        file: &Path,
+        options: &HashObjectOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectHash> {
+        let object_store = Self::open(repo_path.as_ref(), options, git_compat)?;
+
+        if options.object_type != GitObjectType::Blob {
+            let content = std::fs::read(file)?;
+            return Self::hash_content(&object_store, &content, options);
+        }
+
+        if !file.is_file() {
+            return Err(format!("Cannot open '{}'", file.display()).into());
+        }
+        if options.write {
+            object_store.store_blob_from_file(file)
+        } else {
+            object_store.hash_file(file)
+        }
+    }
+
+    /// Hash (and with `-w`, store) content read from standard input
+    pub fn hash_bytes_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        content: &[u8],
+        options: &HashObjectOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectHash> {
+        let object_store = Self::open(repo_path.as_ref(), options, git_compat)?;
+        Self::hash_content(&object_store, content, options)
+    }
+
+    fn hash_content(
+        object_store: &ObjectStore,
+        content: &[u8],
+        options: &HashObjectOptions,
+    ) -> crate::Result<ObjectHash> {
+        let object_type = &options.object_type;
+        if *object_type != GitObjectType::Blob {
+            object_store
+                .parse_object_content(object_type, content)
+                .map_err(|e| format!("corrupt {}: {}", object_type, e))?;
+        }
+
+        if options.write {
+            object_store.store_raw_object(object_type, content)
+        } else {
+            Ok(object_store
+                .format()
+                .hash_object(&object_type.to_string(), content))
+        }
+    }
+
+    /// Only writing needs a repository; plain hashing uses its object format
+    /// when there is one and SHA-1 otherwise
+    fn open(
+        repo_path: &Path,
+        options: &HashObjectOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectStore> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if options.write && !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        Ok(ObjectStore::for_repository(&repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::InitCommand;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hash_object_matches_git_blob_ids() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("hello.txt");
+        fs::write(&file, "Hello World\n").unwrap();
+        let options = HashObjectOptions::default();
+
+        // Works outside a repository when nothing is written
+        let from_file = HashObjectCommand::hash_file_with_compat(
+            temp_dir.path(),
+            &file,
+            &options,
+            GitCompatMode::Educational,
+        )
+        .unwrap();
+        let from_stdin = HashObjectCommand::hash_bytes_with_compat(
+            temp_dir.path(),
+            b"Hello World\n",
+            &options,
+            GitCompatMode::Educational,
+        )
+        .unwrap();
+        assert_eq!(
+            from_file.to_hex(),
+            "557db03de997c86a4a028e1ebd3a1ceb225be238"
+        );
+        assert_eq!(from_stdin, from_file);
+        assert!(!temp_dir.path().join(".git-rs").exists());
+
+        let write = HashObjectOptions {
+            write: true,
+            ..Default::default()
+        };
+        assert!(HashObjectCommand::hash_file_with_compat(
+            temp_dir.path(),
+            &file,
+            &write,
+            GitCompatMode::Educational
+        )
+        .is_err());
+
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+        let stored = HashObjectCommand::hash_file_with_compat(
+            temp_dir.path(),
+            &file,
+            &write,
+            GitCompatMode::Educational,
+        )
+        .unwrap();
+        assert_eq!(stored, from_file);
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+        assert_eq!(
+            store.load_raw_object(&stored).unwrap(),
+            (GitObjectType::Blob, b"Hello World\n".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_hash_object_validates_other_types() {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        let commit = b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+                       author A U Thor <author@example.com> 1112911993 -0700\n\
+                       committer A U Thor <author@example.com> 1112911993 -0700\n\
+                       \n\
+                       empty\n";
+        let options = HashObjectOptions {
+            object_type: GitObjectType::Commit,
+            write: true,
+        };
+        let hash = HashObjectCommand::hash_bytes_with_compat(
+            temp_dir.path(),
+            commit,
+            &options,
+            GitCompatMode::Educational,
+        )
+        .unwrap();
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+        assert_eq!(
+            store.load_raw_object(&hash).unwrap(),
+            (GitObjectType::Commit, commit.to_vec())
+        );
+
+        assert!(HashObjectCommand::hash_bytes_with_compat(
+            temp_dir.path(),
+            b"not a commit",
+            &options,
+            GitCompatMode::Educational,
+        )
+        .is_err());
+    }
+}
@@ -57,6 +57,26 @@ impl InitCommand {
     pub fn init_with_compat<P: AsRef<Path>>(
         path: Option<P>,
         git_compat: GitCompatMode,
+    ) -> crate::Result<GitRepository> {
+        Self::init_with_format(path, git_compat, ObjectFormat::Sha1)
+    }
+
+    /// Initialize a new Git repository using the given object format
+    ///
+    /// # Arguments
+    /// * `path` - Directory path where to initialize the repository (default: current directory)
+    /// * `git_compat` - Git compatibility mode (Educational uses .git-rs, Compatible uses .git)
+    /// * `object_format` - Hash algorithm for object ids (`--object-format=sha1|sha256`)
+    ///
+    /// ## SHA-256 Repositories:
+    /// A SHA-256 repository records `extensions.objectFormat = sha256` in its
+    /// config and bumps `core.repositoryformatversion` to 1, so that tools that
+    /// do not understand the extension refuse to touch it instead of
+    /// misreading 64-character object ids.
+    pub fn init_with_format<P: AsRef<Path>>(
+        path: Option<P>,
+        git_compat: GitCompatMode,
+        object_format: ObjectFormat,
     ) -> crate::Result<GitRepository> {
         let repo_path = match path {
             Some(p) => p.as_ref().to_path_buf(),
@@ -83,7 +103,7 @@ impl InitCommand {
         Self::initialize_reference_store(&repo)?;
 
         // Create initial configuration
-        Self::create_initial_config(&repo, git_compat)?;
+        Self::create_initial_config(&repo, git_compat, object_format)?;
 
         // Create repository description
         Self::create_description(&repo, git_compat)?;
@@ -134,7 +154,7 @@ impl InitCommand {
     fn initialize_object_store(repo: &GitRepository) -> crate::Result<()> {
         println!("🗃️  Initializing object store...");
 
-        let object_store = ObjectStore::new(repo.objects_dir());
+        let object_store = ObjectStore::for_repository(repo);
         object_store.init()?;
 
         println!("   ✓ Object store ready for storing blobs, trees, and commits");
@@ -159,18 +179,39 @@ impl InitCommand {
     }
 
     /// Create initial repository configuration
-    fn create_initial_config(repo: &GitRepository, git_compat: GitCompatMode) -> crate::Result<()> {
+    fn create_initial_config(
+        repo: &GitRepository,
+        git_compat: GitCompatMode,
+        object_format: ObjectFormat,
+    ) -> crate::Result<()> {
         println!("⚙️  Creating initial configuration...");
 
-        let config_content = r#"[core]
-	repositoryformatversion = 0
+        // Extensions are only honoured by readers from format version 1 on
+        let format_version = match object_format {
+            ObjectFormat::Sha1 => 0,
+            ObjectFormat::Sha256 => 1,
+        };
+        let mut config_content = format!(
+            r#"[core]
+	repositoryformatversion = {}
 	filemode = true
 	bare = false
 	logallrefupdates = true
-[user]
+"#,
+            format_version
+        );
+        if object_format != ObjectFormat::Sha1 {
+            config_content.push_str(&format!(
+                "[extensions]\n\tobjectFormat = {}\n",
+                object_format
+            ));
+        }
+        config_content.push_str(
+            r#"[user]
 	name = Git User
 	email = user@example.com
-"#;
+"#,
+        );
 
         fs::write(repo.config_path(), config_content)?;
 
@@ -179,6 +220,9 @@ impl InitCommand {
             GitCompatMode::Compatible => ".git",
         };
         println!("   ✓ Created {}/config with default settings", git_dir_name);
+        if object_format != ObjectFormat::Sha1 {
+            println!("   ✓ Object format: {}", object_format);
+        }
 
         Ok(())
     }
@@ -298,6 +342,67 @@ mod tests {
         assert!(InitCommand::is_git_repository(repo_path));
     }
 
+    #[test]
+    fn test_init_sha256_repository() {
+        let temp_dir = tempdir().unwrap();
+        let repo = InitCommand::init_with_format(
+            Some(temp_dir.path()),
+            GitCompatMode::Educational,
+            ObjectFormat::Sha256,
+        )
+        .unwrap();
+
+        let config = ConfigStore::new(repo.config_path());
+        assert_eq!(config.object_format().unwrap(), ObjectFormat::Sha256);
+        assert_eq!(
+            config
+                .get("core.repositoryformatversion")
+                .unwrap()
+                .as_deref(),
+            Some("1")
+        );
+
+        // The object store picks the format up from the config
+        let store = ObjectStore::for_repository(&repo);
+        assert_eq!(store.format(), ObjectFormat::Sha256);
+
+        let blob_hash = store
+            .store_object(&GitObject::Blob(BlobObject::new(Vec::new())))
+            .unwrap();
+        assert_eq!(
+            blob_hash.to_hex(),
+            "473a0f4c3be8a93681a267e3b1e9a7dcda1185436fe141f7749120a303721813"
+        );
+
+        let mut tree = TreeObject::new();
+        tree.add_entry(TreeEntry::new(
+            FileMode::Regular,
+            "empty.txt".to_string(),
+            blob_hash,
+        ));
+        let tree_hash = store.store_object(&GitObject::Tree(tree)).unwrap();
+        assert_eq!(tree_hash.as_bytes().len(), 32);
+
+        let loaded = store.load_object(&tree_hash).unwrap();
+        assert_eq!(loaded.as_tree().unwrap().entries[0].hash, blob_hash);
+    }
+
+    #[test]
+    fn test_init_sha1_config_has_no_extensions() {
+        let temp_dir = tempdir().unwrap();
+        let repo = InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        let config = ConfigStore::new(repo.config_path());
+        assert_eq!(config.get("extensions.objectFormat").unwrap(), None);
+        assert_eq!(
+            config
+                .get("core.repositoryformatversion")
+                .unwrap()
+                .as_deref(),
+            Some("0")
+        );
+    }
+
     #[test]
     fn test_repository_info() {
         let temp_dir = tempdir().unwrap();
@@ -1,15 +1,24 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::path::Path;
 
-use crate::domain::{objects::*, references::*};
-use crate::infrastructure::{object_store::ObjectStore, ref_store::RefStore};
+use chrono::{DateTime, Utc};
+
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::{
+    object_store::ObjectStore, ref_store::RefStore, revision::RevisionResolver,
+};
 
 /// Git Log Use Case
 ///
 /// This implements the `git log` command functionality.
 ///
 /// ## What `git log` does:
-/// 1. Resolves HEAD to get the current commit
-/// 2. Walks the commit history following parent links
+/// 1. Resolves the starting revision (HEAD by default, or e.g. `v1.0~2`,
+///    `main..topic`) to commits
+/// 2. Walks the commit history following parent links, skipping commits
+///    reachable from the excluded side of a range
 /// 3. Displays commit information in reverse chronological order
 /// 4. Supports limiting the number of commits shown
 ///
@@ -36,6 +45,8 @@ pub struct LogCommand;
 pub struct LogOptions {
     /// Maximum number of commits to show (None = all)
     pub max_count: Option<usize>,
+    /// Where to start: a revision or an `A..B` / `A...B` range (None = HEAD)
+    pub revision: Option<String>,
 }
 
 /// Result of log command containing commit information
@@ -63,6 +74,47 @@ impl LogResult {
     }
 }
 
+/// Commits waiting to be shown, newest committer date first
+#[derive(Default)]
+struct CommitQueue {
+    heap: BinaryHeap<(DateTime<Utc>, Reverse<usize>)>,
+    commits: HashMap<usize, (ObjectHash, CommitObject)>,
+    seen: HashSet<ObjectHash>,
+}
+
+impl CommitQueue {
+    /// Queue a commit unless it was queued before or is hidden
+    fn push(
+        &mut self,
+        object_store: &ObjectStore,
+        hash: ObjectHash,
+        hidden: &HashSet<ObjectHash>,
+    ) -> crate::Result<()> {
+        if hidden.contains(&hash) || !self.seen.insert(hash) {
+            return Ok(());
+        }
+
+        let commit = match object_store.load_object(&hash)? {
+            GitObject::Commit(commit) => commit,
+            _ => return Err(format!("Object {} is not a commit", hash).into()),
+        };
+        // Ties keep insertion order, so a linear history stays in order
+        let order = self.seen.len();
+        self.heap.push((commit.committer.timestamp, Reverse(order)));
+        self.commits.insert(order, (hash, commit));
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<(ObjectHash, CommitObject)> {
+        let (_, Reverse(order)) = self.heap.pop()?;
+        self.commits.remove(&order)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
 impl LogCommand {
     /// Show commit history starting from HEAD
     ///
@@ -74,70 +126,7 @@ impl LogCommand {
     /// * `Ok(LogResult)` - The commit history
     /// * `Err(...)` - If log failed
     pub fn log<P: AsRef<Path>>(repo_path: P, options: LogOptions) -> crate::Result<LogResult> {
-        let repo_path = repo_path.as_ref();
-        let git_dir = repo_path.join(".git-rs");
-
-        if !git_dir.exists() {
-            return Err("Not a git repository (or any of the parent directories): .git-rs".into());
-        }
-
-        println!("🔍 Loading commit history...");
-
-        let ref_store = RefStore::new(git_dir.clone());
-        let object_store = ObjectStore::new(git_dir.join("objects"));
-
-        // Get HEAD reference
-        let head = ref_store.load_head()?;
-        let head = match head {
-            Some(h) => h,
-            None => {
-                println!("📭 No commits found (empty repository)");
-                return Ok(LogResult::new(vec![], 0, false));
-            }
-        };
-
-        // Resolve HEAD to get starting commit hash
-        let starting_commit_hash = Self::resolve_head_to_commit(&head, &ref_store)?;
-
-        // Walk the commit history
-        let mut entries = Vec::new();
-        let mut current_hash = Some(starting_commit_hash);
-        let mut count = 0;
-
-        while let Some(hash) = current_hash.as_ref() {
-            // Check if we've hit our limit
-            if let Some(max) = options.max_count {
-                if count >= max {
-                    break;
-                }
-            }
-
-            // Load the commit object
-            let object = object_store.load_object(hash)?;
-            let commit = match object.as_commit() {
-                Some(c) => c.clone(),
-                None => {
-                    return Err(format!("Object {} is not a commit", hash).into());
-                }
-            };
-
-            // Add to results
-            entries.push(LogEntry {
-                hash: hash.clone(),
-                commit: commit.clone(),
-            });
-
-            // Move to parent commit
-            current_hash = commit.parents.first().cloned();
-            count += 1;
-        }
-
-        let has_more = current_hash.is_some();
-        let total_entries = entries.len();
-
-        println!("📊 Found {} commit(s)", total_entries);
-
-        Ok(LogResult::new(entries, total_entries, has_more))
+        Self::log_with_compat(repo_path, options, GitCompatMode::Educational)
     }
 
     /// Show commit history with git compatibility mode
@@ -153,78 +142,60 @@ impl LogCommand {
     pub fn log_with_compat<P: AsRef<Path>>(
         repo_path: P,
         options: LogOptions,
-        git_compat: crate::domain::repository::GitCompatMode,
+        git_compat: GitCompatMode,
     ) -> crate::Result<LogResult> {
-        let repo_path = repo_path.as_ref();
-        let git_dir = match git_compat {
-            crate::domain::repository::GitCompatMode::Educational => repo_path.join(".git-rs"),
-            crate::domain::repository::GitCompatMode::Compatible => repo_path.join(".git"),
-        };
-
-        if !git_dir.exists() {
-            let dir_name = match git_compat {
-                crate::domain::repository::GitCompatMode::Educational => ".git-rs",
-                crate::domain::repository::GitCompatMode::Compatible => ".git",
-            };
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
             return Err(format!(
                 "Not a git repository (or any of the parent directories): {}",
-                dir_name
+                repo.git_dir().display()
             )
             .into());
         }
 
         println!("🔍 Loading commit history...");
 
-        let ref_store = RefStore::new(git_dir.clone());
-        let object_store = ObjectStore::new(git_dir.join("objects"));
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let object_store = ObjectStore::for_repository(&repo);
+        let resolver = RevisionResolver::new(&object_store, &ref_store);
 
-        // Get HEAD reference
-        let head = ref_store.load_head()?;
-        let head = match head {
-            Some(h) => h,
-            None => {
+        let revision = match &options.revision {
+            Some(revision) => revision.as_str(),
+            None if ref_store.get_head()?.is_none() => {
                 println!("📭 No commits found (empty repository)");
                 return Ok(LogResult::new(vec![], 0, false));
             }
+            None => "HEAD",
         };
 
-        // Resolve HEAD to get starting commit hash
-        let starting_commit_hash = Self::resolve_head_to_commit(&head, &ref_store)?;
-
-        // Walk the commit history
-        let mut entries = Vec::new();
-        let mut current_hash = Some(starting_commit_hash);
-        let mut count = 0;
-
-        while let Some(hash) = current_hash.as_ref() {
-            // Check if we've hit our limit
-            if let Some(max) = options.max_count {
-                if count >= max {
-                    break;
-                }
+        // Commits reachable from an excluded side of a range are never shown
+        let range = resolver.resolve_range(revision)?;
+        let hidden = {
+            let mut excluded = Vec::new();
+            for hash in &range.exclude {
+                excluded.push(object_store.peel_to_commit(hash)?);
             }
+            resolver.ancestors(&excluded)?
+        };
 
-            // Load the commit object
-            let object = object_store.load_object(hash)?;
-            let commit = match object.as_commit() {
-                Some(c) => c.clone(),
-                None => {
-                    return Err(format!("Object {} is not a commit", hash).into());
-                }
-            };
-
-            // Add to results
-            entries.push(LogEntry {
-                hash: hash.clone(),
-                commit: commit.clone(),
-            });
+        // Walk newest-first by committer date, following every parent
+        let mut queue = CommitQueue::default();
+        for hash in &range.include {
+            queue.push(&object_store, object_store.peel_to_commit(hash)?, &hidden)?;
+        }
 
-            // Move to parent commit
-            current_hash = commit.parents.first().cloned();
-            count += 1;
+        let mut entries = Vec::new();
+        while options.max_count.is_none_or(|max| entries.len() < max) {
+            let Some((hash, commit)) = queue.pop() else {
+                break;
+            };
+            for parent in &commit.parents {
+                queue.push(&object_store, *parent, &hidden)?;
+            }
+            entries.push(LogEntry { hash, commit });
         }
 
-        let has_more = current_hash.is_some();
+        let has_more = !queue.is_empty();
         let total_entries = entries.len();
 
         println!("📊 Found {} commit(s)", total_entries);
@@ -232,39 +203,9 @@ impl LogCommand {
         Ok(LogResult::new(entries, total_entries, has_more))
     }
 
-    /// Resolve HEAD reference to actual commit hash
-    fn resolve_head_to_commit(head: &HeadRef, ref_store: &RefStore) -> crate::Result<ObjectHash> {
-        match head {
-            HeadRef::Direct(hash) => Ok(hash.clone()),
-            HeadRef::Symbolic(ref_name) => {
-                // Load all refs to find the branch
-                let refs = ref_store.load_refs()?;
-
-                // Extract branch name from refs/heads/branch_name
-                // Handle cases where ref_name might be malformed like refs/heads/refs/heads/main
-                let branch_name = if ref_name.starts_with("refs/heads/refs/heads/") {
-                    ref_name.strip_prefix("refs/heads/refs/heads/").unwrap()
-                } else if ref_name.starts_with("refs/heads/") {
-                    ref_name.strip_prefix("refs/heads/").unwrap()
-                } else {
-                    return Err(format!("Invalid symbolic ref: {}", ref_name).into());
-                };
-
-                // Find the branch reference
-                let branches = refs.branches();
-                let branch_ref = branches
-                    .iter()
-                    .find(|r| r.name == branch_name)
-                    .ok_or_else(|| format!("Branch '{}' not found", branch_name))?;
-
-                Ok(branch_ref.hash.clone())
-            }
-        }
-    }
-
     /// Format a log entry for display
     pub fn format_log_entry(entry: &LogEntry) -> String {
-        let short_hash = &entry.hash.as_str()[..7];
+        let short_hash = entry.hash.to_short_hex(7);
         let message_lines: Vec<&str> = entry.commit.message.lines().collect();
         let first_line = message_lines.first().unwrap_or(&"");
 
@@ -277,7 +218,7 @@ impl LogCommand {
             entry
                 .commit
                 .author
-                .timestamp
+                .local_time()
                 .format("%a %b %d %H:%M:%S %Y %z"),
             first_line
         )
@@ -313,19 +254,16 @@ mod tests {
 
     #[test]
     fn test_log_entry_formatting() {
-        let hash = ObjectHash::new("a1b2c3d4e5f6789012345678901234567890abcd".to_string());
+        let hash = ObjectHash::from_hex("a1b2c3d4e5f6789012345678901234567890abcd").unwrap();
         let author = Signature::new("Test Author".to_string(), "test@example.com".to_string());
         let commit = CommitObject::new(
-            hash.clone(),
+            hash,
             vec![],
             author,
             "Initial commit\n\nThis is the first commit in the repository.".to_string(),
         );
 
-        let entry = LogEntry {
-            hash: hash.clone(),
-            commit,
-        };
+        let entry = LogEntry { hash, commit };
 
         let formatted = LogCommand::format_log_entry(&entry);
         assert!(formatted.contains("commit a1b2c3d4e5f6789012345678901234567890abcd"));
@@ -348,6 +286,47 @@ mod tests {
             .contains("Not a git repository"));
     }
 
+    #[test]
+    fn test_log_from_revision_and_range() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = temp_dir.path();
+        crate::application::InitCommand::init(Some(repo_path)).unwrap();
+        let mut commits = Vec::new();
+        for message in ["first", "second", "third"] {
+            let options = crate::application::CommitOptions {
+                allow_empty: true,
+                ..Default::default()
+            };
+            let result = crate::application::CommitCommand::commit(repo_path, message, options);
+            commits.push(result.unwrap().commit_hash);
+        }
+
+        let log = |revision: &str| {
+            let options = LogOptions {
+                revision: Some(revision.to_string()),
+                ..Default::default()
+            };
+            let result = LogCommand::log(repo_path, options).unwrap();
+            result
+                .entries
+                .into_iter()
+                .map(|entry| entry.hash)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(log("HEAD~1"), vec![commits[1], commits[0]]);
+        assert_eq!(log("HEAD~2..HEAD"), vec![commits[2], commits[1]]);
+        assert_eq!(log(&format!("{}..", commits[1])), vec![commits[2]]);
+        assert!(LogCommand::log(
+            repo_path,
+            LogOptions {
+                revision: Some("nope".to_string()),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_log_result_creation() {
         let entries = vec![];
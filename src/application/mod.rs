@@ -1,15 +1,37 @@
 pub mod add;
+pub mod cat_file;
 pub mod clone;
 pub mod commit;
+pub mod commit_tree;
 pub mod diff;
+pub mod fsck;
+pub mod hash_object;
 pub mod init;
 pub mod log;
+pub mod mv;
+pub mod repack;
+pub mod rev_parse;
+pub mod rm;
 pub mod status;
+pub mod tag;
+pub mod update_ref;
+pub mod write_tree;
 
 pub use add::*;
+pub use cat_file::*;
 pub use clone::*;
 pub use commit::*;
+pub use commit_tree::*;
 pub use diff::*;
+pub use fsck::*;
+pub use hash_object::*;
 pub use init::*;
 pub use log::*;
+pub use mv::*;
+pub use repack::*;
+pub use rev_parse::*;
+pub use rm::*;
 pub use status::*;
+pub use tag::*;
+pub use update_ref::*;
+pub use write_tree::*;
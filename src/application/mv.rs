@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::objects::FileMode;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::index_store::IndexStore;
+use crate::infrastructure::working_tree::WorkingTree;
+
+/// Git Mv Use Case
+///
+/// This implements the `git mv` command: renaming a tracked file or
+/// directory on disk and in the index in one step.
+///
+/// ## Usage:
+/// ```text
+/// $ git-rs mv old.txt new.txt        rename a file
+/// $ git-rs mv a.txt b.txt docs       move both into the existing docs/
+/// $ git-rs mv src lib                rename a directory and all it tracks
+/// ```
+///
+/// ## Visual Guide - What Changes:
+/// ```text
+/// index entry  path: old.txt ──▶ new.txt
+///              blob, mode, stat data: unchanged
+/// working tree old.txt ──rename──▶ new.txt
+/// ```
+///
+/// ## Educational Insights:
+/// - **No Rename Objects**: Git stores no record of a rename. The next
+///   commit simply has the same blob under a new path; `status` and `log`
+///   detect renames afterwards by comparing content
+/// - **Content Is Not Re-read**: The blob id and stat data move with the
+///   entry, so even a huge file is renamed without hashing it again
+/// - **All or Nothing**: Every move is checked before anything is renamed,
+///   and the renames are undone if the index cannot be saved
+pub struct MvCommand;
+
+/// Options for the mv command
+#[derive(Debug, Clone, Default)]
+pub struct MvOptions {
+    /// Overwrite an existing destination file (`-f`)
+    pub force: bool,
+}
+
+/// Result of the mv operation
+#[derive(Debug, Clone, Default)]
+pub struct MvResult {
+    /// Each source with its new path, in the order given
+    pub moved: Vec<(PathBuf, PathBuf)>,
+}
+
+impl MvCommand {
+    /// Move `sources` to `destination`, or into it when it is a directory
+    /// (which it must be for several sources)
+    pub fn mv_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        sources: &[String],
+        destination: &str,
+        options: &MvOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<MvResult> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+        if sources.is_empty() {
+            return Err("usage: git-rs mv [-f] <source>... <destination>".into());
+        }
+
+        let index_store = IndexStore::new(repo.index_path());
+        let mut index = index_store.load_index()?;
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+
+        let destination_path = repo.normalize_path(destination)?;
+        let into_directory =
+            working_tree.file_mode(&destination_path)? == Some(FileMode::Directory);
+        if sources.len() > 1 && !into_directory {
+            return Err(format!("destination '{}' is not a directory", destination).into());
+        }
+
+        // Check every move, and find the index entries each one renames
+        let mut moves = Vec::new();
+        let mut renames = Vec::new();
+        let mut targets = HashSet::new();
+        for source in sources {
+            let source_path = repo.normalize_path(source)?;
+            let target = match source_path.file_name() {
+                Some(name) if into_directory => destination_path.join(name),
+                _ => destination_path.clone(),
+            };
+            let bad = |reason: &str| -> crate::Result<MvResult> {
+                Err(format!(
+                    "{}, source={}, destination={}",
+                    reason,
+                    source_path.display(),
+                    target.display()
+                )
+                .into())
+            };
+
+            let Some(source_mode) = working_tree.file_mode(&source_path)? else {
+                return bad("bad source");
+            };
+            if source_path.as_os_str().is_empty() || target.starts_with(&source_path) {
+                return bad("can not move directory into itself");
+            }
+
+            let target_mode = working_tree.file_mode(&target)?;
+            if source_mode == FileMode::Directory {
+                if target_mode.is_some() {
+                    return bad("cannot move directory over file");
+                }
+                let under = |path: &&PathBuf| path.starts_with(&source_path);
+                if index.conflicts.keys().any(|path| under(&path)) {
+                    return bad("conflicted");
+                }
+                let mut tracked: Vec<&PathBuf> = index.entries.keys().filter(under).collect();
+                if tracked.is_empty() {
+                    return bad("source directory is empty");
+                }
+                tracked.sort();
+                for path in tracked {
+                    let relative = path.strip_prefix(&source_path)?;
+                    renames.push((path.clone(), target.join(relative)));
+                }
+            } else {
+                if index.conflicts.contains_key(&source_path) {
+                    return bad("conflicted");
+                }
+                if !index.entries.contains_key(&source_path) {
+                    return bad("not under version control");
+                }
+                match target_mode {
+                    None => {}
+                    Some(_) if !options.force => return bad("destination exists"),
+                    // Only files can overwrite each other
+                    Some(FileMode::Directory | FileMode::Gitlink) => {
+                        return bad("Cannot overwrite")
+                    }
+                    Some(_) => {}
+                }
+                renames.push((source_path.clone(), target.clone()));
+            }
+
+            let parent = target.parent().unwrap_or(Path::new(""));
+            if working_tree.file_mode(parent)? != Some(FileMode::Directory) {
+                return bad("destination directory does not exist");
+            }
+            if !targets.insert(target.clone()) {
+                return bad("multiple sources for the same target");
+            }
+            moves.push((source_path, target));
+        }
+
+        // Rename on disk first, so a failure leaves the index untouched
+        Self::rename_all(repo.root_path(), &moves)?;
+
+        for (from, to) in &renames {
+            if let Some(mut entry) = index.remove_entry(from) {
+                entry.path = to.clone();
+                index.add_entry(entry);
+            }
+        }
+        if let Err(e) = index_store.save_index(&index) {
+            Self::undo_renames(repo.root_path(), &moves);
+            return Err(e);
+        }
+
+        Ok(MvResult { moved: moves })
+    }
+
+    /// Rename each source to its target, undoing the earlier renames if
+    /// one fails
+    fn rename_all(root: &Path, moves: &[(PathBuf, PathBuf)]) -> crate::Result<()> {
+        for (done, (source, target)) in moves.iter().enumerate() {
+            if let Err(e) = fs::rename(root.join(source), root.join(target)) {
+                Self::undo_renames(root, &moves[..done]);
+                return Err(format!("renaming '{}' failed: {}", source.display(), e).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn undo_renames(root: &Path, moves: &[(PathBuf, PathBuf)]) {
+        for (source, target) in moves.iter().rev() {
+            let _ = fs::rename(root.join(target), root.join(source));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        AddCommand, AddOptions, CommitCommand, CommitOptions, InitCommand, StatusCommand,
+        StatusOptions,
+    };
+    use crate::domain::index::GitIndex;
+    use tempfile::TempDir;
+
+    fn setup_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path();
+        InitCommand::init(Some(path)).unwrap();
+
+        fs::create_dir_all(path.join("src/nested")).unwrap();
+        fs::write(path.join("a.txt"), "a\n").unwrap();
+        fs::write(path.join("b.txt"), "b\n").unwrap();
+        fs::write(path.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(path.join("src/nested/lib.rs"), "// lib\n").unwrap();
+        AddCommand::add(path, &[".".to_string()], AddOptions::default()).unwrap();
+        CommitCommand::commit(path, "Initial commit", CommitOptions::default()).unwrap();
+        temp_dir
+    }
+
+    fn mv(
+        temp_dir: &TempDir,
+        sources: &[&str],
+        destination: &str,
+        force: bool,
+    ) -> crate::Result<MvResult> {
+        let sources: Vec<String> = sources.iter().map(|s| s.to_string()).collect();
+        MvCommand::mv_with_compat(
+            temp_dir.path(),
+            &sources,
+            destination,
+            &MvOptions { force },
+            GitCompatMode::Educational,
+        )
+    }
+
+    fn load_index(temp_dir: &TempDir) -> GitIndex {
+        IndexStore::new(temp_dir.path().join(".git-rs/git-rs-index"))
+            .load_index()
+            .unwrap()
+    }
+
+    fn tracked(temp_dir: &TempDir) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = load_index(temp_dir).entries.into_keys().collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_mv_renames_file_keeping_its_entry() {
+        let temp_dir = setup_repo();
+        let path = temp_dir.path();
+        let before = load_index(&temp_dir).entries[Path::new("a.txt")].clone();
+
+        mv(&temp_dir, &["a.txt"], "renamed.txt", false).unwrap();
+        assert!(!path.join("a.txt").exists());
+        assert_eq!(fs::read_to_string(path.join("renamed.txt")).unwrap(), "a\n");
+
+        // Same blob, mode and stat data, only the path changed
+        let after = load_index(&temp_dir).entries[Path::new("renamed.txt")].clone();
+        assert_eq!((after.hash, after.mode), (before.hash, before.mode));
+        assert_eq!((after.mtime, after.ino), (before.mtime, before.ino));
+
+        let status = StatusCommand::status(path, StatusOptions::default()).unwrap();
+        assert_eq!(
+            status.file_changes.staged_new,
+            [PathBuf::from("renamed.txt")]
+        );
+        assert_eq!(status.file_changes.staged_deleted, [PathBuf::from("a.txt")]);
+        assert!(status.file_changes.modified.is_empty());
+        assert!(status.file_changes.untracked.is_empty());
+    }
+
+    #[test]
+    fn test_mv_into_directories() {
+        let temp_dir = setup_repo();
+        let path = temp_dir.path();
+
+        // Several sources go into an existing directory
+        let result = mv(&temp_dir, &["a.txt", "./b.txt"], "src/nested", false).unwrap();
+        assert_eq!(
+            result.moved,
+            vec![
+                (PathBuf::from("a.txt"), PathBuf::from("src/nested/a.txt")),
+                (PathBuf::from("b.txt"), PathBuf::from("src/nested/b.txt")),
+            ]
+        );
+
+        // A directory takes everything under it along, untracked files too
+        fs::write(path.join("src/notes.tmp"), "scratch\n").unwrap();
+        mv(&temp_dir, &["src"], "lib", false).unwrap();
+        assert!(!path.join("src").exists());
+        assert!(path.join("lib/notes.tmp").exists());
+        assert_eq!(
+            tracked(&temp_dir),
+            vec![
+                PathBuf::from("lib/main.rs"),
+                PathBuf::from("lib/nested/a.txt"),
+                PathBuf::from("lib/nested/b.txt"),
+                PathBuf::from("lib/nested/lib.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mv_refuses_bad_moves() {
+        let temp_dir = setup_repo();
+        let path = temp_dir.path();
+        fs::write(path.join("untracked.txt"), "u\n").unwrap();
+        let error = |sources: &[&str], destination: &str| {
+            mv(&temp_dir, sources, destination, false)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(error(&["a.txt"], "b.txt").starts_with("destination exists"));
+        assert!(error(&["missing.txt"], "c.txt").starts_with("bad source"));
+        assert!(error(&["untracked.txt"], "c.txt").starts_with("not under version control"));
+        assert!(error(&["src"], "src/nested/inner").contains("into itself"));
+        assert!(error(&["a.txt"], "nowhere/a.txt").contains("does not exist"));
+        assert!(error(&["a.txt", "b.txt"], "c.txt").contains("is not a directory"));
+
+        // A bad source anywhere means nothing moves
+        assert!(error(&["a.txt", "missing.txt"], "src").starts_with("bad source"));
+        assert!(path.join("a.txt").exists());
+        assert_eq!(tracked(&temp_dir).len(), 4);
+
+        // -f replaces an existing file, and its entry
+        mv(&temp_dir, &["a.txt"], "b.txt", true).unwrap();
+        assert_eq!(fs::read_to_string(path.join("b.txt")).unwrap(), "a\n");
+        assert_eq!(
+            tracked(&temp_dir),
+            vec![
+                PathBuf::from("b.txt"),
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("src/nested/lib.rs"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::objects::*;
+use crate::domain::remote::PackObjectType;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::pack::PackReader;
+use crate::infrastructure::pack_writer::{PackInput, PackWriter, WrittenPack};
+
+/// Git Repack Use Case
+///
+/// This implements the `git repack` and `git gc` commands.
+///
+/// ## What `git repack` does:
+/// 1. Collects loose objects (and, with `-a`, every object already packed)
+/// 2. Sorts them so that similar objects sit next to each other
+/// 3. Stores objects as deltas against similar ones where that saves space
+/// 4. Writes one new `pack-<checksum>.pack` + `.idx` pair
+/// 5. With `-d`, deletes the loose files and old packs the new pack replaces
+///
+/// ## Educational Insights:
+/// - **Loose vs Packed**: Every `add`/`commit` writes one zlib file per object;
+///   packing turns thousands of small files into a single file
+/// - **Delta Compression**: Versions of the same file usually differ by a few
+///   lines, so storing "copy these bytes from the old version" is much smaller
+/// - **Safety First**: The new pack is read back and every object re-hashed
+///   before anything is deleted
+///
+/// ## Visual Guide - Before and After:
+/// ```text
+/// objects/                          objects/
+/// ├── 5a/1b2c...   (blob v1)        └── pack/
+/// ├── 7f/0e9d...   (blob v2)  ──▶       ├── pack-3c4d....pack
+/// ├── ab/cd12...   (tree)               └── pack-3c4d....idx
+/// └── fe/dcba...   (commit)
+/// ```
+pub struct RepackCommand;
+
+/// Options for the repack command
+#[derive(Debug, Clone)]
+pub struct RepackOptions {
+    /// Pack every object, including those already in packs (`-a`)
+    pub all: bool,
+    /// Delete loose objects and packs made redundant by the new pack (`-d`)
+    pub delete_redundant: bool,
+    /// Number of objects considered as delta bases for each object
+    pub window: usize,
+    /// Maximum delta chain length
+    pub depth: usize,
+}
+
+impl Default for RepackOptions {
+    fn default() -> Self {
+        Self {
+            all: false,
+            delete_redundant: false,
+            window: 10,
+            depth: 50,
+        }
+    }
+}
+
+impl RepackOptions {
+    /// Options used by `gc`: pack everything and clean up afterwards
+    pub fn gc() -> Self {
+        Self {
+            all: true,
+            delete_redundant: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Result of a repack operation
+#[derive(Debug, Clone, Default)]
+pub struct RepackResult {
+    /// The pack that was written (None when there was nothing to pack)
+    pub pack: Option<WrittenPack>,
+    pub objects_packed: usize,
+    pub deltas: usize,
+    pub loose_objects_removed: usize,
+    pub packs_removed: usize,
+}
+
+impl RepackResult {
+    /// Get a summary of the repack operation
+    pub fn summary(&self) -> String {
+        match &self.pack {
+            Some(pack) => format!(
+                "📦 Packed {} object(s) ({} as deltas) into {}\n🧹 Removed {} loose object(s) and {} old pack(s)",
+                self.objects_packed,
+                self.deltas,
+                pack.pack_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                self.loose_objects_removed,
+                self.packs_removed
+            ),
+            None => "✨ Nothing new to pack".to_string(),
+        }
+    }
+}
+
+impl RepackCommand {
+    /// Repack the objects of the repository at `repo_path`
+    pub fn repack<P: AsRef<Path>>(
+        repo_path: P,
+        options: RepackOptions,
+    ) -> crate::Result<RepackResult> {
+        Self::repack_with_compat(repo_path, options, GitCompatMode::Educational)
+    }
+
+    /// Repack objects with git compatibility mode
+    ///
+    /// # Arguments
+    /// * `repo_path` - Path to the repository root
+    /// * `options` - Repack options
+    /// * `git_compat` - Git compatibility mode
+    ///
+    /// # Returns
+    /// * `Ok(RepackResult)` - What was packed and removed
+    /// * `Err(...)` - If reading objects or writing the pack failed
+    pub fn repack_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        options: RepackOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<RepackResult> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        let pack_dir = repo.objects_dir().join("pack");
+        let object_store = ObjectStore::for_repository(&repo);
+
+        let loose_objects = object_store.list_loose_objects()?;
+        let existing_packs = object_store.packs()?;
+        let old_packs: Vec<PathBuf> = existing_packs
+            .iter()
+            .map(|pack| pack.pack_path().to_path_buf())
+            .collect();
+
+        // Without -a, objects that are already packed stay where they are.
+        // Objects borrowed from alternates are never copied in.
+        let to_pack: Vec<ObjectHash> = if options.all {
+            object_store.list_local_objects()?
+        } else {
+            loose_objects
+                .iter()
+                .filter(|hash| !existing_packs.iter().any(|pack| pack.contains(hash)))
+                .cloned()
+                .collect()
+        };
+
+        let mut result = RepackResult::default();
+        if to_pack.is_empty() && !options.delete_redundant {
+            println!("✨ Nothing new to pack");
+            return Ok(result);
+        }
+
+        println!("🔍 Collecting {} object(s)...", to_pack.len());
+        let inputs = Self::collect_inputs(&object_store, &to_pack)?;
+
+        if !inputs.is_empty() {
+            println!(
+                "🗜️  Compressing objects (window {}, depth {})...",
+                options.window, options.depth
+            );
+            let written = PackWriter::new()
+                .with_format(object_store.format())
+                .with_window(options.window)
+                .with_max_depth(options.depth)
+                .write(inputs, &pack_dir)?;
+
+            println!("🔎 Verifying {}...", written.pack_path.display());
+            Self::verify_pack(&written, &to_pack, object_store.format())?;
+
+            result.objects_packed = written.object_count;
+            result.deltas = written.delta_count;
+            result.pack = Some(written);
+        }
+
+        if options.delete_redundant {
+            // Only loose objects that are now in some pack may be deleted
+            let packed: HashSet<&ObjectHash> = to_pack.iter().collect();
+            for hash in &loose_objects {
+                let covered =
+                    packed.contains(hash) || existing_packs.iter().any(|pack| pack.contains(hash));
+                if covered {
+                    fs::remove_file(object_store.get_object_path(hash))?;
+                    result.loose_objects_removed += 1;
+                }
+            }
+            Self::remove_empty_fanout_dirs(object_store.objects_dir())?;
+
+            if options.all {
+                let new_pack = result.pack.as_ref().map(|pack| pack.pack_path.clone());
+                for pack_path in old_packs {
+                    if Some(&pack_path) == new_pack.as_ref() {
+                        continue;
+                    }
+                    fs::remove_file(pack_path.with_extension("idx"))?;
+                    fs::remove_file(&pack_path)?;
+                    result.packs_removed += 1;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Read the objects to pack, using tree entries as path hints
+    fn collect_inputs(
+        object_store: &ObjectStore,
+        hashes: &[ObjectHash],
+    ) -> crate::Result<Vec<PackInput>> {
+        let mut raw_objects = Vec::with_capacity(hashes.len());
+        let mut path_hints: HashMap<ObjectHash, String> = HashMap::new();
+
+        for hash in hashes {
+            let (object_type, data) = object_store.load_raw_object(hash)?;
+            if object_type == GitObjectType::Tree {
+                if let GitObject::Tree(tree) =
+                    object_store.parse_object_content(&object_type, &data)?
+                {
+                    for entry in tree.entries {
+                        path_hints.entry(entry.hash).or_insert(entry.name);
+                    }
+                }
+            }
+            raw_objects.push((*hash, object_type, data));
+        }
+
+        Ok(raw_objects
+            .into_iter()
+            .map(|(hash, object_type, data)| {
+                let hint = path_hints.get(&hash).cloned();
+                let input = PackInput::new(hash, PackObjectType::from(&object_type), data);
+                match hint {
+                    Some(path) => input.with_path_hint(path),
+                    None => input,
+                }
+            })
+            .collect())
+    }
+
+    /// Read every object back from the new pack and check its hash
+    fn verify_pack(
+        written: &WrittenPack,
+        expected: &[ObjectHash],
+        format: ObjectFormat,
+    ) -> crate::Result<()> {
+        let reader = PackReader::open(&written.idx_path, format)?;
+
+        for hash in expected {
+            let (pack_type, data) = reader
+                .read_object(hash)?
+                .ok_or_else(|| format!("Object {} missing from new pack", hash))?;
+            let type_name = pack_type
+                .type_name()
+                .ok_or_else(|| format!("Unresolved delta for object {}", hash))?;
+            if &format.hash_object(type_name, &data) != hash {
+                return Err(format!("Object {} is corrupt in new pack", hash).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove `objects/XX` directories left empty after deleting loose objects
+    fn remove_empty_fanout_dirs(objects_dir: &Path) -> crate::Result<()> {
+        for entry in fs::read_dir(objects_dir)? {
+            let path = entry?.path();
+            let is_fanout = path
+                .file_name()
+                .is_some_and(|name| name.len() == 2 && name != "..");
+            if is_fanout && path.is_dir() && fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init::InitCommand;
+    use tempfile::TempDir;
+
+    fn setup_repo_with_objects() -> (TempDir, ObjectStore, Vec<ObjectHash>) {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+
+        let mut content: String = (0..300).map(|i| format!("line {}\n", i)).collect();
+        let mut hashes = Vec::new();
+        for version in 0..3 {
+            content.push_str(&format!("version {}\n", version));
+            let blob = GitObject::Blob(BlobObject::new(content.clone().into_bytes()));
+            hashes.push(store.store_object(&blob).unwrap());
+        }
+
+        let mut tree = TreeObject::new();
+        tree.add_entry(TreeEntry::new(
+            FileMode::Regular,
+            "notes.txt".to_string(),
+            hashes[2],
+        ));
+        let tree = GitObject::Tree(tree);
+        hashes.push(store.store_object(&tree).unwrap());
+
+        (temp_dir, store, hashes)
+    }
+
+    #[test]
+    fn test_repack_moves_loose_objects_into_pack() {
+        let (temp_dir, _, hashes) = setup_repo_with_objects();
+
+        let options = RepackOptions {
+            delete_redundant: true,
+            ..Default::default()
+        };
+        let result = RepackCommand::repack(temp_dir.path(), options).unwrap();
+
+        assert_eq!(result.objects_packed, 4);
+        assert_eq!(result.deltas, 2);
+        assert_eq!(result.loose_objects_removed, 4);
+
+        // A fresh store finds everything in the pack
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+        assert!(store.list_loose_objects().unwrap().is_empty());
+        for hash in &hashes {
+            assert!(store.object_exists(hash));
+            store.load_object(hash).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_gc_consolidates_packs() {
+        let (temp_dir, store, hashes) = setup_repo_with_objects();
+        RepackCommand::repack(temp_dir.path(), RepackOptions::gc()).unwrap();
+
+        let extra = GitObject::Blob(BlobObject::new(b"later".to_vec()));
+        let extra_hash = store.store_object(&extra).unwrap();
+
+        let result = RepackCommand::repack(temp_dir.path(), RepackOptions::gc()).unwrap();
+        assert_eq!(result.objects_packed, 5);
+        assert_eq!(result.loose_objects_removed, 1);
+        assert_eq!(result.packs_removed, 1);
+
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+        assert_eq!(store.packs().unwrap().len(), 1);
+        for hash in hashes.iter().chain([&extra_hash]) {
+            store.load_object(hash).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_gc_leaves_alternate_objects_out() {
+        use crate::application::{
+            AddCommand, AddOptions, CloneCommand, CloneOptions, CommitCommand, CommitOptions,
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        InitCommand::init(Some(&source)).unwrap();
+        fs::write(source.join("README"), "hello\n").unwrap();
+        AddCommand::add(&source, &["README".to_string()], AddOptions::default()).unwrap();
+        CommitCommand::commit(&source, "Initial", CommitOptions::default()).unwrap();
+
+        let shared = temp_dir.path().join("shared");
+        let options = CloneOptions {
+            shared: true,
+            progress: false,
+            ..Default::default()
+        };
+        CloneCommand::clone(source.to_str().unwrap(), shared.to_str(), options).unwrap();
+        let store = ObjectStore::new(shared.join(".git-rs/objects"));
+        let borrowed = store.list_objects().unwrap();
+        assert_eq!(borrowed.len(), 3);
+        let local = store
+            .store_object(&GitObject::Blob(BlobObject::new(b"local".to_vec())))
+            .unwrap();
+
+        let result = RepackCommand::repack(&shared, RepackOptions::gc()).unwrap();
+        assert_eq!(result.objects_packed, 1);
+
+        // The pack holds only the local object; the rest is still borrowed
+        let store = ObjectStore::new(shared.join(".git-rs/objects"));
+        let packs = store.packs().unwrap();
+        assert_eq!(packs.len(), 1);
+        assert_eq!(packs[0].index().hashes(), vec![local]);
+        for hash in &borrowed {
+            store.load_object(hash).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_repack_without_objects() {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        let result = RepackCommand::repack(temp_dir.path(), RepackOptions::default()).unwrap();
+        assert!(result.pack.is_none());
+        assert_eq!(result.objects_packed, 0);
+    }
+}
@@ -0,0 +1,346 @@
+use std::path::Path;
+
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::domain::revision::RevisionRange;
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+use crate::infrastructure::revision::RevisionResolver;
+
+/// Git Rev-Parse Use Case
+///
+/// This implements the `git rev-parse` plumbing command: turning revision
+/// expressions into object ids.
+///
+/// ## Revision Syntax:
+/// ```text
+/// HEAD, @, main, v1.0, refs/remotes/origin/main   names
+/// 3b18e51                                          abbreviated id (4+ digits)
+/// HEAD~3                                           third first-parent ancestor
+/// main^2                                           second parent of a merge
+/// v1.0^{tree}, v1.0^{}                             peel to a type / past tags
+/// HEAD:src/main.rs                                 object at a path in a tree
+/// A..B                                             B, excluding A's history
+/// A...B                                            A and B, excluding merge bases
+/// ```
+///
+/// ## Visual Guide - Ancestry:
+/// ```text
+///        HEAD~2      HEAD~1       HEAD
+///   ──▶ o ─────────▶ o ─────────▶ M   M^1 = HEAD~1 (first parent)
+///                               ▲
+///                    o ─────────┘     M^2 (second parent)
+/// ```
+///
+/// ## Educational Insights:
+/// - **Every Command Speaks This**: `log`, `cat-file`, `commit-tree` and
+///   `update-ref` all resolve their arguments the same way
+/// - **Ranges Are Sets**: `A..B` is printed as `B ^A`: include B's history,
+///   exclude A's
+/// - **Abbreviations Must Be Unique**: A short id that matches two objects
+///   is an error, not a guess
+pub struct RevParseCommand;
+
+/// Options for the rev-parse command
+#[derive(Debug, Clone, Default)]
+pub struct RevParseOptions {
+    /// Require exactly one single revision (`--verify`)
+    pub verify: bool,
+    /// Print ids abbreviated to at least this many digits (`--short`)
+    pub short: Option<usize>,
+}
+
+impl RevParseCommand {
+    /// Resolve each argument and return the lines `rev-parse` prints
+    ///
+    /// Excluded commits of a range are returned with a leading `^`.
+    pub fn rev_parse_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        revisions: &[String],
+        options: &RevParseOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<Vec<String>> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        let object_store = ObjectStore::for_repository(&repo);
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let resolver = RevisionResolver::new(&object_store, &ref_store);
+
+        if options.verify {
+            let single = match revisions {
+                [revision] => {
+                    matches!(RevisionRange::parse(revision), Ok(RevisionRange::Single(_)))
+                }
+                _ => false,
+            };
+            if !single {
+                return Err("Needed a single revision".into());
+            }
+        }
+
+        let format = |hash: &ObjectHash| -> crate::Result<String> {
+            match options.short {
+                Some(len) => resolver.abbreviate(hash, len),
+                None => Ok(hash.to_string()),
+            }
+        };
+
+        let mut lines = Vec::new();
+        for revision in revisions {
+            let set = resolver.resolve_range(revision).map_err(|e| {
+                if options.verify {
+                    "Needed a single revision".into()
+                } else {
+                    e
+                }
+            })?;
+            for hash in &set.include {
+                lines.push(format(hash)?);
+            }
+            for hash in &set.exclude {
+                lines.push(format!("^{}", format(hash)?));
+            }
+        }
+
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        AddCommand, AddOptions, CommitCommand, CommitOptions, InitCommand, TagCommand, TagOptions,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// main: c1 ── c2 ── merge        side: c1 ── c3 ──┘ (second parent)
+    struct History {
+        temp_dir: TempDir,
+        c1: ObjectHash,
+        c2: ObjectHash,
+        c3: ObjectHash,
+        merge: ObjectHash,
+    }
+
+    fn setup_history() -> History {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path();
+        InitCommand::init(Some(path)).unwrap();
+
+        fs::create_dir_all(path.join("src")).unwrap();
+        fs::write(path.join("src/main.rs"), "fn main() {}\n").unwrap();
+        AddCommand::add(path, &[".".to_string()], AddOptions::default()).unwrap();
+        let c1 = CommitCommand::commit(path, "c1", CommitOptions::default())
+            .unwrap()
+            .commit_hash;
+
+        fs::write(path.join("README"), "readme\n").unwrap();
+        AddCommand::add(path, &["README".to_string()], AddOptions::default()).unwrap();
+        let c2 = CommitCommand::commit(path, "c2", CommitOptions::default())
+            .unwrap()
+            .commit_hash;
+
+        let object_store = ObjectStore::new(path.join(".git-rs/objects"));
+        let commit = |parents: Vec<ObjectHash>, message: &str| {
+            let tree = object_store
+                .load_object(&c2)
+                .unwrap()
+                .as_commit()
+                .unwrap()
+                .tree;
+            let author = Signature::new("A".to_string(), "a@example.com".to_string());
+            let commit = CommitObject::new(tree, parents, author, format!("{}\n", message));
+            object_store
+                .store_object(&GitObject::Commit(commit))
+                .unwrap()
+        };
+        let c3 = commit(vec![c1], "c3");
+        let merge = commit(vec![c2, c3], "merge");
+
+        let ref_store = RefStore::new(path.join(".git-rs"));
+        ref_store.write_ref("refs/heads/side", &c3).unwrap();
+        ref_store.write_ref("refs/heads/main", &merge).unwrap();
+        ref_store.set_head_to_branch("main").unwrap();
+        TagCommand::create(
+            path,
+            "v1.0",
+            Some("side"),
+            TagOptions {
+                message: Some("release".to_string()),
+                ..Default::default()
+            },
+            GitCompatMode::Educational,
+        )
+        .unwrap();
+
+        History {
+            temp_dir,
+            c1,
+            c2,
+            c3,
+            merge,
+        }
+    }
+
+    fn rev_parse(history: &History, revisions: &[&str]) -> crate::Result<Vec<String>> {
+        let revisions: Vec<String> = revisions.iter().map(|r| r.to_string()).collect();
+        RevParseCommand::rev_parse_with_compat(
+            history.temp_dir.path(),
+            &revisions,
+            &RevParseOptions::default(),
+            GitCompatMode::Educational,
+        )
+    }
+
+    #[test]
+    fn test_rev_parse_navigation() {
+        let history = setup_history();
+        let one = |revision: &str| rev_parse(&history, &[revision]).unwrap().join("\n");
+
+        assert_eq!(one("HEAD"), history.merge.to_string());
+        assert_eq!(one("@"), history.merge.to_string());
+        assert_eq!(one("main^"), history.c2.to_string());
+        assert_eq!(one("main^2"), history.c3.to_string());
+        assert_eq!(one("HEAD~2"), history.c1.to_string());
+        assert_eq!(one("HEAD^2~1"), history.c1.to_string());
+        assert_eq!(one("v1.0^{commit}"), history.c3.to_string());
+        assert_eq!(one("v1.0^{}"), history.c3.to_string());
+        assert_eq!(one(&history.c3.to_short_hex(6)), history.c3.to_string());
+
+        let object_store = ObjectStore::new(history.temp_dir.path().join(".git-rs/objects"));
+        let tree = object_store
+            .load_object(&history.c1)
+            .unwrap()
+            .as_commit()
+            .unwrap()
+            .tree;
+        assert_eq!(one("HEAD~2^{tree}"), tree.to_string());
+        assert_eq!(one("HEAD~2:"), tree.to_string());
+        assert_eq!(
+            one("HEAD:src/main.rs"),
+            object_store
+                .hash_file(&history.temp_dir.path().join("src/main.rs"))
+                .unwrap()
+                .to_string()
+        );
+
+        assert!(rev_parse(&history, &["HEAD^3"]).is_err());
+        assert!(rev_parse(&history, &["HEAD~5"]).is_err());
+        assert!(rev_parse(&history, &["HEAD:missing"]).is_err());
+        assert!(rev_parse(&history, &["HEAD^{tag}"]).is_err());
+        assert!(rev_parse(&history, &["nope"]).is_err());
+    }
+
+    #[test]
+    fn test_rev_parse_ranges_and_options() {
+        let history = setup_history();
+
+        assert_eq!(
+            rev_parse(&history, &["side..main"]).unwrap(),
+            vec![history.merge.to_string(), format!("^{}", history.c3)]
+        );
+        assert_eq!(
+            rev_parse(&history, &["main~1...side"]).unwrap(),
+            vec![
+                history.c3.to_string(),
+                history.c2.to_string(),
+                format!("^{}", history.c1)
+            ]
+        );
+
+        let verify = RevParseOptions {
+            verify: true,
+            short: Some(7),
+        };
+        let run = |revisions: &[&str]| {
+            let revisions: Vec<String> = revisions.iter().map(|r| r.to_string()).collect();
+            RevParseCommand::rev_parse_with_compat(
+                history.temp_dir.path(),
+                &revisions,
+                &verify,
+                GitCompatMode::Educational,
+            )
+        };
+        assert_eq!(run(&["HEAD"]).unwrap(), vec![history.merge.to_short_hex(7)]);
+        assert!(run(&["side..main"]).is_err());
+        assert!(run(&["HEAD", "side"]).is_err());
+        assert!(run(&["nope"]).is_err());
+    }
+
+    #[test]
+    fn test_rev_parse_merge_bases() {
+        let history = setup_history();
+        let path = history.temp_dir.path();
+        let object_store = ObjectStore::new(path.join(".git-rs/objects"));
+        let ref_store = RefStore::new(path.join(".git-rs"));
+        let tree = object_store
+            .load_object(&history.c1)
+            .unwrap()
+            .as_commit()
+            .unwrap()
+            .tree;
+        let mut count = 0;
+        let mut commit = |parents: Vec<ObjectHash>| {
+            count += 1;
+            let mut author = Signature::new("A".to_string(), "a@example.com".to_string());
+            author.timestamp += chrono::Duration::seconds(count);
+            let commit = CommitObject::new(tree, parents, author, format!("commit {}\n", count));
+            object_store
+                .store_object(&GitObject::Commit(commit))
+                .unwrap()
+        };
+
+        // Criss-cross: each side merged the other, so both are best bases
+        let x1 = commit(vec![history.c3]);
+        let y1 = commit(vec![history.c3]);
+        let x2 = commit(vec![x1, y1]);
+        let y2 = commit(vec![y1, x1]);
+        ref_store.write_ref("refs/heads/x", &x2).unwrap();
+        ref_store.write_ref("refs/heads/y", &y2).unwrap();
+        let mut bases = rev_parse(&history, &["x...y"]).unwrap().split_off(2);
+        bases.sort();
+        let mut expected = vec![format!("^{}", x1), format!("^{}", y1)];
+        expected.sort();
+        assert_eq!(bases, expected);
+
+        // A long history is walked only down to the merge base
+        let mut tip = history.merge;
+        for _ in 0..500 {
+            tip = commit(vec![tip]);
+        }
+        ref_store.write_ref("refs/heads/long", &tip).unwrap();
+        assert_eq!(
+            rev_parse(&history, &["long~1...long~2"]).unwrap()[2],
+            format!("^{}", commit_at(&object_store, tip, 2))
+        );
+        assert_eq!(
+            rev_parse(&history, &["long...side"]).unwrap()[2],
+            format!("^{}", history.c3)
+        );
+    }
+
+    fn commit_at(
+        object_store: &ObjectStore,
+        mut hash: ObjectHash,
+        generations: usize,
+    ) -> ObjectHash {
+        for _ in 0..generations {
+            hash = object_store
+                .load_object(&hash)
+                .unwrap()
+                .as_commit()
+                .unwrap()
+                .parents[0];
+        }
+        hash
+    }
+}
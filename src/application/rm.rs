@@ -0,0 +1,400 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::domain::index::GitIndex;
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::index_store::IndexStore;
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+use crate::infrastructure::working_tree::WorkingTree;
+
+/// Git Rm Use Case
+///
+/// This implements the `git rm` command: removing paths from the index so
+/// the next commit records their deletion, and from the working tree unless
+/// `--cached` is given.
+///
+/// ## Usage:
+/// ```text
+/// $ git-rs rm old.txt               untrack old.txt and delete it
+/// $ git-rs rm --cached secret.env   untrack, but keep the file on disk
+/// $ git-rs rm -r build              everything tracked under build/
+/// ```
+///
+/// ## Visual Guide - The Safety Check:
+/// ```text
+/// HEAD ───(1)─── index ───(2)─── working tree
+///
+/// (1) changes staged in the index     would be lost by `rm`
+/// (2) local modifications on disk     would be lost by `rm`
+/// (1) and (2) together                would be lost even by `rm --cached`
+/// ```
+///
+/// Without `-f` nothing is removed when content would be lost.
+///
+/// ## Educational Insights:
+/// - **Deleting Is Not Removing**: A file deleted from disk is still in the
+///   index, so the next commit would still contain it; removing the index
+///   entry is what records the deletion
+/// - **Untracking Keeps History**: `rm --cached` is how a file that should
+///   have been ignored leaves the repository while staying on disk; older
+///   commits still contain it
+/// - **All or Nothing**: Every path is checked before anything is removed
+pub struct RmCommand;
+
+/// Options for the rm command
+#[derive(Debug, Clone, Default)]
+pub struct RmOptions {
+    /// Only remove paths from the index, keeping the files (`--cached`)
+    pub cached: bool,
+    /// Allow a directory to remove everything tracked under it (`-r`)
+    pub recursive: bool,
+    /// Remove even when content would be lost (`-f`)
+    pub force: bool,
+}
+
+/// Result of the rm operation
+#[derive(Debug, Clone, Default)]
+pub struct RmResult {
+    /// Paths removed from the index, sorted
+    pub removed: Vec<PathBuf>,
+}
+
+impl RmCommand {
+    /// Remove the paths matching `pathspecs` from the index, and the working tree
+    pub fn rm_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        pathspecs: &[String],
+        options: &RmOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<RmResult> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+        if pathspecs.is_empty() {
+            return Err("No pathspec was given. Which files should I remove?".into());
+        }
+
+        let object_store = ObjectStore::for_repository(&repo);
+        let index_store = IndexStore::new(repo.index_path());
+        let mut index = index_store.load_index()?;
+
+        let paths = Self::match_pathspecs(&repo, &index, pathspecs, options)?;
+        if !options.force {
+            Self::check_local_modifications(&repo, &object_store, &index, &paths, options)?;
+        }
+
+        for path in &paths {
+            index.remove_entry(path);
+        }
+        index_store.save_index(&index)?;
+
+        if !options.cached {
+            let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+            for path in &paths {
+                working_tree.remove_path(path)?;
+            }
+        }
+
+        Ok(RmResult {
+            removed: paths.into_iter().collect(),
+        })
+    }
+
+    /// Find the tracked paths each pathspec names: a file, or with `-r`
+    /// everything under a directory (`.` is the whole repository)
+    fn match_pathspecs(
+        repo: &GitRepository,
+        index: &GitIndex,
+        pathspecs: &[String],
+        options: &RmOptions,
+    ) -> crate::Result<BTreeSet<PathBuf>> {
+        let tracked: Vec<&PathBuf> = index.entries.keys().chain(index.conflicts.keys()).collect();
+        let mut paths = BTreeSet::new();
+
+        for pathspec in pathspecs {
+            let prefix = repo.normalize_path(pathspec)?;
+            let mut matched = false;
+
+            for path in &tracked {
+                if **path != prefix && !path.starts_with(&prefix) {
+                    continue;
+                }
+                if **path != prefix && !options.recursive {
+                    return Err(
+                        format!("not removing '{}' recursively without -r", pathspec).into(),
+                    );
+                }
+                paths.insert(path.to_path_buf());
+                matched = true;
+            }
+
+            if !matched {
+                return Err(format!("pathspec '{}' did not match any files", pathspec).into());
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Refuse to remove paths whose staged or local changes would be lost
+    ///
+    /// Unmerged paths are not checked: there is no single staged version,
+    /// and removing the path is one way of resolving the conflict.
+    fn check_local_modifications(
+        repo: &GitRepository,
+        object_store: &ObjectStore,
+        index: &GitIndex,
+        paths: &BTreeSet<PathBuf>,
+        options: &RmOptions,
+    ) -> crate::Result<()> {
+        let head_files = Self::head_files(repo, object_store)?;
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+        let mut staged_and_local = Vec::new();
+        let mut staged = Vec::new();
+        let mut local = Vec::new();
+
+        for path in paths {
+            let Some(entry) = index.entries.get(path) else {
+                continue;
+            };
+            let state = (entry.mode, entry.hash);
+
+            // Before the first commit everything staged counts as a change
+            let staged_changes = head_files.get(path) != Some(&state);
+            let local_changes = match working_tree.file_mode(path)? {
+                None | Some(FileMode::Directory) => false,
+                Some(_) => working_tree.hash_path(object_store, path)? != state,
+            };
+
+            if staged_changes && local_changes {
+                // An intent-to-add entry has no staged content to lose
+                if !options.cached || !entry.intent_to_add {
+                    staged_and_local.push(path);
+                }
+            } else if !options.cached {
+                if staged_changes {
+                    staged.push(path);
+                }
+                if local_changes {
+                    local.push(path);
+                }
+            }
+        }
+
+        let mut problems = Vec::new();
+        let mut report = |files: &[&PathBuf], what: &str, hint: &str| {
+            if files.is_empty() {
+                return;
+            }
+            let subject = if files.len() == 1 {
+                "file has"
+            } else {
+                "files have"
+            };
+            let list: Vec<String> = files
+                .iter()
+                .map(|path| format!("    {}", path.display()))
+                .collect();
+            problems.push(format!(
+                "the following {} {}:\n{}\n{}",
+                subject,
+                what,
+                list.join("\n"),
+                hint
+            ));
+        };
+        let keep_hint = "(use --cached to keep the file, or -f to force removal)";
+        report(
+            &staged_and_local,
+            "staged content different from both the file and the HEAD",
+            "(use -f to force removal)",
+        );
+        report(&staged, "changes staged in the index", keep_hint);
+        report(&local, "local modifications", keep_hint);
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("\n").into())
+        }
+    }
+
+    /// Mode and id of every file in the HEAD commit (none before the first commit)
+    fn head_files(
+        repo: &GitRepository,
+        object_store: &ObjectStore,
+    ) -> crate::Result<HashMap<PathBuf, (FileMode, ObjectHash)>> {
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let Some(head) = ref_store.get_head()? else {
+            return Ok(HashMap::new());
+        };
+
+        let commit_hash = object_store.peel_to_commit(&head)?;
+        let commit = match object_store.load_object(&commit_hash)? {
+            GitObject::Commit(commit) => commit,
+            _ => return Err(format!("{} is not a commit", commit_hash).into()),
+        };
+
+        Ok(object_store
+            .flatten_tree(&commit.tree)?
+            .into_iter()
+            .map(|(path, entry)| (path, (entry.mode, entry.hash)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{
+        AddCommand, AddOptions, CommitCommand, CommitOptions, InitCommand, StatusCommand,
+        StatusOptions,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path();
+        InitCommand::init(Some(path)).unwrap();
+
+        fs::create_dir_all(path.join("dir/sub")).unwrap();
+        fs::write(path.join("a.txt"), "a\n").unwrap();
+        fs::write(path.join("dir/b.txt"), "b\n").unwrap();
+        fs::write(path.join("dir/sub/c.txt"), "c\n").unwrap();
+        AddCommand::add(path, &[".".to_string()], AddOptions::default()).unwrap();
+        CommitCommand::commit(path, "Initial commit", CommitOptions::default()).unwrap();
+        temp_dir
+    }
+
+    fn rm(temp_dir: &TempDir, paths: &[&str], options: RmOptions) -> crate::Result<RmResult> {
+        let paths: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+        RmCommand::rm_with_compat(
+            temp_dir.path(),
+            &paths,
+            &options,
+            GitCompatMode::Educational,
+        )
+    }
+
+    fn tracked(temp_dir: &TempDir) -> Vec<PathBuf> {
+        let index = IndexStore::new(temp_dir.path().join(".git-rs/git-rs-index"))
+            .load_index()
+            .unwrap();
+        let mut paths: Vec<PathBuf> = index.entries.into_keys().collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_rm_removes_from_index_and_working_tree() {
+        let temp_dir = setup_repo();
+        let path = temp_dir.path();
+
+        let result = rm(&temp_dir, &["a.txt"], RmOptions::default()).unwrap();
+        assert_eq!(result.removed, vec![PathBuf::from("a.txt")]);
+        assert!(!path.join("a.txt").exists());
+
+        let error = rm(&temp_dir, &["dir"], RmOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("without -r"), "{}", error);
+        assert!(rm(&temp_dir, &["missing.txt"], RmOptions::default()).is_err());
+        assert!(rm(&temp_dir, &["../a.txt"], RmOptions::default()).is_err());
+
+        // Directories left empty go too; Git does not track directories
+        let recursive = RmOptions {
+            recursive: true,
+            ..Default::default()
+        };
+        let result = rm(&temp_dir, &["./dir/"], recursive).unwrap();
+        assert_eq!(
+            result.removed,
+            vec![PathBuf::from("dir/b.txt"), PathBuf::from("dir/sub/c.txt")]
+        );
+        assert!(!path.join("dir").exists());
+        assert!(tracked(&temp_dir).is_empty());
+
+        let status = StatusCommand::status(path, StatusOptions::default()).unwrap();
+        assert_eq!(status.file_changes.staged_deleted.len(), 3);
+        assert!(status.file_changes.deleted.is_empty());
+    }
+
+    #[test]
+    fn test_rm_cached_keeps_the_file() {
+        let temp_dir = setup_repo();
+        let path = temp_dir.path();
+
+        // A file already deleted from disk only needs its entry removed
+        fs::remove_file(path.join("dir/b.txt")).unwrap();
+        let cached = RmOptions {
+            cached: true,
+            ..Default::default()
+        };
+        rm(&temp_dir, &["a.txt"], cached).unwrap();
+        rm(&temp_dir, &["dir/b.txt"], RmOptions::default()).unwrap();
+
+        assert!(path.join("a.txt").exists());
+        assert_eq!(tracked(&temp_dir), vec![PathBuf::from("dir/sub/c.txt")]);
+        let status = StatusCommand::status(path, StatusOptions::default()).unwrap();
+        assert_eq!(status.file_changes.untracked, vec![PathBuf::from("a.txt")]);
+    }
+
+    #[test]
+    fn test_rm_refuses_to_lose_changes() {
+        let temp_dir = setup_repo();
+        let path = temp_dir.path();
+        let cached = || RmOptions {
+            cached: true,
+            ..Default::default()
+        };
+
+        // Local modifications: only --cached (or -f) may remove the entry
+        fs::write(path.join("a.txt"), "edited\n").unwrap();
+        let error = rm(&temp_dir, &["a.txt"], RmOptions::default()).unwrap_err();
+        assert!(
+            error.to_string().contains("local modifications"),
+            "{}",
+            error
+        );
+        assert!(path.join("a.txt").exists());
+
+        // Staged changes that differ from the file too: even --cached refuses
+        AddCommand::add(path, &["a.txt".to_string()], AddOptions::default()).unwrap();
+        fs::write(path.join("a.txt"), "edited again\n").unwrap();
+        let error = rm(&temp_dir, &["a.txt"], cached()).unwrap_err();
+        assert!(
+            error.to_string().contains("staged content different"),
+            "{}",
+            error
+        );
+
+        // A new file only exists in the index and the working tree
+        fs::write(path.join("new.txt"), "new\n").unwrap();
+        AddCommand::add(path, &["new.txt".to_string()], AddOptions::default()).unwrap();
+        let error = rm(&temp_dir, &["new.txt"], RmOptions::default()).unwrap_err();
+        assert!(
+            error.to_string().contains("changes staged in the index"),
+            "{}",
+            error
+        );
+        rm(&temp_dir, &["new.txt"], cached()).unwrap();
+        assert!(path.join("new.txt").exists());
+
+        let force = RmOptions {
+            force: true,
+            ..Default::default()
+        };
+        rm(&temp_dir, &["a.txt"], force).unwrap();
+        assert!(!path.join("a.txt").exists());
+        assert_eq!(
+            tracked(&temp_dir),
+            vec![PathBuf::from("dir/b.txt"), PathBuf::from("dir/sub/c.txt")]
+        );
+    }
+}
@@ -43,6 +43,10 @@ use crate::infrastructure::*;
 /// - **Staged**: File has changes ready to be committed
 pub struct StatusCommand;
 
+/// Mode and object id of each file in one of the three areas; comparing the
+/// pair means a `chmod +x` shows up as a modification just like an edit
+type FileStates = std::collections::HashMap<PathBuf, (FileMode, ObjectHash)>;
+
 impl StatusCommand {
     /// Show the working tree status
     ///
@@ -57,12 +61,25 @@ impl StatusCommand {
         repo_path: P,
         options: StatusOptions,
     ) -> crate::Result<StatusResult> {
-        let repo_path = repo_path.as_ref();
-        let mut repo = GitRepository::new(repo_path);
+        Self::status_with_compat(repo_path, options, GitCompatMode::Educational)
+    }
+
+    /// Show the working tree status, with the repository layout chosen by
+    /// `git_compat`
+    pub fn status_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        options: StatusOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<StatusResult> {
+        let mut repo = GitRepository::new_with_compat(repo_path, git_compat);
 
         // Verify this is a Git repository
         if !repo.is_repository() {
-            return Err("Not a git repository (or any of the parent directories): .git".into());
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
         }
 
         println!("📊 Checking repository status...");
@@ -74,7 +91,7 @@ impl StatusCommand {
         let branch_info = Self::get_branch_info(&repo)?;
 
         // Analyze file changes
-        let file_changes = Self::analyze_file_changes(&repo)?;
+        let file_changes = Self::analyze_file_changes(&mut repo)?;
 
         // Create status result
         let mut result = StatusResult::new(branch_info);
@@ -114,19 +131,31 @@ impl StatusCommand {
     }
 
     /// Analyze file changes across working directory, index, and last commit
-    fn analyze_file_changes(repo: &GitRepository) -> crate::Result<FileChanges> {
+    fn analyze_file_changes(repo: &mut GitRepository) -> crate::Result<FileChanges> {
         let mut changes = FileChanges::new();
 
         // Get all files from different areas
-        let working_files = Self::get_working_directory_files(repo)?;
+        let working_files = Self::get_tracked_working_files(repo)?;
         let staged_files = Self::get_staged_files(repo);
         let committed_files = Self::get_committed_files(repo)?; // Will be empty until we have commits
 
-        // Build sets for comparison
-        let working_set: HashSet<PathBuf> = working_files.keys().cloned().collect();
+        // Build sets for comparison; untracked files only need to exist
+        let mut working_set = Self::get_working_directory_paths(repo)?;
+        working_set.extend(working_files.keys().cloned());
         let staged_set: HashSet<PathBuf> = staged_files.keys().cloned().collect();
         let committed_set: HashSet<PathBuf> = committed_files.keys().cloned().collect();
 
+        // Unmerged paths are only reported as such until they are resolved
+        for path in repo.index.conflicted_paths() {
+            let kind = repo.index.conflicts[path].kind();
+            changes.unmerged.push((path.clone(), kind));
+            working_set.remove(path);
+        }
+        let committed_set: HashSet<PathBuf> = committed_set
+            .into_iter()
+            .filter(|path| !repo.index.conflicts.contains_key(path))
+            .collect();
+
         // Find changes to be committed (staged vs last commit)
         for path in &staged_set {
             if !committed_set.contains(path) {
@@ -145,21 +174,16 @@ impl StatusCommand {
             }
         }
 
-        // Find changes not staged for commit (working vs staged)
+        // Find changes not staged for commit (working vs staged); a path
+        // removed from the index is a staged deletion, not tracked any more
         for path in &working_set {
-            if staged_set.contains(path) {
-                // File is tracked - check if modified
-                if working_files.get(path) != staged_files.get(path) {
-                    changes.modified.push(path.clone());
-                }
-            } else if committed_set.contains(path) {
-                // File was in last commit but not staged
+            if staged_set.contains(path) && working_files.get(path) != staged_files.get(path) {
                 changes.modified.push(path.clone());
             }
         }
 
-        // Find deleted files (in staged/committed but not in working directory)
-        for path in staged_set.union(&committed_set) {
+        // Find deleted files (staged but not in working directory)
+        for path in &staged_set {
             if !working_set.contains(path) {
                 changes.deleted.push(path.clone());
             }
@@ -167,7 +191,7 @@ impl StatusCommand {
 
         // Find untracked files
         for path in &working_set {
-            if !staged_set.contains(path) && !committed_set.contains(path) {
+            if !staged_set.contains(path) {
                 changes.untracked.push(path.clone());
             }
         }
@@ -175,20 +199,38 @@ impl StatusCommand {
         Ok(changes)
     }
 
-    /// Get all files in working directory with their content hashes
-    fn get_working_directory_files(
-        repo: &GitRepository,
-    ) -> crate::Result<std::collections::HashMap<PathBuf, ObjectHash>> {
-        let mut files = std::collections::HashMap::new();
-        Self::scan_directory_recursive(repo, repo.root_path(), &mut files)?;
-        Ok(files)
+    /// Get the mode and hash of every tracked file in the working directory
+    ///
+    /// Files whose stat data matches their index entry are not read at all.
+    /// Refreshed stat data is saved back to the index so the next run can
+    /// skip files whose content turned out to be unchanged.
+    fn get_tracked_working_files(repo: &mut GitRepository) -> crate::Result<FileStates> {
+        let object_store = ObjectStore::for_repository(repo);
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+        let refresh = working_tree.refresh_index(&object_store, &mut repo.index)?;
+        if refresh.changed {
+            IndexStore::new(repo.index_path()).update_index_if_able(&repo.index);
+        }
+        Ok(refresh.files)
+    }
+
+    /// Get the paths of all files in the working directory
+    fn get_working_directory_paths(repo: &GitRepository) -> crate::Result<HashSet<PathBuf>> {
+        let mut paths = HashSet::new();
+        let working_tree = WorkingTree::new(repo.root_path().to_path_buf());
+        Self::scan_directory_recursive(repo, &working_tree, repo.root_path(), &mut paths)?;
+        Ok(paths)
     }
 
     /// Recursively scan directory for files
+    ///
+    /// Only `lstat` is used: symlinks are listed without being followed and
+    /// a nested repository is listed as a single entry (its gitlink).
     fn scan_directory_recursive(
         repo: &GitRepository,
+        working_tree: &WorkingTree,
         dir_path: &Path,
-        files: &mut std::collections::HashMap<PathBuf, ObjectHash>,
+        paths: &mut HashSet<PathBuf>,
     ) -> crate::Result<()> {
         for entry in fs::read_dir(dir_path)? {
             let entry = entry?;
@@ -199,58 +241,54 @@ impl StatusCommand {
                 continue;
             }
 
-            if path.is_file() {
-                // Calculate hash for file content
-                match fs::read(&path) {
-                    Ok(content) => {
-                        let blob = BlobObject::new(content);
-                        let object_content = format!("blob {}\0", blob.content.len());
-                        let mut full_content = object_content.into_bytes();
-                        full_content.extend_from_slice(&blob.content);
-
-                        let hash = Self::calculate_hash(&full_content);
-                        let rel_path = repo.to_relative_path(&path)?;
-                        files.insert(rel_path, hash);
-                    }
-                    Err(e) => {
-                        // Skip files we can't read (permissions, etc.)
-                        eprintln!("⚠️  Skipping file {}: {}", path.display(), e);
-                        continue;
-                    }
+            let rel_path = repo.to_relative_path(&path)?;
+            match working_tree.file_mode(&rel_path)? {
+                Some(FileMode::Directory) => {
+                    Self::scan_directory_recursive(repo, working_tree, &path, paths)?;
+                }
+                Some(_) => {
+                    paths.insert(rel_path);
                 }
-            } else if path.is_dir() {
-                Self::scan_directory_recursive(repo, &path, files)?;
+                None => {}
             }
         }
 
         Ok(())
     }
 
-    /// Get files from staging area with their hashes
-    fn get_staged_files(repo: &GitRepository) -> std::collections::HashMap<PathBuf, ObjectHash> {
+    /// Get files from staging area with their modes and hashes
+    fn get_staged_files(repo: &GitRepository) -> FileStates {
         repo.index
             .entries
             .iter()
-            .map(|(path, entry)| (path.clone(), entry.hash.clone()))
+            .map(|(path, entry)| (path.clone(), (entry.mode, entry.hash)))
             .collect()
     }
 
-    /// Get files from last commit with their hashes
-    fn get_committed_files(
-        _repo: &GitRepository,
-    ) -> crate::Result<std::collections::HashMap<PathBuf, ObjectHash>> {
-        // TODO: Implement when we have commits
-        // For now, return empty since no commits exist yet
-        Ok(std::collections::HashMap::new())
-    }
+    /// Get files from last commit with their modes and hashes
+    ///
+    /// The commit's root tree is walked recursively, so files in
+    /// subdirectories come back with their full paths.
+    fn get_committed_files(repo: &GitRepository) -> crate::Result<FileStates> {
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let head = match ref_store.get_head()? {
+            Some(head) => head,
+            // No commits yet
+            None => return Ok(FileStates::new()),
+        };
 
-    /// Calculate SHA-1 hash of content
-    fn calculate_hash(content: &[u8]) -> ObjectHash {
-        use sha1::{Digest, Sha1};
-        let mut hasher = Sha1::new();
-        hasher.update(content);
-        let result = hasher.finalize();
-        ObjectHash::new(hex::encode(result))
+        let object_store = ObjectStore::for_repository(repo);
+        let commit_hash = object_store.peel_to_commit(&head)?;
+        let commit = match object_store.load_object(&commit_hash)? {
+            GitObject::Commit(commit) => commit,
+            _ => return Err(format!("{} is not a commit", commit_hash).into()),
+        };
+
+        Ok(object_store
+            .flatten_tree(&commit.tree)?
+            .into_iter()
+            .map(|(path, entry)| (path, (entry.mode, entry.hash)))
+            .collect())
     }
 
     /// Display status in human-readable format
@@ -265,7 +303,7 @@ impl StatusCommand {
 
         // Commit information
         match &result.branch_info.current_commit {
-            Some(commit) => println!("Latest commit: {}", &commit.as_str()[..8]),
+            Some(commit) => println!("Latest commit: {}", commit.to_short_hex(8)),
             None => println!("No commits yet"),
         }
 
@@ -292,6 +330,19 @@ impl StatusCommand {
             println!();
         }
 
+        // Unmerged paths
+        if !result.file_changes.unmerged.is_empty() {
+            println!("Unmerged paths:");
+            println!("  (use \"git-rs add <file>...\" to mark resolution)");
+            println!();
+
+            for (file, kind) in &result.file_changes.unmerged {
+                let label = format!("{}:", kind.description());
+                println!("	{:<17}{}", label, file.display());
+            }
+            println!();
+        }
+
         // Changes not staged for commit
         if !result.file_changes.modified.is_empty() || !result.file_changes.deleted.is_empty() {
             println!("Changes not staged for commit:");
@@ -351,6 +402,9 @@ pub struct FileChanges {
 
     // Untracked files
     pub untracked: Vec<PathBuf>,
+
+    // Unmerged paths (conflicts left by a merge), sorted by path
+    pub unmerged: Vec<(PathBuf, ConflictKind)>,
 }
 
 impl FileChanges {
@@ -362,6 +416,7 @@ impl FileChanges {
             modified: Vec::new(),
             deleted: Vec::new(),
             untracked: Vec::new(),
+            unmerged: Vec::new(),
         }
     }
 }
@@ -403,6 +458,7 @@ impl StatusResult {
             && self.file_changes.modified.is_empty()
             && self.file_changes.deleted.is_empty()
             && self.file_changes.untracked.is_empty()
+            && self.file_changes.unmerged.is_empty()
     }
 
     /// Check if there are staged changes
@@ -416,6 +472,7 @@ impl StatusResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{DateTime, Utc};
     use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
@@ -468,6 +525,54 @@ mod tests {
             .contains(&PathBuf::from("file2.txt")));
     }
 
+    #[test]
+    fn test_status_in_git_compatible_repository() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = temp_dir.path();
+        let compat = GitCompatMode::Compatible;
+        crate::application::InitCommand::init_with_format(
+            Some(repo_path),
+            compat,
+            ObjectFormat::Sha1,
+        )
+        .unwrap();
+        std::fs::write(repo_path.join("file.txt"), "content\n").unwrap();
+
+        // Everything goes through .git and its binary index
+        crate::application::AddCommand::add_with_compat(
+            repo_path,
+            &["file.txt".to_string()],
+            crate::application::AddOptions::default(),
+            compat,
+        )
+        .unwrap();
+        let result =
+            StatusCommand::status_with_compat(repo_path, StatusOptions::default(), compat).unwrap();
+        assert_eq!(result.file_changes.staged_new, [PathBuf::from("file.txt")]);
+
+        let options = crate::application::CommitOptions::default();
+        crate::application::CommitCommand::commit_with_compat(
+            repo_path, "Initial", options, compat,
+        )
+        .unwrap();
+        std::fs::write(repo_path.join("file.txt"), "changed\n").unwrap();
+        let result =
+            StatusCommand::status_with_compat(repo_path, StatusOptions::default(), compat).unwrap();
+        assert_eq!(result.file_changes.modified, [PathBuf::from("file.txt")]);
+        assert!(result.file_changes.untracked.is_empty());
+
+        let diff = crate::application::DiffCommand::diff_with_compat(
+            repo_path,
+            crate::application::DiffOptions::default(),
+            compat,
+        )
+        .unwrap();
+        assert_eq!(diff.files_changed, 1);
+
+        // The educational layout is not there
+        assert!(StatusCommand::status(repo_path, StatusOptions::default()).is_err());
+    }
+
     #[test]
     fn test_status_with_staged_files() {
         let (temp_dir, _repo) = create_test_repo_with_files().unwrap();
@@ -496,4 +601,217 @@ mod tests {
             .untracked
             .contains(&PathBuf::from("file2.txt")));
     }
+
+    #[test]
+    fn test_status_refreshes_stat_data() {
+        let (temp_dir, repo) = create_test_repo_with_files().unwrap();
+        let repo_path = temp_dir.path();
+        crate::application::AddCommand::add(
+            repo_path,
+            &["file1.txt".to_string(), "file2.txt".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+
+        // Touching a file changes its stat data but not its content
+        let touched = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        File::options()
+            .write(true)
+            .open(repo_path.join("file1.txt"))
+            .unwrap()
+            .set_modified(touched)
+            .unwrap();
+        let index_store = IndexStore::new(repo.index_path());
+        let entry = |name: &str| index_store.load_index().unwrap().entries[Path::new(name)].clone();
+        assert_ne!(entry("file1.txt").mtime, DateTime::<Utc>::from(touched));
+
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert!(result.file_changes.modified.is_empty());
+
+        // The new stat data is saved, so the next run need not read the file
+        assert_eq!(entry("file1.txt").mtime, DateTime::<Utc>::from(touched));
+        let metadata = fs::symlink_metadata(repo_path.join("file1.txt")).unwrap();
+        assert!(entry("file1.txt").stat_matches(&metadata));
+
+        // Content changes are still found
+        fs::write(repo_path.join("file2.txt"), "Changed content").unwrap();
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert_eq!(
+            result.file_changes.modified,
+            vec![PathBuf::from("file2.txt")]
+        );
+    }
+
+    #[test]
+    fn test_status_reports_unmerged_paths() {
+        let (temp_dir, repo) = create_test_repo_with_files().unwrap();
+        let repo_path = temp_dir.path();
+        let add = |paths: &[&str]| {
+            let paths: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+            crate::application::AddCommand::add(
+                repo_path,
+                &paths,
+                crate::application::AddOptions::default(),
+            )
+            .unwrap();
+        };
+        add(&["file1.txt", "file2.txt"]);
+        crate::application::CommitCommand::commit(
+            repo_path,
+            "Add files",
+            crate::application::CommitOptions::default(),
+        )
+        .unwrap();
+
+        // Leave the index the way a merge with conflicts does
+        let index_store = IndexStore::new(repo.index_path());
+        let mut index = index_store.load_index().unwrap();
+        let stage = |path: &str, stage: u16, content: &str| {
+            let hash = ObjectFormat::Sha1.hash_object("blob", content.as_bytes());
+            let mut entry = IndexEntry::new(
+                PathBuf::from(path),
+                hash,
+                content.len() as u64,
+                FileMode::Regular,
+            );
+            entry.stage = stage;
+            entry
+        };
+        for (number, content) in [(1, "base"), (2, "ours"), (3, "theirs")] {
+            index.add_entry(stage("file1.txt", number, content));
+        }
+        for (number, content) in [(1, "base"), (2, "ours")] {
+            index.add_entry(stage("file2.txt", number, content));
+        }
+        index_store.save_index(&index).unwrap();
+        fs::write(repo_path.join("file1.txt"), "<<<<<<< ours\n").unwrap();
+
+        // Conflicted paths are reported only as unmerged
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        let changes = &result.file_changes;
+        assert_eq!(
+            changes.unmerged,
+            vec![
+                (PathBuf::from("file1.txt"), ConflictKind::BothModified),
+                (PathBuf::from("file2.txt"), ConflictKind::DeletedByThem),
+            ]
+        );
+        assert!(changes.staged_deleted.is_empty() && changes.deleted.is_empty());
+        assert!(changes.modified.is_empty() && changes.untracked.is_empty());
+        assert!(!result.is_clean());
+
+        // Adding the file marks the conflict resolved
+        fs::write(repo_path.join("file1.txt"), "resolved\n").unwrap();
+        add(&["file1.txt"]);
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert_eq!(
+            result.file_changes.unmerged,
+            vec![(PathBuf::from("file2.txt"), ConflictKind::DeletedByThem)]
+        );
+        assert_eq!(
+            result.file_changes.staged_modified,
+            vec![PathBuf::from("file1.txt")]
+        );
+        let index = index_store.load_index().unwrap();
+        assert!(index.get_conflict(&PathBuf::from("file1.txt")).is_none());
+        assert_eq!(index.entries[Path::new("file1.txt")].stage, 0);
+    }
+
+    #[test]
+    fn test_status_after_commit_with_subdirectories() {
+        let (temp_dir, _repo) = create_test_repo_with_files().unwrap();
+        let repo_path = temp_dir.path();
+
+        std::fs::create_dir_all(repo_path.join("src/nested")).unwrap();
+        std::fs::write(repo_path.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(repo_path.join("src/nested/deep.rs"), "// deep\n").unwrap();
+
+        crate::application::AddCommand::add(
+            repo_path,
+            &["file1.txt".to_string(), "src".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+        crate::application::CommitCommand::commit(
+            repo_path,
+            "Add files",
+            crate::application::CommitOptions::default(),
+        )
+        .unwrap();
+
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert!(!result.has_staged_changes());
+        assert!(result.file_changes.modified.is_empty());
+        assert_eq!(
+            result.file_changes.untracked,
+            vec![PathBuf::from("file2.txt")]
+        );
+
+        // Modify a nested file and stage it
+        std::fs::write(repo_path.join("src/nested/deep.rs"), "// deeper\n").unwrap();
+        crate::application::AddCommand::add(
+            repo_path,
+            &["src/nested/deep.rs".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert_eq!(
+            result.file_changes.staged_modified,
+            vec![PathBuf::from("src/nested/deep.rs")]
+        );
+        assert!(result.file_changes.staged_new.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_status_reports_mode_changes_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (temp_dir, _repo) = create_test_repo_with_files().unwrap();
+        let repo_path = temp_dir.path();
+        std::os::unix::fs::symlink("file1.txt", repo_path.join("link")).unwrap();
+
+        crate::application::AddCommand::add(
+            repo_path,
+            &[".".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+        crate::application::CommitCommand::commit(
+            repo_path,
+            "Add files",
+            crate::application::CommitOptions::default(),
+        )
+        .unwrap();
+
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert!(result.is_clean());
+
+        // Only the exec bit changes; the content stays the same
+        std::fs::set_permissions(
+            repo_path.join("file2.txt"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert_eq!(
+            result.file_changes.modified,
+            vec![PathBuf::from("file2.txt")]
+        );
+
+        crate::application::AddCommand::add(
+            repo_path,
+            &["file2.txt".to_string()],
+            crate::application::AddOptions::default(),
+        )
+        .unwrap();
+        let result = StatusCommand::status(repo_path, StatusOptions::default()).unwrap();
+        assert!(result.file_changes.modified.is_empty());
+        assert_eq!(
+            result.file_changes.staged_modified,
+            vec![PathBuf::from("file2.txt")]
+        );
+    }
 }
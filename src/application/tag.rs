@@ -0,0 +1,297 @@
+use std::path::Path;
+
+use crate::application::commit::CommitCommand;
+use crate::domain::objects::*;
+use crate::domain::references::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::{
+    object_store::ObjectStore, ref_store::RefStore, revision::RevisionResolver,
+};
+
+/// Git Tag Use Case
+///
+/// This implements the `git tag` command: creating, listing and deleting tags.
+///
+/// ## Lightweight vs Annotated Tags:
+/// - **Lightweight**: `refs/tags/v1.0` simply contains a commit hash, exactly
+///   like a branch that never moves
+/// - **Annotated** (`-a -m`): a tag *object* is stored in the object database
+///   with the tagger, date and message, and the ref points at that object
+///
+/// ## Visual Guide - Annotated Tag:
+/// ```text
+/// refs/tags/v1.0 ──▶ tag object ──▶ commit ──▶ tree
+///                    (tagger,       ▲
+///                     message)      └── "peeling" follows tags down to here
+/// ```
+///
+/// ## Educational Insights:
+/// - **Peeling**: Anything that needs a commit (log, checkout) must follow
+///   the tag object to the object it points at
+/// - **Immutability**: Moving a tag requires `--force`, because others may
+///   already rely on it
+pub struct TagCommand;
+
+/// Options for creating a tag
+#[derive(Debug, Clone, Default)]
+pub struct TagOptions {
+    /// Create an annotated tag object (`-a`)
+    pub annotate: bool,
+    /// Tag message (`-m`), implies `annotate`
+    pub message: Option<String>,
+    /// Replace an existing tag (`-f`)
+    pub force: bool,
+}
+
+/// Information about a tag
+#[derive(Debug, Clone)]
+pub struct TagInfo {
+    pub name: String,
+    /// What `refs/tags/<name>` points at (a tag object for annotated tags)
+    pub hash: ObjectHash,
+    /// The object reached after peeling annotated tags
+    pub target: ObjectHash,
+    /// The tag object, for annotated tags
+    pub annotation: Option<TagObject>,
+}
+
+impl TagInfo {
+    pub fn is_annotated(&self) -> bool {
+        self.annotation.is_some()
+    }
+}
+
+impl TagCommand {
+    /// Create a tag named `name` pointing at `target` (HEAD when None)
+    ///
+    /// # Returns
+    /// * `Ok(TagInfo)` - The created tag
+    /// * `Err(...)` - If the name is invalid, the tag exists, or the target
+    ///   cannot be resolved
+    pub fn create<P: AsRef<Path>>(
+        repo_path: P,
+        name: &str,
+        target: Option<&str>,
+        options: TagOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<TagInfo> {
+        let (ref_store, object_store) = Self::open_stores(repo_path.as_ref(), git_compat)?;
+
+        if !GitRef::is_valid_name(name) {
+            return Err(format!("'{}' is not a valid tag name.", name).into());
+        }
+        if ref_store.load_ref(name, RefType::Tag)?.is_some() && !options.force {
+            return Err(format!("tag '{}' already exists", name).into());
+        }
+
+        let target_hash = Self::resolve_target(&ref_store, &object_store, target)?;
+        let (target_type, _) = object_store.load_raw_object(&target_hash)?;
+
+        let annotate = options.annotate || options.message.is_some();
+        let (ref_hash, annotation) = if annotate {
+            let mut message = options
+                .message
+                .ok_or("Annotated tags need a message (use -m <message>)")?;
+            if !message.ends_with('\n') {
+                message.push('\n');
+            }
+
+            let (tagger_name, tagger_email) = CommitCommand::get_git_config()?;
+            let tag = TagObject::new(
+                target_hash,
+                target_type,
+                name.to_string(),
+                Signature::new(tagger_name, tagger_email),
+                message,
+            );
+            let tag_hash = object_store.store_object(&GitObject::Tag(tag.clone()))?;
+            println!("🏷️  Created tag object {}", tag_hash);
+            (tag_hash, Some(tag))
+        } else {
+            (target_hash, None)
+        };
+
+        ref_store.create_tag(name, ref_hash)?;
+        println!("📌 refs/tags/{} -> {}", name, ref_hash);
+
+        Ok(TagInfo {
+            name: name.to_string(),
+            hash: ref_hash,
+            target: object_store.peel(&target_hash)?.0,
+            annotation,
+        })
+    }
+
+    /// List all tags, sorted by name
+    pub fn list<P: AsRef<Path>>(
+        repo_path: P,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<Vec<TagInfo>> {
+        let (ref_store, object_store) = Self::open_stores(repo_path.as_ref(), git_compat)?;
+
+        let refs = ref_store.load_refs()?;
+        let mut tags = Vec::new();
+
+        for tag_ref in refs.tags() {
+            let annotation = object_store.load_object(&tag_ref.hash)?.as_tag().cloned();
+            tags.push(TagInfo {
+                name: tag_ref.name.clone(),
+                hash: tag_ref.hash,
+                target: object_store.peel(&tag_ref.hash)?.0,
+                annotation,
+            });
+        }
+
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    /// Delete the tag `name`, returning the hash it pointed at
+    ///
+    /// Only the reference is removed; a tag object stays in the object
+    /// database until it is garbage collected.
+    pub fn delete<P: AsRef<Path>>(
+        repo_path: P,
+        name: &str,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectHash> {
+        let (ref_store, _) = Self::open_stores(repo_path.as_ref(), git_compat)?;
+
+        let tag_ref = ref_store
+            .load_ref(name, RefType::Tag)?
+            .ok_or_else(|| format!("tag '{}' not found.", name))?;
+        ref_store.delete_ref(&tag_ref)?;
+
+        Ok(tag_ref.hash)
+    }
+
+    fn open_stores(
+        repo_path: &Path,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<(RefStore, ObjectStore)> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        Ok((
+            RefStore::new(repo.git_dir().to_path_buf()),
+            ObjectStore::for_repository(&repo),
+        ))
+    }
+
+    /// Resolve the object to tag: any revision, HEAD by default
+    fn resolve_target(
+        ref_store: &RefStore,
+        object_store: &ObjectStore,
+        target: Option<&str>,
+    ) -> crate::Result<ObjectHash> {
+        let target = target.unwrap_or("HEAD");
+
+        RevisionResolver::new(object_store, ref_store)
+            .resolve(target)?
+            .ok_or_else(|| match target {
+                "HEAD" => "HEAD does not point to a commit yet".into(),
+                _ => format!("Not a valid object name: '{}'", target).into(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::init::InitCommand;
+    use tempfile::TempDir;
+
+    fn setup_repo_with_commit() -> (TempDir, ObjectHash) {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        let git_dir = temp_dir.path().join(".git-rs");
+        let store = ObjectStore::new(git_dir.join("objects"));
+        let tree = store
+            .store_object(&GitObject::Tree(TreeObject::new()))
+            .unwrap();
+        let author = Signature::new("Test".to_string(), "test@example.com".to_string());
+        let commit = CommitObject::new(tree, vec![], author, "Initial\n".to_string());
+        let commit_hash = store.store_object(&GitObject::Commit(commit)).unwrap();
+
+        RefStore::new(git_dir)
+            .create_branch("main", commit_hash)
+            .unwrap();
+
+        (temp_dir, commit_hash)
+    }
+
+    #[test]
+    fn test_lightweight_and_annotated_tags() {
+        let (temp_dir, commit_hash) = setup_repo_with_commit();
+        let mode = GitCompatMode::Educational;
+
+        let light =
+            TagCommand::create(temp_dir.path(), "v0.1", None, TagOptions::default(), mode).unwrap();
+        assert!(!light.is_annotated());
+        assert_eq!(light.hash, commit_hash);
+
+        let options = TagOptions {
+            annotate: true,
+            message: Some("Release 1.0".to_string()),
+            ..Default::default()
+        };
+        let annotated =
+            TagCommand::create(temp_dir.path(), "v1.0", Some("main"), options, mode).unwrap();
+        assert!(annotated.is_annotated());
+        assert_ne!(annotated.hash, commit_hash);
+        assert_eq!(annotated.target, commit_hash);
+        assert_eq!(annotated.annotation.unwrap().message, "Release 1.0\n");
+
+        let tags = TagCommand::list(temp_dir.path(), mode).unwrap();
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["v0.1", "v1.0"]);
+        assert!(tags.iter().all(|t| t.target == commit_hash));
+    }
+
+    #[test]
+    fn test_existing_tag_requires_force() {
+        let (temp_dir, _) = setup_repo_with_commit();
+        let mode = GitCompatMode::Educational;
+
+        TagCommand::create(temp_dir.path(), "v1", None, TagOptions::default(), mode).unwrap();
+        assert!(
+            TagCommand::create(temp_dir.path(), "v1", None, TagOptions::default(), mode).is_err()
+        );
+
+        let force = TagOptions {
+            force: true,
+            ..Default::default()
+        };
+        TagCommand::create(temp_dir.path(), "v1", None, force, mode).unwrap();
+
+        assert!(TagCommand::create(
+            temp_dir.path(),
+            "bad..name",
+            None,
+            TagOptions::default(),
+            mode
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_delete_tag() {
+        let (temp_dir, commit_hash) = setup_repo_with_commit();
+        let mode = GitCompatMode::Educational;
+
+        TagCommand::create(temp_dir.path(), "v1", None, TagOptions::default(), mode).unwrap();
+        assert_eq!(
+            TagCommand::delete(temp_dir.path(), "v1", mode).unwrap(),
+            commit_hash
+        );
+        assert!(TagCommand::list(temp_dir.path(), mode).unwrap().is_empty());
+        assert!(TagCommand::delete(temp_dir.path(), "v1", mode).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::path::Path;
+
+use crate::domain::objects::*;
+use crate::domain::references::HeadRef;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+use crate::infrastructure::revision::RevisionResolver;
+
+/// Git Update-Ref Use Case
+///
+/// This implements the `git update-ref` plumbing command: pointing a
+/// reference at an object, optionally only if it still has an expected value.
+///
+/// ## Compare-and-Swap:
+/// ```text
+/// $ git-rs update-ref refs/heads/main <new>          # unconditional
+/// $ git-rs update-ref refs/heads/main <new> <old>    # only if main is at <old>
+/// $ git-rs update-ref refs/heads/topic <new> 0000... # only if topic is new
+/// ```
+///
+/// ## Educational Insights:
+/// - **Refs Are Files**: `refs/heads/main` holds a hex id and a newline;
+///   updating it is rewriting that file
+/// - **HEAD Is Followed**: Updating `HEAD` while on a branch moves the
+///   branch, just like committing does; a detached HEAD is moved itself
+/// - **Safe Concurrent Updates**: The `<old>` check lets a script move a ref
+///   only if nobody else moved it since it was read
+pub struct UpdateRefCommand;
+
+impl UpdateRefCommand {
+    /// Point `reference` at `new_value` and return the new id
+    ///
+    /// # Arguments
+    /// * `repo_path` - Path to the repository root
+    /// * `reference` - `HEAD` or a full name under `refs/`
+    /// * `new_value` - Name of the object to point at
+    /// * `old_value` - Value the ref must have now; all zeros means it must
+    ///   not exist yet
+    /// * `git_compat` - Git compatibility mode
+    pub fn update_ref_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        reference: &str,
+        new_value: &str,
+        old_value: Option<&str>,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectHash> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        let object_store = ObjectStore::for_repository(&repo);
+        let ref_store = RefStore::new(repo.git_dir().to_path_buf());
+        let resolver = RevisionResolver::new(&object_store, &ref_store);
+
+        // HEAD on a branch updates the branch; a detached HEAD is a ref itself
+        let target = match (reference, ref_store.load_head()?) {
+            ("HEAD", Some(HeadRef::Symbolic(branch))) => Some(branch),
+            ("HEAD", _) => None,
+            (name, _) => Some(name.to_string()),
+        };
+        let current = match &target {
+            Some(name) => ref_store.read_ref(name)?,
+            None => ref_store.get_head()?,
+        };
+        let display_name = target.as_deref().unwrap_or("HEAD");
+
+        let new_hash = resolver.require(new_value)?;
+
+        if let Some(old_value) = old_value {
+            let is_null = old_value.is_empty() || old_value.bytes().all(|b| b == b'0');
+            match (&current, is_null) {
+                (Some(_), true) => {
+                    return Err(format!(
+                        "cannot lock ref '{}': reference already exists",
+                        display_name
+                    )
+                    .into());
+                }
+                (None, true) => {}
+                (current, false) => {
+                    let expected = resolver.require(old_value)?;
+                    if current.as_ref() != Some(&expected) {
+                        return Err(format!(
+                            "cannot lock ref '{}': is at {} but expected {}",
+                            display_name,
+                            current
+                                .as_ref()
+                                .map(|hash| hash.to_string())
+                                .unwrap_or_else(|| "nothing".to_string()),
+                            expected
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+
+        match &target {
+            Some(name) => ref_store.write_ref(name, &new_hash)?,
+            None => ref_store.set_head_to_commit(new_hash)?,
+        }
+
+        Ok(new_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{CommitCommand, CommitOptions, InitCommand};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn update(
+        temp_dir: &TempDir,
+        reference: &str,
+        new_value: &str,
+        old_value: Option<&str>,
+    ) -> crate::Result<ObjectHash> {
+        UpdateRefCommand::update_ref_with_compat(
+            temp_dir.path(),
+            reference,
+            new_value,
+            old_value,
+            GitCompatMode::Educational,
+        )
+    }
+
+    #[test]
+    fn test_update_ref_with_old_value_check() {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+        let options = || CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+        let first = CommitCommand::commit(temp_dir.path(), "First", options())
+            .unwrap()
+            .commit_hash;
+        let second = CommitCommand::commit(temp_dir.path(), "Second", options())
+            .unwrap()
+            .commit_hash;
+        let ref_store = RefStore::new(temp_dir.path().join(".git-rs"));
+        let zero = "0".repeat(40);
+
+        // Create only if missing
+        update(&temp_dir, "refs/heads/topic", &first.to_hex(), Some(&zero)).unwrap();
+        assert_eq!(ref_store.read_ref("refs/heads/topic").unwrap(), Some(first));
+        assert!(update(&temp_dir, "refs/heads/topic", &second.to_hex(), Some(&zero)).is_err());
+
+        // Compare-and-swap
+        assert!(update(
+            &temp_dir,
+            "refs/heads/topic",
+            "HEAD",
+            Some(&second.to_hex())
+        )
+        .is_err());
+        update(&temp_dir, "refs/heads/topic", "HEAD", Some(&first.to_hex())).unwrap();
+        assert_eq!(
+            ref_store.read_ref("refs/heads/topic").unwrap(),
+            Some(second)
+        );
+
+        // Any namespace under refs/, but nothing outside it
+        update(&temp_dir, "refs/notes/commits", &first.to_hex(), None).unwrap();
+        assert!(update(&temp_dir, "topic", &first.to_hex(), None).is_err());
+        assert!(update(&temp_dir, "refs/heads/topic", "no-such-object", None).is_err());
+    }
+
+    #[test]
+    fn test_update_ref_head_follows_branch() {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+        let options = || CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+        let first = CommitCommand::commit(temp_dir.path(), "First", options())
+            .unwrap()
+            .commit_hash;
+        let second = CommitCommand::commit(temp_dir.path(), "Second", options())
+            .unwrap()
+            .commit_hash;
+        let ref_store = RefStore::new(temp_dir.path().join(".git-rs"));
+        let branch = ref_store.get_current_branch().unwrap().unwrap();
+
+        update(&temp_dir, "HEAD", &first.to_hex(), None).unwrap();
+        assert_eq!(
+            ref_store
+                .read_ref(&format!("refs/heads/{}", branch))
+                .unwrap(),
+            Some(first)
+        );
+
+        // Detached: HEAD itself moves and the branch stays
+        ref_store.set_head_to_commit(first).unwrap();
+        update(&temp_dir, "HEAD", &second.to_hex(), Some(&first.to_hex())).unwrap();
+        let head = fs::read_to_string(temp_dir.path().join(".git-rs/HEAD")).unwrap();
+        assert_eq!(head.trim(), second.to_hex());
+        assert_eq!(
+            ref_store
+                .read_ref(&format!("refs/heads/{}", branch))
+                .unwrap(),
+            Some(first)
+        );
+    }
+}
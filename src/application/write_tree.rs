@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use crate::application::commit::CommitCommand;
+use crate::domain::objects::*;
+use crate::domain::repository::{GitCompatMode, GitRepository};
+use crate::infrastructure::index_store::IndexStore;
+use crate::infrastructure::object_store::ObjectStore;
+
+/// Git Write-Tree Use Case
+///
+/// This implements the `git write-tree` plumbing command: turning the
+/// current index into tree objects and printing the root tree's id.
+///
+/// ## Visual Guide:
+/// ```text
+/// index (flat)           object database
+/// README.md     ──▶      tree 9bdbc4a4 ──▶ blob README.md
+/// src/main.rs            └─ tree src   ──▶ blob main.rs
+/// ```
+///
+/// ## Educational Insights:
+/// - **Half a Commit**: `commit` is `write-tree` followed by `commit-tree`
+///   and a ref update; the plumbing exposes each step on its own
+/// - **Objects Must Exist**: Every blob the index names has to be in the
+///   database already, otherwise the tree would point at nothing. Gitlinks
+///   are the exception: their commit lives in the submodule
+pub struct WriteTreeCommand;
+
+impl WriteTreeCommand {
+    /// Write the index as trees and return the root tree id
+    pub fn write_tree_with_compat<P: AsRef<Path>>(
+        repo_path: P,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<ObjectHash> {
+        let repo = GitRepository::new_with_compat(repo_path, git_compat);
+        if !repo.is_repository() {
+            return Err(format!(
+                "Not a git repository (or any of the parent directories): {}",
+                repo.git_dir().display()
+            )
+            .into());
+        }
+
+        let object_store = ObjectStore::for_repository(&repo);
+        let index = IndexStore::new(repo.index_path()).load_index()?;
+
+        if index.has_conflicts() {
+            let unmerged: Vec<String> = index
+                .get_sorted_entries()
+                .into_iter()
+                .filter(|entry| entry.stage != 0)
+                .map(|entry| format!("{}: unmerged ({})", entry.path.display(), entry.hash))
+                .collect();
+            return Err(format!("{}\nerror building trees", unmerged.join("\n")).into());
+        }
+
+        for (path, entry) in &index.entries {
+            if entry.mode != FileMode::Gitlink
+                && !entry.intent_to_add
+                && !object_store.object_exists(&entry.hash)
+            {
+                return Err(format!(
+                    "invalid object {} {} for '{}'",
+                    entry.mode.as_octal(),
+                    entry.hash,
+                    path.display()
+                )
+                .into());
+            }
+        }
+
+        CommitCommand::create_tree_from_index(&object_store, &index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::{AddCommand, AddOptions, InitCommand};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_tree_from_index() {
+        let temp_dir = TempDir::new().unwrap();
+        InitCommand::init(Some(temp_dir.path())).unwrap();
+
+        // An empty index writes the empty tree
+        let empty =
+            WriteTreeCommand::write_tree_with_compat(temp_dir.path(), GitCompatMode::Educational)
+                .unwrap();
+        assert_eq!(empty.to_hex(), "4b825dc642cb6eb9a060e54bf8d69288fbee4904");
+
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("hello.txt"), "Hello World\n").unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        AddCommand::add(temp_dir.path(), &[".".to_string()], AddOptions::default()).unwrap();
+
+        let tree =
+            WriteTreeCommand::write_tree_with_compat(temp_dir.path(), GitCompatMode::Educational)
+                .unwrap();
+        let store = ObjectStore::new(temp_dir.path().join(".git-rs/objects"));
+        let entries = store.load_object(&tree).unwrap().as_tree().unwrap().clone();
+        let names: Vec<&str> = entries.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["hello.txt", "src"]);
+
+        // A blob that went missing makes the index unwritable
+        let blob = ObjectHash::from_hex("557db03de997c86a4a028e1ebd3a1ceb225be238").unwrap();
+        fs::remove_file(store.get_object_path(&blob)).unwrap();
+        let error =
+            WriteTreeCommand::write_tree_with_compat(temp_dir.path(), GitCompatMode::Educational)
+                .unwrap_err();
+        assert!(error.to_string().contains("for 'hello.txt'"));
+    }
+}
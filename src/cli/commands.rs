@@ -1,10 +1,22 @@
 use crate::application::add::{AddCommand, AddOptions};
+use crate::application::cat_file::{BatchMode, CatFileCommand, CatFileMode};
 use crate::application::clone::{CloneCommand, CloneOptions};
 use crate::application::commit::{CommitCommand, CommitOptions};
+use crate::application::commit_tree::CommitTreeCommand;
 use crate::application::diff::{DiffCommand, DiffOptions};
+use crate::application::fsck::{FsckCommand, FsckOptions};
+use crate::application::hash_object::{HashObjectCommand, HashObjectOptions};
 use crate::application::init::InitCommand;
 use crate::application::log::{LogCommand, LogOptions};
+use crate::application::mv::{MvCommand, MvOptions};
+use crate::application::repack::{RepackCommand, RepackOptions};
+use crate::application::rev_parse::{RevParseCommand, RevParseOptions};
+use crate::application::rm::{RmCommand, RmOptions};
 use crate::application::status::{StatusCommand, StatusOptions};
+use crate::application::tag::{TagCommand, TagOptions};
+use crate::application::update_ref::UpdateRefCommand;
+use crate::application::write_tree::WriteTreeCommand;
+use crate::domain::objects::ObjectFormat;
 use crate::domain::repository::GitCompatMode;
 use std::path::Path;
 
@@ -32,48 +44,107 @@ impl GitCommand {
 
     /// Handle `git add` command
     pub fn add(files: &[String]) -> crate::Result<()> {
-        println!("git-rs add {:?}", files);
-        println!("==================");
+        Self::add_with_compat(files, AddOptions::default(), GitCompatMode::Educational)
+    }
 
-        if files.is_empty() {
-            return Err(
-                "Nothing specified, nothing added.\nMaybe you wanted to say 'git add .'?".into(),
-            );
-        }
+    /// Handle `git status` command
+    pub fn status() -> crate::Result<()> {
+        Self::status_with_compat(GitCompatMode::Educational)
+    }
 
-        let current_dir = std::env::current_dir()?;
-        let options = AddOptions::default();
+    /// Handle `git commit` command
+    pub fn commit(message: &str) -> crate::Result<()> {
+        Self::commit_with_compat(message, GitCompatMode::Educational)
+    }
+
+    /// Handle `git diff` command
+    pub fn diff(cached: bool) -> crate::Result<()> {
+        Self::diff_with_compat(cached, GitCompatMode::Educational)
+    }
 
-        let result = AddCommand::add(&current_dir, files, options)?;
+    /// Handle `git clone` command
+    pub fn clone(url: &str, directory: Option<&str>) -> crate::Result<()> {
+        Self::clone_with_compat(
+            url,
+            directory,
+            CloneOptions::default(),
+            GitCompatMode::Educational,
+        )
+    }
 
-        if result.has_failures() {
-            for (path, error) in &result.failed_files {
-                println!("⚠️  Failed to add {}: {}", path.display(), error);
-            }
+    /// Handle `git log` command
+    pub fn log(count: Option<usize>) -> crate::Result<()> {
+        println!("git-rs log");
+        println!("==========");
+
+        let options = LogOptions {
+            max_count: count,
+            revision: None,
+        };
+
+        let result = LogCommand::log(".", options)?;
+
+        if result.entries.is_empty() {
+            println!("📭 No commits found in this repository");
+            return Ok(());
         }
 
-        if result.total_staged() > 0 {
-            println!("\n🎯 Successfully staged {} file(s)", result.total_staged());
+        // Display the log entries
+        print!("{}", LogCommand::format_log_result(&result));
+
+        if result.has_more && count.is_some() {
+            println!("💡 Use 'git-rs log' (without -n) to see all commits");
         }
 
         Ok(())
     }
 
-    /// Handle `git status` command
-    pub fn status() -> crate::Result<()> {
-        println!("git-rs status");
-        println!("=============");
+    // Git compatibility methods
+
+    /// Handle `git init` command with compatibility mode
+    pub fn init_with_compat(
+        object_format: ObjectFormat,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        println!("git-rs init");
+        println!("============");
+
+        let repo = InitCommand::init_with_format::<&Path>(None, git_compat, object_format)?;
+        let info = InitCommand::get_repository_info(&repo);
+
+        println!("\n📊 Repository Summary:");
+        println!("{}", info);
+
+        Ok(())
+    }
+
+    /// Handle `git add` command with compatibility mode
+    pub fn add_with_compat(
+        files: &[String],
+        options: AddOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        println!("git-rs add {:?}", files);
+        println!("==================");
 
         let current_dir = std::env::current_dir()?;
-        let options = StatusOptions::default();
+        let result = AddCommand::add_with_compat(&current_dir, files, options, git_compat)?;
+
+        if result.has_failures() {
+            for (path, error) in &result.failed_files {
+                println!("⚠️  Failed to add {}: {}", path.display(), error);
+            }
+        }
 
-        let _result = StatusCommand::status(&current_dir, options)?;
+        if result.total_staged() > 0 {
+            println!("\n🎯 Successfully staged {} file(s)", result.total_staged());
+        }
 
         Ok(())
     }
 
-    /// Handle `git commit` command
-    pub fn commit(message: &str) -> crate::Result<()> {
+    /// Handle `git commit` command with compatibility mode
+    pub fn commit_with_compat(message: &str, git_compat: GitCompatMode) -> crate::Result<()> {
         println!("git-rs commit -m \"{}\"", message);
         println!("=======================");
 
@@ -83,7 +154,7 @@ impl GitCommand {
         let current_dir = std::env::current_dir()?;
         let options = CommitOptions::default();
 
-        let result = CommitCommand::commit(&current_dir, message, options)?;
+        let result = CommitCommand::commit_with_compat(&current_dir, message, options, git_compat)?;
 
         println!("\n{}", result.summary());
         println!("📁 Tree: {}", result.tree_hash);
@@ -96,8 +167,21 @@ impl GitCommand {
         Ok(())
     }
 
-    /// Handle `git diff` command
-    pub fn diff(cached: bool) -> crate::Result<()> {
+    /// Handle `git status` command with compatibility mode
+    pub fn status_with_compat(git_compat: GitCompatMode) -> crate::Result<()> {
+        println!("git-rs status");
+        println!("=============");
+
+        let current_dir = std::env::current_dir()?;
+        let options = StatusOptions::default();
+
+        let _result = StatusCommand::status_with_compat(&current_dir, options, git_compat)?;
+
+        Ok(())
+    }
+
+    /// Handle `git diff` command with compatibility mode
+    pub fn diff_with_compat(cached: bool, git_compat: GitCompatMode) -> crate::Result<()> {
         if cached {
             println!("git-rs diff --cached");
         } else {
@@ -111,7 +195,7 @@ impl GitCommand {
             ..Default::default()
         };
 
-        let result = DiffCommand::diff(&current_dir, options)?;
+        let result = DiffCommand::diff_with_compat(&current_dir, options, git_compat)?;
 
         if result.files_changed == 0 {
             if cached {
@@ -126,15 +210,21 @@ impl GitCommand {
         Ok(())
     }
 
-    /// Handle `git clone` command
-    pub fn clone(url: &str, directory: Option<&str>) -> crate::Result<()> {
+    /// Handle `git clone` command with compatibility mode
+    ///
+    /// TODO: Pass git_compat to CloneCommand when it supports it
+    pub fn clone_with_compat(
+        url: &str,
+        directory: Option<&str>,
+        options: CloneOptions,
+        _git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
         match directory {
             Some(dir) => println!("git-rs clone {} {}", url, dir),
             None => println!("git-rs clone {}", url),
         }
         println!("====================");
 
-        let options = CloneOptions::default();
         let result = CloneCommand::clone(url, directory, options)?;
 
         println!("\n📊 Clone Summary:");
@@ -143,14 +233,21 @@ impl GitCommand {
         Ok(())
     }
 
-    /// Handle `git log` command
-    pub fn log(count: Option<usize>) -> crate::Result<()> {
+    /// Handle `git log` command with compatibility mode
+    pub fn log_with_compat(
+        count: Option<usize>,
+        revision: Option<String>,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
         println!("git-rs log");
         println!("==========");
 
-        let options = LogOptions { max_count: count };
+        let options = LogOptions {
+            max_count: count,
+            revision,
+        };
 
-        let result = LogCommand::log(".", options)?;
+        let result = LogCommand::log_with_compat(".", options, git_compat)?;
 
         if result.entries.is_empty() {
             println!("📭 No commits found in this repository");
@@ -167,82 +264,281 @@ impl GitCommand {
         Ok(())
     }
 
-    // Git compatibility methods
+    /// Handle `git repack` command with compatibility mode
+    pub fn repack_with_compat(
+        options: RepackOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        println!("git-rs repack");
+        println!("=============");
 
-    /// Handle `git init` command with compatibility mode
-    pub fn init_with_compat(git_compat: GitCompatMode) -> crate::Result<()> {
-        println!("git-rs init");
-        println!("============");
+        let current_dir = std::env::current_dir()?;
+        let result = RepackCommand::repack_with_compat(&current_dir, options, git_compat)?;
 
-        let repo = InitCommand::init_with_compat::<&Path>(None, git_compat)?;
-        let info = InitCommand::get_repository_info(&repo);
+        println!("\n{}", result.summary());
 
-        println!("\n📊 Repository Summary:");
-        println!("{}", info);
+        Ok(())
+    }
+
+    /// Handle `git gc` command with compatibility mode
+    pub fn gc_with_compat(git_compat: GitCompatMode) -> crate::Result<()> {
+        println!("git-rs gc");
+        println!("=========");
+
+        let current_dir = std::env::current_dir()?;
+        let result =
+            RepackCommand::repack_with_compat(&current_dir, RepackOptions::gc(), git_compat)?;
+
+        println!("\n{}", result.summary());
 
         Ok(())
     }
 
-    /// Handle `git add` command with compatibility mode
-    pub fn add_with_compat(files: &[String], _git_compat: GitCompatMode) -> crate::Result<()> {
-        // For now, just delegate to the original add method
-        // TODO: Pass git_compat to AddCommand when it supports it
-        Self::add(files)
+    /// Handle `git cat-file -t|-s|-e|-p <object>` command with compatibility mode
+    ///
+    /// Plumbing output goes to stdout undecorated, so scripts can parse it.
+    pub fn cat_file_with_compat(
+        object: &str,
+        mode: CatFileMode,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        let found =
+            CatFileCommand::cat_file_with_compat(&current_dir, object, mode, git_compat, &mut out)?;
+
+        if !found {
+            // `-e` reports through the exit status only
+            std::process::exit(1);
+        }
+
+        Ok(())
     }
 
-    /// Handle `git commit` command with compatibility mode
-    pub fn commit_with_compat(message: &str, _git_compat: GitCompatMode) -> crate::Result<()> {
-        // For now, just delegate to the original commit method
-        // TODO: Pass git_compat to CommitCommand when it supports it
-        Self::commit(message)
+    /// Handle `git cat-file --batch` / `--batch-check` command with compatibility mode
+    pub fn cat_file_batch_with_compat(
+        mode: BatchMode,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+        let mut out = std::io::BufWriter::new(stdout.lock());
+        CatFileCommand::batch_with_compat(&current_dir, mode, git_compat, stdin.lock(), &mut out)?;
+
+        Ok(())
     }
 
-    /// Handle `git status` command with compatibility mode
-    pub fn status_with_compat(_git_compat: GitCompatMode) -> crate::Result<()> {
-        // For now, just delegate to the original status method
-        // TODO: Pass git_compat to StatusCommand when it supports it
-        Self::status()
+    /// Handle `git rev-parse` command with compatibility mode
+    pub fn rev_parse_with_compat(
+        revisions: &[String],
+        options: RevParseOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let lines =
+            RevParseCommand::rev_parse_with_compat(&current_dir, revisions, &options, git_compat)?;
+        for line in lines {
+            println!("{}", line);
+        }
+
+        Ok(())
     }
 
-    /// Handle `git diff` command with compatibility mode
-    pub fn diff_with_compat(staged: bool, _git_compat: GitCompatMode) -> crate::Result<()> {
-        // For now, just delegate to the original diff method
-        // TODO: Pass git_compat to DiffCommand when it supports it
-        Self::diff(staged)
+    /// Handle `git hash-object` command with compatibility mode
+    ///
+    /// Prints one id per file, or a single id for `--stdin`.
+    pub fn hash_object_with_compat(
+        files: &[String],
+        stdin: bool,
+        options: HashObjectOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+
+        if stdin {
+            let mut content = Vec::new();
+            std::io::Read::read_to_end(&mut std::io::stdin().lock(), &mut content)?;
+            let hash = HashObjectCommand::hash_bytes_with_compat(
+                &current_dir,
+                &content,
+                &options,
+                git_compat,
+            )?;
+            println!("{}", hash);
+        }
+
+        for file in files {
+            let hash = HashObjectCommand::hash_file_with_compat(
+                &current_dir,
+                Path::new(file),
+                &options,
+                git_compat,
+            )?;
+            println!("{}", hash);
+        }
+
+        Ok(())
     }
 
-    /// Handle `git clone` command with compatibility mode
-    pub fn clone_with_compat(
-        url: &str,
-        directory: Option<&str>,
-        _git_compat: GitCompatMode,
+    /// Handle `git write-tree` command with compatibility mode
+    pub fn rm_with_compat(
+        paths: &[String],
+        options: RmOptions,
+        git_compat: GitCompatMode,
     ) -> crate::Result<()> {
-        // For now, just delegate to the original clone method
-        // TODO: Pass git_compat to CloneCommand when it supports it
-        Self::clone(url, directory)
+        let current_dir = std::env::current_dir()?;
+        let result = RmCommand::rm_with_compat(&current_dir, paths, &options, git_compat)?;
+        for path in &result.removed {
+            println!("rm '{}'", path.display());
+        }
+
+        Ok(())
     }
 
-    /// Handle `git log` command with compatibility mode
-    pub fn log_with_compat(count: Option<usize>, git_compat: GitCompatMode) -> crate::Result<()> {
-        println!("git-rs log");
+    /// Handle `git mv` command with compatibility mode; the last path is the
+    /// destination
+    pub fn mv_with_compat(
+        paths: &[String],
+        options: MvOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let Some((destination, sources)) = paths.split_last() else {
+            return Err("usage: git-rs mv [-f] <source>... <destination>".into());
+        };
+        let current_dir = std::env::current_dir()?;
+        let result =
+            MvCommand::mv_with_compat(&current_dir, sources, destination, &options, git_compat)?;
+        for (source, target) in &result.moved {
+            println!("Renaming {} to {}", source.display(), target.display());
+        }
+
+        Ok(())
+    }
+
+    pub fn write_tree_with_compat(git_compat: GitCompatMode) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let hash = WriteTreeCommand::write_tree_with_compat(&current_dir, git_compat)?;
+        println!("{}", hash);
+
+        Ok(())
+    }
+
+    /// Handle `git commit-tree` command with compatibility mode
+    pub fn commit_tree_with_compat(
+        tree: &str,
+        parents: &[String],
+        messages: &[String],
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let hash = CommitTreeCommand::commit_tree_with_compat(
+            &current_dir,
+            tree,
+            parents,
+            messages,
+            git_compat,
+        )?;
+        println!("{}", hash);
+
+        Ok(())
+    }
+
+    /// Handle `git update-ref` command with compatibility mode
+    ///
+    /// Like Git, a successful update prints nothing.
+    pub fn update_ref_with_compat(
+        reference: &str,
+        new_value: &str,
+        old_value: Option<&str>,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        UpdateRefCommand::update_ref_with_compat(
+            &current_dir,
+            reference,
+            new_value,
+            old_value,
+            git_compat,
+        )?;
+
+        Ok(())
+    }
+
+    /// Handle `git fsck` command with compatibility mode
+    pub fn fsck_with_compat(options: FsckOptions, git_compat: GitCompatMode) -> crate::Result<()> {
+        println!("git-rs fsck");
+        println!("===========");
+
+        let current_dir = std::env::current_dir()?;
+        let result = FsckCommand::fsck_with_compat(&current_dir, options, git_compat)?;
+
+        for issue in &result.issues {
+            println!("{}", issue);
+        }
+        println!("\n{}", result.summary());
+
+        if !result.is_ok() {
+            return Err(format!("fsck found {} problem(s)", result.error_count()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Handle `git tag <name> [<target>]` command with compatibility mode
+    pub fn tag_create_with_compat(
+        name: &str,
+        target: Option<&str>,
+        options: TagOptions,
+        git_compat: GitCompatMode,
+    ) -> crate::Result<()> {
+        println!("git-rs tag {}", name);
         println!("==========");
 
-        let options = LogOptions { max_count: count };
+        let current_dir = std::env::current_dir()?;
+        let tag = TagCommand::create(&current_dir, name, target, options, git_compat)?;
 
-        let result = LogCommand::log_with_compat(".", options, git_compat)?;
+        if tag.is_annotated() {
+            println!("\n✅ Annotated tag '{}' -> {}", tag.name, tag.target);
+        } else {
+            println!("\n✅ Lightweight tag '{}' -> {}", tag.name, tag.target);
+        }
 
-        if result.entries.is_empty() {
-            println!("📭 No commits found in this repository");
+        Ok(())
+    }
+
+    /// Handle `git tag` / `git tag -l` command with compatibility mode
+    pub fn tag_list_with_compat(git_compat: GitCompatMode) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let tags = TagCommand::list(&current_dir, git_compat)?;
+
+        if tags.is_empty() {
+            println!("📭 No tags found in this repository");
             return Ok(());
         }
 
-        // Display the log entries
-        print!("{}", LogCommand::format_log_result(&result));
-
-        if result.has_more && count.is_some() {
-            println!("💡 Use 'git-rs log' (without -n) to see all commits");
+        for tag in &tags {
+            match &tag.annotation {
+                Some(annotation) => println!(
+                    "🏷️  {:<20} {}",
+                    tag.name,
+                    annotation.message.lines().next().unwrap_or("")
+                ),
+                None => println!("📌 {:<20} {}", tag.name, tag.target.to_short_hex(8)),
+            }
         }
 
         Ok(())
     }
+
+    /// Handle `git tag -d` command with compatibility mode
+    pub fn tag_delete_with_compat(name: &str, git_compat: GitCompatMode) -> crate::Result<()> {
+        let current_dir = std::env::current_dir()?;
+        let hash = TagCommand::delete(&current_dir, name, git_compat)?;
+
+        println!("🗑️  Deleted tag '{}' (was {})", name, hash.to_short_hex(8));
+
+        Ok(())
+    }
 }
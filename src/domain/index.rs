@@ -29,6 +29,15 @@ pub struct IndexEntry {
     pub stage: u16,
     /// File path relative to repository root
     pub path: PathBuf,
+    /// Trust the entry without checking the file (`update-index --assume-unchanged`)
+    #[serde(default)]
+    pub assume_valid: bool,
+    /// Leave the file out of the working tree (sparse checkout)
+    #[serde(default)]
+    pub skip_worktree: bool,
+    /// Added with `add -N`: the path is known but its content is not staged yet
+    #[serde(default)]
+    pub intent_to_add: bool,
 }
 
 impl IndexEntry {
@@ -46,6 +55,9 @@ impl IndexEntry {
             hash,
             stage: 0,
             path,
+            assume_valid: false,
+            skip_worktree: false,
+            intent_to_add: false,
         }
     }
 
@@ -60,7 +72,8 @@ impl IndexEntry {
             #[cfg(unix)]
             {
                 use std::os::unix::fs::MetadataExt;
-                if metadata.mode() & 0o111 != 0 {
+                // Git only looks at the owner's bit, like `WorkingTree`
+                if metadata.mode() & 0o100 != 0 {
                     FileMode::Executable
                 } else {
                     FileMode::Regular
@@ -76,30 +89,26 @@ impl IndexEntry {
             FileMode::Symlink
         };
 
-        let now = Utc::now();
+        // Unix has a real change time; elsewhere the creation time stands in
+        #[cfg(unix)]
+        let (ctime, mtime) = {
+            use std::os::unix::fs::MetadataExt;
+            (
+                Self::timestamp(metadata.ctime(), metadata.ctime_nsec()),
+                Self::timestamp(metadata.mtime(), metadata.mtime_nsec()),
+            )
+        };
 
-        // Get timestamps with fallback to current time
-        let ctime = metadata
-            .created()
-            .ok()
-            .and_then(|t| {
-                DateTime::from_timestamp(
-                    t.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64,
-                    0,
-                )
-            })
-            .unwrap_or(now);
-
-        let mtime = metadata
-            .modified()
-            .ok()
-            .and_then(|t| {
-                DateTime::from_timestamp(
-                    t.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64,
-                    0,
-                )
-            })
-            .unwrap_or(now);
+        #[cfg(not(unix))]
+        let (ctime, mtime) = {
+            let time = |time: std::io::Result<std::time::SystemTime>| {
+                time.ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| Self::timestamp(d.as_secs() as i64, d.subsec_nanos() as i64))
+                    .unwrap_or_else(Utc::now)
+            };
+            (time(metadata.created()), time(metadata.modified()))
+        };
 
         // Get Unix-specific metadata if available
         #[cfg(unix)]
@@ -128,9 +137,69 @@ impl IndexEntry {
             hash,
             stage: 0,
             path,
+            assume_valid: false,
+            skip_worktree: false,
+            intent_to_add: false,
         }
     }
 
+    /// A stat time as seconds and nanoseconds since the epoch
+    pub fn timestamp(seconds: i64, nanoseconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, nanoseconds.clamp(0, 999_999_999) as u32)
+            .unwrap_or_default()
+    }
+
+    /// Whether the file's current stat data still matches the entry
+    ///
+    /// Matching times, size, inode, owner and mode mean the file has not
+    /// been touched since it was staged, so its content need not be read.
+    /// The device is not compared: it can change across reboots and
+    /// network mounts without the file changing. Only the low 32 bits of
+    /// the size are compared, as that is all the binary index stores.
+    ///
+    /// An entry with size 0 that is not the empty blob has been smudged: it
+    /// was racily clean when found modified, and must never match again.
+    pub fn stat_matches(&self, metadata: &std::fs::Metadata) -> bool {
+        if self.size == 0 && self.hash != self.hash.format().hash_object("blob", b"") {
+            return false;
+        }
+
+        let current = Self::from_file_metadata(self.path.clone(), self.hash, metadata);
+        self.mtime == current.mtime
+            && self.ctime == current.ctime
+            && self.size as u32 == current.size as u32
+            && self.ino == current.ino
+            && self.uid == current.uid
+            && self.gid == current.gid
+            && self.mode == current.mode
+    }
+
+    /// Whether the file may have changed without its stat data showing it
+    ///
+    /// A file modified in the same clock tick as the index was written can
+    /// keep the mtime recorded in the entry, so an entry whose mtime is not
+    /// older than the index file cannot be trusted on stat data alone.
+    pub fn is_racily_clean(&self, index_timestamp: Option<DateTime<Utc>>) -> bool {
+        index_timestamp.is_some_and(|timestamp| self.mtime >= timestamp)
+    }
+
+    /// Record the file's current stat data, keeping the staged content
+    pub fn refresh_stat(&mut self, metadata: &std::fs::Metadata) {
+        let current = Self::from_file_metadata(self.path.clone(), self.hash, metadata);
+        self.ctime = current.ctime;
+        self.mtime = current.mtime;
+        self.dev = current.dev;
+        self.ino = current.ino;
+        self.uid = current.uid;
+        self.gid = current.gid;
+        self.size = current.size;
+    }
+
+    /// Whether the entry needs the extended flags of index version 3
+    pub fn has_extended_flags(&self) -> bool {
+        self.skip_worktree || self.intent_to_add
+    }
+
     /// Get the file name
     pub fn name(&self) -> Option<&str> {
         self.path.file_name().and_then(|n| n.to_str())
@@ -147,13 +216,155 @@ impl IndexEntry {
     }
 }
 
+/// An extension block stored after the index entries, kept as raw bytes
+///
+/// Git uses extensions for caches (`TREE`: the tree ids of directories,
+/// `UNTR`: untracked files) and extra state (`REUC`: how conflicts were
+/// resolved). A signature starting with an uppercase letter marks an
+/// optional extension that readers may ignore; anything else must be
+/// understood to use the index at all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexExtension {
+    /// Four-byte name, e.g. `TREE`
+    pub signature: [u8; 4],
+    /// Extension content
+    pub data: Vec<u8>,
+}
+
+impl IndexExtension {
+    pub fn new(signature: [u8; 4], data: Vec<u8>) -> Self {
+        Self { signature, data }
+    }
+
+    /// Printable name of the extension
+    pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.signature).into_owned()
+    }
+
+    /// Optional extensions may be skipped by readers that do not know them
+    pub fn is_optional(&self) -> bool {
+        self.signature[0].is_ascii_uppercase()
+    }
+
+    /// Whether the extension describes the entries themselves and so goes
+    /// stale as soon as they change (caches and offset tables)
+    pub fn depends_on_entries(&self) -> bool {
+        matches!(
+            &self.signature,
+            b"TREE" | b"UNTR" | b"EOIE" | b"IEOT" | b"FSMN"
+        )
+    }
+}
+
+/// The versions of an unmerged path, one per conflict stage
+///
+/// A merge that cannot combine two versions of a file leaves all of them
+/// in the index instead of a single stage 0 entry:
+///
+/// ```text
+/// stage 1 (base)    the version in the common ancestor
+/// stage 2 (ours)    the version on the current branch
+/// stage 3 (theirs)  the version being merged in
+/// ```
+///
+/// A missing stage means the file did not exist on that side, e.g. no
+/// theirs for a file the other branch deleted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Conflict {
+    pub base: Option<IndexEntry>,
+    pub ours: Option<IndexEntry>,
+    pub theirs: Option<IndexEntry>,
+}
+
+impl Conflict {
+    /// The entry at a conflict stage (1, 2 or 3)
+    pub fn stage(&self, stage: u16) -> Option<&IndexEntry> {
+        match stage {
+            1 => self.base.as_ref(),
+            2 => self.ours.as_ref(),
+            3 => self.theirs.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The entries present, in stage order
+    pub fn entries(&self) -> impl Iterator<Item = &IndexEntry> {
+        [&self.base, &self.ours, &self.theirs]
+            .into_iter()
+            .filter_map(Option::as_ref)
+    }
+
+    /// How the sides disagree, as `status` reports it
+    pub fn kind(&self) -> ConflictKind {
+        match (
+            self.base.is_some(),
+            self.ours.is_some(),
+            self.theirs.is_some(),
+        ) {
+            (true, true, true) => ConflictKind::BothModified,
+            (false, true, true) => ConflictKind::BothAdded,
+            (true, false, true) => ConflictKind::DeletedByUs,
+            (true, true, false) => ConflictKind::DeletedByThem,
+            (false, true, false) => ConflictKind::AddedByUs,
+            (false, false, true) => ConflictKind::AddedByThem,
+            (_, false, false) => ConflictKind::BothDeleted,
+        }
+    }
+
+    fn slot_mut(&mut self, stage: u16) -> Option<&mut Option<IndexEntry>> {
+        match stage {
+            1 => Some(&mut self.base),
+            2 => Some(&mut self.ours),
+            3 => Some(&mut self.theirs),
+            _ => None,
+        }
+    }
+}
+
+/// The kinds of unmerged path `git status` distinguishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    BothModified,
+    BothAdded,
+    DeletedByUs,
+    DeletedByThem,
+    AddedByUs,
+    AddedByThem,
+    BothDeleted,
+}
+
+impl ConflictKind {
+    /// Label used by `status`, e.g. "both modified"
+    pub fn description(self) -> &'static str {
+        match self {
+            ConflictKind::BothModified => "both modified",
+            ConflictKind::BothAdded => "both added",
+            ConflictKind::DeletedByUs => "deleted by us",
+            ConflictKind::DeletedByThem => "deleted by them",
+            ConflictKind::AddedByUs => "added by us",
+            ConflictKind::AddedByThem => "added by them",
+            ConflictKind::BothDeleted => "both deleted",
+        }
+    }
+}
+
 /// Git Index (staging area) containing staged files
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GitIndex {
-    /// Version of the index format
+    /// Version of the index format (2, 3 or 4 on disk)
     pub version: u32,
-    /// Map of file paths to index entries
+    /// Map of file paths to their merged (stage 0) entries
     pub entries: HashMap<PathBuf, IndexEntry>,
+    /// Unmerged paths with their conflict stages; such a path has no
+    /// stage 0 entry until the conflict is resolved
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub conflicts: HashMap<PathBuf, Conflict>,
+    /// Extension blocks read from disk, written back when still valid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<IndexExtension>,
+    /// When the index file was last written, if it was loaded from disk
+    #[serde(skip)]
+    pub timestamp: Option<DateTime<Utc>>,
 }
 
 impl GitIndex {
@@ -161,49 +372,93 @@ impl GitIndex {
         Self {
             version: 2, // Git index format version 2
             entries: HashMap::new(),
+            conflicts: HashMap::new(),
+            extensions: Vec::new(),
+            timestamp: None,
         }
     }
 
     /// Add a file to the index
+    ///
+    /// A stage 0 entry resolves any conflict on its path, as `add` does
+    /// after a merge. A conflict stage (1-3) replaces the stage 0 entry and
+    /// joins the other stages of the path. An entry with any other stage
+    /// is ignored and leaves the index unchanged.
     pub fn add_entry(&mut self, entry: IndexEntry) {
-        self.entries.insert(entry.path.clone(), entry);
+        match entry.stage {
+            0 => {
+                self.conflicts.remove(&entry.path);
+                self.entries.insert(entry.path.clone(), entry);
+            }
+            1..=3 => {
+                self.entries.remove(&entry.path);
+                let conflict = self.conflicts.entry(entry.path.clone()).or_default();
+                if let Some(slot) = conflict.slot_mut(entry.stage) {
+                    *slot = Some(entry);
+                }
+            }
+            _ => {}
+        }
     }
 
-    /// Remove a file from the index
+    /// Remove a file from the index, along with any conflict stages
     pub fn remove_entry(&mut self, path: &PathBuf) -> Option<IndexEntry> {
+        self.conflicts.remove(path);
         self.entries.remove(path)
     }
 
+    /// The conflict stages of an unmerged path
+    pub fn get_conflict(&self, path: &PathBuf) -> Option<&Conflict> {
+        self.conflicts.get(path)
+    }
+
+    /// Whether any path is still unmerged
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// All unmerged paths, sorted
+    pub fn conflicted_paths(&self) -> Vec<&PathBuf> {
+        let mut paths: Vec<&PathBuf> = self.conflicts.keys().collect();
+        paths.sort();
+        paths
+    }
+
     /// Get an entry by path
     pub fn get_entry(&self, path: &PathBuf) -> Option<&IndexEntry> {
         self.entries.get(path)
     }
 
-    /// Get all entries sorted by path
+    /// Get all entries, conflict stages included, sorted by path and stage
     pub fn get_sorted_entries(&self) -> Vec<&IndexEntry> {
-        let mut entries: Vec<&IndexEntry> = self.entries.values().collect();
-        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut entries: Vec<&IndexEntry> = self
+            .entries
+            .values()
+            .chain(self.conflicts.values().flat_map(Conflict::entries))
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path).then(a.stage.cmp(&b.stage)));
         entries
     }
 
     /// Check if the index is empty
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+        self.entries.is_empty() && self.conflicts.is_empty()
     }
 
-    /// Get the number of entries
+    /// Get the number of entries, counting every conflict stage
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.entries.len() + self.conflicts.values().flat_map(Conflict::entries).count()
     }
 
     /// Clear all entries
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.conflicts.clear();
     }
 
-    /// Check if a file is staged
+    /// Check if a file is tracked, merged or not
     pub fn is_staged(&self, path: &PathBuf) -> bool {
-        self.entries.contains_key(path)
+        self.entries.contains_key(path) || self.conflicts.contains_key(path)
     }
 
     /// Get all staged file paths
@@ -213,7 +468,7 @@ impl GitIndex {
 
     /// Update an existing entry or add a new one
     pub fn update_entry(&mut self, entry: IndexEntry) {
-        self.entries.insert(entry.path.clone(), entry);
+        self.add_entry(entry);
     }
 }
 
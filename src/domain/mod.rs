@@ -3,9 +3,11 @@ pub mod objects;
 pub mod references;
 pub mod remote;
 pub mod repository;
+pub mod revision;
 
 pub use index::*;
 pub use objects::*;
 pub use references::*;
 pub use remote::*;
 pub use repository::*;
+pub use revision::*;
@@ -1,35 +1,262 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
-
-/// Git Object Hash - A 40-character hexadecimal string
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct ObjectHash(pub String);
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// Length of the longest binary object id (SHA-256)
+pub const MAX_HASH_LEN: usize = 32;
+
+/// Git Object Hash - the binary id of an object
+///
+/// Ids are kept as raw bytes (20 for SHA-1, 32 for SHA-256) in a fixed-size
+/// array, so they are `Copy`, compare as bytes and never allocate. Hex is
+/// only produced at the edges: loose object paths, refs, and output.
+///
+/// ```text
+/// "557db03d..." (40 hex chars) ◀──▶ [0x55, 0x7d, 0xb0, 0x3d, ...] (20 bytes)
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectHash {
+    bytes: [u8; MAX_HASH_LEN],
+    len: u8,
+}
 
 impl ObjectHash {
-    /// Create a new object hash from a string
-    pub fn new(hash: String) -> Self {
-        Self(hash)
+    /// Create an object hash from a binary id (20 or 32 bytes)
+    pub fn from_bytes(raw: &[u8]) -> crate::Result<Self> {
+        if raw.len() != ObjectFormat::Sha1.raw_len() && raw.len() != ObjectFormat::Sha256.raw_len()
+        {
+            return Err(format!("invalid object id length: {} bytes", raw.len()).into());
+        }
+        let mut bytes = [0; MAX_HASH_LEN];
+        bytes[..raw.len()].copy_from_slice(raw);
+        Ok(Self {
+            bytes,
+            len: raw.len() as u8,
+        })
     }
 
-    /// Get the hash as a string
-    pub fn as_str(&self) -> &str {
-        &self.0
+    /// Parse a full hexadecimal id (40 or 64 characters, any case)
+    pub fn from_hex(hex: &str) -> crate::Result<Self> {
+        if hex.len() != ObjectFormat::Sha1.hex_len() && hex.len() != ObjectFormat::Sha256.hex_len()
+        {
+            return Err(format!("invalid object id: '{}'", hex).into());
+        }
+        let mut raw = [0; MAX_HASH_LEN];
+        let raw = &mut raw[..hex.len() / 2];
+        hex::decode_to_slice(hex, raw).map_err(|_| format!("invalid object id: '{}'", hex))?;
+        Self::from_bytes(raw)
     }
 
-    /// Get the first 2 characters for directory name
-    pub fn dir_name(&self) -> &str {
-        &self.0[0..2]
+    /// The all-zero id, used for "no object" (e.g. a ref that must not exist)
+    pub fn null(format: ObjectFormat) -> Self {
+        Self {
+            bytes: [0; MAX_HASH_LEN],
+            len: format.raw_len() as u8,
+        }
+    }
+
+    /// The hash function that produced this id, told apart by its length
+    pub fn format(&self) -> ObjectFormat {
+        if self.len as usize == ObjectFormat::Sha256.raw_len() {
+            ObjectFormat::Sha256
+        } else {
+            ObjectFormat::Sha1
+        }
     }
 
-    /// Get the remaining characters for file name
-    pub fn file_name(&self) -> &str {
-        &self.0[2..]
+    /// Whether this is the all-zero id
+    pub fn is_null(&self) -> bool {
+        self.as_bytes().iter().all(|&b| b == 0)
+    }
+
+    /// The binary id
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// The full lowercase hex id
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.as_bytes())
+    }
+
+    /// The first `len` hex digits, for abbreviated display
+    pub fn to_short_hex(&self, len: usize) -> String {
+        let mut hex = self.to_hex();
+        hex.truncate(len);
+        hex
+    }
+
+    /// Whether the hex form of this id starts with `prefix` (any case)
+    pub fn starts_with_hex(&self, prefix: &str) -> bool {
+        if prefix.len() > self.len as usize * 2 {
+            return false;
+        }
+        prefix.bytes().enumerate().all(|(i, c)| {
+            let byte = self.bytes[i / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            (c as char).to_digit(16) == Some(nibble as u32)
+        })
+    }
+
+    /// Get the first 2 hex characters for the loose object directory name
+    pub fn dir_name(&self) -> String {
+        hex::encode(&self.bytes[..1])
+    }
+
+    /// Get the remaining hex characters for the loose object file name
+    pub fn file_name(&self) -> String {
+        hex::encode(&self.as_bytes()[1..])
+    }
+}
+
+impl std::str::FromStr for ObjectHash {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
     }
 }
 
 impl std::fmt::Display for ObjectHash {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ObjectHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ObjectHash({})", self)
+    }
+}
+
+/// Serialized as hex, so JSON files (the educational index) stay readable
+impl Serialize for ObjectHash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectHash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Self::from_hex(&hex).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Object Format - The hash function a repository uses to name objects
+///
+/// Repositories default to SHA-1. A repository created with
+/// `init --object-format=sha256` records `extensions.objectFormat = sha256`
+/// in its config, and every object id (blobs, trees, commits, tags and refs)
+/// is then a SHA-256 digest:
+///
+/// ```text
+/// sha1:   20 bytes, 40 hex characters
+/// sha256: 32 bytes, 64 hex characters
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ObjectFormat {
+    #[default]
+    Sha1,
+    Sha256,
+}
+
+impl ObjectFormat {
+    /// Name used in `extensions.objectFormat`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObjectFormat::Sha1 => "sha1",
+            ObjectFormat::Sha256 => "sha256",
+        }
+    }
+
+    /// Length of a binary object id
+    pub fn raw_len(&self) -> usize {
+        match self {
+            ObjectFormat::Sha1 => 20,
+            ObjectFormat::Sha256 => 32,
+        }
+    }
+
+    /// Length of a hex object id
+    pub fn hex_len(&self) -> usize {
+        self.raw_len() * 2
+    }
+
+    /// Hash arbitrary bytes (also used for pack and index checksums)
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            ObjectFormat::Sha1 => Sha1::digest(data).to_vec(),
+            ObjectFormat::Sha256 => Sha256::digest(data).to_vec(),
+        }
+    }
+
+    /// Compute the id of `content` stored as an object of `type_name`
+    ///
+    /// This hashes `"{type} {size}\0{content}"`, the same bytes a loose
+    /// object holds before compression.
+    pub fn hash_object(&self, type_name: &str, content: &[u8]) -> ObjectHash {
+        let mut hasher = self.hasher();
+        hasher.update(format!("{} {}\0", type_name, content.len()).as_bytes());
+        hasher.update(content);
+        hasher.finish()
+    }
+
+    /// Start an incremental hash, for content too large to hold in memory
+    pub fn hasher(&self) -> ObjectHasher {
+        match self {
+            ObjectFormat::Sha1 => ObjectHasher::Sha1(Sha1::new()),
+            ObjectFormat::Sha256 => ObjectHasher::Sha256(Sha256::new()),
+        }
+    }
+}
+
+/// Incremental object id calculation
+///
+/// Feed the object header and then the content in as many chunks as needed;
+/// the result equals `ObjectFormat::hash_object` over the same bytes.
+#[derive(Clone)]
+pub enum ObjectHasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl ObjectHasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            ObjectHasher::Sha1(hasher) => hasher.update(data),
+            ObjectHasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finish(self) -> ObjectHash {
+        let digest = match self {
+            ObjectHasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            ObjectHasher::Sha256(hasher) => hasher.finalize().to_vec(),
+        };
+        ObjectHash::from_bytes(&digest).expect("digest has a valid id length")
+    }
+}
+
+impl std::fmt::Display for ObjectFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for ObjectFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha1" => Ok(ObjectFormat::Sha1),
+            "sha256" => Ok(ObjectFormat::Sha256),
+            _ => Err(format!("Unknown object format: {}", s)),
+        }
     }
 }
 
@@ -39,6 +266,7 @@ pub enum GitObjectType {
     Blob,
     Tree,
     Commit,
+    Tag,
 }
 
 impl std::fmt::Display for GitObjectType {
@@ -47,6 +275,21 @@ impl std::fmt::Display for GitObjectType {
             GitObjectType::Blob => write!(f, "blob"),
             GitObjectType::Tree => write!(f, "tree"),
             GitObjectType::Commit => write!(f, "commit"),
+            GitObjectType::Tag => write!(f, "tag"),
+        }
+    }
+}
+
+impl std::str::FromStr for GitObjectType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blob" => Ok(GitObjectType::Blob),
+            "tree" => Ok(GitObjectType::Tree),
+            "commit" => Ok(GitObjectType::Commit),
+            "tag" => Ok(GitObjectType::Tag),
+            _ => Err(format!("Unknown object type: {}", s)),
         }
     }
 }
@@ -88,6 +331,8 @@ pub enum FileMode {
     Symlink = 0o120000,
     /// Directory (tree)
     Directory = 0o040000,
+    /// Submodule commit ("gitlink"); the hash names a commit in another repository
+    Gitlink = 0o160000,
 }
 
 impl FileMode {
@@ -97,6 +342,7 @@ impl FileMode {
             0o100755 => Some(FileMode::Executable),
             0o120000 => Some(FileMode::Symlink),
             0o040000 => Some(FileMode::Directory),
+            0o160000 => Some(FileMode::Gitlink),
             _ => None,
         }
     }
@@ -104,6 +350,23 @@ impl FileMode {
     pub fn as_u32(self) -> u32 {
         self as u32
     }
+
+    /// Mode as written in tree objects: octal without leading zeros,
+    /// so directories are `40000` (not `040000`)
+    pub fn as_tree_mode(self) -> &'static str {
+        match self {
+            FileMode::Regular => "100644",
+            FileMode::Executable => "100755",
+            FileMode::Symlink => "120000",
+            FileMode::Directory => "40000",
+            FileMode::Gitlink => "160000",
+        }
+    }
+
+    /// Mode as shown by `git diff` and `git ls-files -s` (always six digits)
+    pub fn as_octal(self) -> String {
+        format!("{:06o}", self.as_u32())
+    }
 }
 
 /// A Tree Entry represents a file or subdirectory in a tree object
@@ -118,6 +381,27 @@ impl TreeEntry {
     pub fn new(mode: FileMode, name: String, hash: ObjectHash) -> Self {
         Self { mode, name, hash }
     }
+
+    /// Compare entries the way Git orders them inside a tree
+    ///
+    /// Names are compared byte by byte, but a directory is compared as if
+    /// its name ended in '/'. So `foo.txt` ('.' is 0x2e) sorts before the
+    /// directory `foo` ("foo/", '/' is 0x2f), which sorts before `foo0`:
+    ///
+    /// ```text
+    /// 100644 blob ...  foo-bar
+    /// 100644 blob ...  foo.txt
+    /// 40000  tree ...  foo
+    /// 100644 blob ...  foo0
+    /// ```
+    pub fn git_cmp(&self, other: &TreeEntry) -> std::cmp::Ordering {
+        self.sort_key().cmp(other.sort_key())
+    }
+
+    fn sort_key(&self) -> impl Iterator<Item = u8> + '_ {
+        let suffix = (self.mode == FileMode::Directory).then_some(b'/');
+        self.name.bytes().chain(suffix)
+    }
 }
 
 /// A Git Tree object represents a directory
@@ -135,8 +419,13 @@ impl TreeObject {
 
     pub fn add_entry(&mut self, entry: TreeEntry) {
         self.entries.push(entry);
-        // Keep entries sorted by name
-        self.entries.sort_by(|a, b| a.name.cmp(&b.name));
+        // Keep entries in Git's tree order
+        self.sort_entries();
+    }
+
+    /// Sort entries into the order Git requires (see `TreeEntry::git_cmp`)
+    pub fn sort_entries(&mut self) {
+        self.entries.sort_by(|a, b| a.git_cmp(b));
     }
 
     pub fn find_entry(&self, name: &str) -> Option<&TreeEntry> {
@@ -150,12 +439,23 @@ impl Default for TreeObject {
     }
 }
 
-/// Signature for author/committer information
+/// Signature for author/committer/tagger information
+///
+/// Serialized as `Name <email> <unix timestamp> <+hhmm>`; the offset is the
+/// author's local timezone and is part of the object's bytes, so it must be
+/// kept to reproduce the same hash.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Signature {
     pub name: String,
     pub email: String,
     pub timestamp: DateTime<Utc>,
+    /// Offset from UTC in minutes (e.g. `+0200` is 120)
+    #[serde(default)]
+    pub timezone_offset: i32,
+    /// The offset was written `-0000` (UTC, local time unknown) rather than
+    /// `+0000`; only meaningful when `timezone_offset` is 0
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub negative_zero_offset: bool,
 }
 
 impl Signature {
@@ -164,29 +464,69 @@ impl Signature {
             name,
             email,
             timestamp: Utc::now(),
+            timezone_offset: 0,
+            negative_zero_offset: false,
         }
     }
+
+    /// Format the timezone offset the way Git stores it (`+0200`, `-0530`)
+    pub fn format_timezone(&self) -> String {
+        let negative =
+            self.timezone_offset < 0 || (self.timezone_offset == 0 && self.negative_zero_offset);
+        let sign = if negative { '-' } else { '+' };
+        let minutes = self.timezone_offset.abs();
+        format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+    }
+
+    /// The timestamp in the signer's own timezone
+    pub fn local_time(&self) -> DateTime<FixedOffset> {
+        let offset = FixedOffset::east_opt(self.timezone_offset * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        self.timestamp.with_timezone(&offset)
+    }
 }
 
 impl std::fmt::Display for Signature {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{} <{}> {}",
+            "{} <{}> {} {}",
             self.name,
             self.email,
-            self.timestamp.timestamp()
+            self.timestamp.timestamp(),
+            self.format_timezone()
         )
     }
 }
 
 /// A Git Commit object
+///
+/// ```text
+/// tree <hash>
+/// parent <hash>                  (zero or more)
+/// author Name <email> 1692960000 +0200
+/// committer Name <email> 1692960000 +0200
+/// encoding ISO-8859-1            ┐
+/// mergetag object <hash>         │ extra headers, kept in order
+///  type commit                   │ (continuation lines start with a space)
+///  ...                           │
+/// gpgsig -----BEGIN PGP ...      ┘
+///
+/// Message
+/// ```
+///
+/// The extra headers and the message are kept exactly as read, so a commit
+/// loaded from a real repository re-serializes to the same bytes (and hash).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CommitObject {
     pub tree: ObjectHash,
     pub parents: Vec<ObjectHash>,
     pub author: Signature,
     pub committer: Signature,
+    /// Headers after `committer` as (name, value); multi-line values are
+    /// joined with '\n' without the continuation space
+    #[serde(default)]
+    pub extra_headers: Vec<(String, String)>,
     pub message: String,
 }
 
@@ -203,6 +543,7 @@ impl CommitObject {
             parents,
             author,
             committer,
+            extra_headers: Vec::new(),
             message,
         }
     }
@@ -210,6 +551,86 @@ impl CommitObject {
     pub fn is_root_commit(&self) -> bool {
         self.parents.is_empty()
     }
+
+    /// Value of the first extra header called `name`
+    pub fn extra_header(&self, name: &str) -> Option<&str> {
+        self.extra_headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Character encoding of the message (`encoding` header), UTF-8 when absent
+    pub fn encoding(&self) -> Option<&str> {
+        self.extra_header("encoding")
+    }
+
+    /// Whether the commit carries an `encoding` header naming something other
+    /// than UTF-8; such commits are stored byte for byte (see `ObjectStore`)
+    pub fn has_legacy_encoding(&self) -> bool {
+        self.encoding().is_some_and(|encoding| {
+            !encoding.eq_ignore_ascii_case("utf-8") && !encoding.eq_ignore_ascii_case("utf8")
+        })
+    }
+
+    /// The PGP/SSH signature (`gpgsig` header), if the commit is signed
+    pub fn gpg_signature(&self) -> Option<&str> {
+        self.extra_header("gpgsig")
+    }
+}
+
+/// A Git Tag object (annotated tag)
+///
+/// Lightweight tags are just refs pointing at a commit. Annotated tags are
+/// objects of their own that point at another object and carry a tagger and
+/// a message:
+///
+/// ```text
+/// object <hash>
+/// type commit
+/// tag v1.0
+/// tagger Name <email> 1692960000 +0200
+///
+/// Release 1.0
+/// ```
+///
+/// `refs/tags/v1.0` then points at the tag object, which has to be "peeled"
+/// to reach the commit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagObject {
+    /// The tagged object
+    pub object: ObjectHash,
+    /// Type of the tagged object
+    pub object_type: GitObjectType,
+    /// Tag name
+    pub tag: String,
+    /// Who created the tag (very old tags have none)
+    pub tagger: Option<Signature>,
+    /// Headers after `tagger` as (name, value); multi-line values are
+    /// joined with '\n' without the continuation space
+    #[serde(default)]
+    pub extra_headers: Vec<(String, String)>,
+    /// Message exactly as stored, including the trailing newline and any signature
+    pub message: String,
+}
+
+impl TagObject {
+    pub fn new(
+        object: ObjectHash,
+        object_type: GitObjectType,
+        tag: String,
+        tagger: Signature,
+        message: String,
+    ) -> Self {
+        Self {
+            object,
+            object_type,
+            tag,
+            tagger: Some(tagger),
+            extra_headers: Vec::new(),
+            message,
+        }
+    }
 }
 
 /// A Git Object that can be stored in the object database
@@ -218,6 +639,7 @@ pub enum GitObject {
     Blob(BlobObject),
     Tree(TreeObject),
     Commit(CommitObject),
+    Tag(TagObject),
 }
 
 impl GitObject {
@@ -226,6 +648,7 @@ impl GitObject {
             GitObject::Blob(_) => GitObjectType::Blob,
             GitObject::Tree(_) => GitObjectType::Tree,
             GitObject::Commit(_) => GitObjectType::Commit,
+            GitObject::Tag(_) => GitObjectType::Tag,
         }
     }
 
@@ -249,4 +672,64 @@ impl GitObject {
             _ => None,
         }
     }
+
+    pub fn as_tag(&self) -> Option<&TagObject> {
+        match self {
+            GitObject::Tag(tag) => Some(tag),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_hash_hex_round_trip() {
+        let hex = "557db03de997c86a4a028e1ebd3a1ceb225be238";
+        let hash = ObjectHash::from_hex(hex).unwrap();
+        assert_eq!(hash.as_bytes().len(), 20);
+        assert_eq!(hash.to_hex(), hex);
+        assert_eq!(hash.to_string(), hex);
+        assert_eq!(hash.to_short_hex(7), "557db03");
+        assert_eq!(
+            (hash.dir_name(), hash.file_name().len()),
+            ("55".to_string(), 38)
+        );
+        assert_eq!(ObjectHash::from_hex(&hex.to_uppercase()).unwrap(), hash);
+        assert_eq!(ObjectHash::from_bytes(hash.as_bytes()).unwrap(), hash);
+
+        let sha256 = ObjectFormat::Sha256.hash_object("blob", b"");
+        assert_eq!(ObjectHash::from_hex(&sha256.to_hex()).unwrap(), sha256);
+        assert_ne!(
+            ObjectHash::null(ObjectFormat::Sha1),
+            ObjectHash::null(ObjectFormat::Sha256)
+        );
+        assert!(ObjectHash::null(ObjectFormat::Sha1).is_null());
+
+        assert!(ObjectHash::from_hex("557db03").is_err());
+        assert!(ObjectHash::from_hex("zz7db03de997c86a4a028e1ebd3a1ceb225be238").is_err());
+        assert!(ObjectHash::from_bytes(&[0; 21]).is_err());
+
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex));
+        assert_eq!(serde_json::from_str::<ObjectHash>(&json).unwrap(), hash);
+    }
+
+    #[test]
+    fn test_object_hash_prefix_and_ordering() {
+        let hash = ObjectHash::from_hex("557db03de997c86a4a028e1ebd3a1ceb225be238").unwrap();
+        assert!(hash.starts_with_hex("557d"));
+        assert!(hash.starts_with_hex("557DB03"));
+        assert!(hash.starts_with_hex(&hash.to_hex()));
+        assert!(!hash.starts_with_hex("557e"));
+        assert!(!hash.starts_with_hex("557g"));
+        assert!(!hash.starts_with_hex(&format!("{}0", hash)));
+
+        let low = ObjectHash::from_hex("0fffffffffffffffffffffffffffffffffffffff").unwrap();
+        let high = ObjectHash::from_hex("1000000000000000000000000000000000000000").unwrap();
+        assert!(low < high);
+        assert!(high < hash);
+    }
 }
@@ -47,6 +47,30 @@ impl GitRef {
     pub fn tag(name: String, hash: ObjectHash) -> Self {
         Self::new(name, hash, RefType::Tag)
     }
+
+    /// Check whether `name` is acceptable as a reference name
+    ///
+    /// Follows the rules of `git check-ref-format`: no empty components, no
+    /// component starting with `.` or ending in `.lock`, no `..`, `@{`,
+    /// whitespace, control characters or any of `~ ^ : ? * [ \`.
+    pub fn is_valid_name(name: &str) -> bool {
+        if name.is_empty() || name == "@" || name.ends_with('.') {
+            return false;
+        }
+        if name.contains("..") || name.contains("@{") {
+            return false;
+        }
+        if name
+            .chars()
+            .any(|c| c.is_ascii_control() || " ~^:?*[\\".contains(c))
+        {
+            return false;
+        }
+
+        name.split('/').all(|component| {
+            !component.is_empty() && !component.starts_with('.') && !component.ends_with(".lock")
+        })
+    }
 }
 
 /// HEAD reference - points to the current branch or commit
@@ -164,8 +188,8 @@ impl ReferenceManager {
     /// Resolve HEAD to get the actual commit hash
     pub fn resolve_head(&self) -> Option<ObjectHash> {
         match &self.head {
-            Some(HeadRef::Direct(hash)) => Some(hash.clone()),
-            Some(HeadRef::Symbolic(ref_name)) => self.find_ref(ref_name).map(|r| r.hash.clone()),
+            Some(HeadRef::Direct(hash)) => Some(*hash),
+            Some(HeadRef::Symbolic(ref_name)) => self.find_ref(ref_name).map(|r| r.hash),
             None => None,
         }
     }
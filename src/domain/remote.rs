@@ -1,3 +1,4 @@
+use crate::domain::objects::{GitObjectType, ObjectHash};
 use std::collections::HashMap;
 use url::Url;
 
@@ -48,14 +49,14 @@ pub struct PackObject {
     pub object_type: PackObjectType,
     /// Uncompressed size of the object
     pub size: u64,
-    /// Object data (compressed)
+    /// Object data (inflated, with deltas resolved)
     pub data: Vec<u8>,
     /// SHA-1 hash of the object
-    pub hash: Option<String>,
+    pub hash: Option<ObjectHash>,
 }
 
 /// Types of objects in a pack file
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PackObjectType {
     /// A commit object
     Commit = 1,
@@ -111,6 +112,27 @@ impl RemoteRepository {
     }
 }
 
+impl PackObjectType {
+    /// Get the Git object type name ("commit", "tree", "blob", "tag")
+    ///
+    /// Returns `None` for delta entries, which only become real objects once
+    /// applied to their base.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            PackObjectType::Commit => Some("commit"),
+            PackObjectType::Tree => Some("tree"),
+            PackObjectType::Blob => Some("blob"),
+            PackObjectType::Tag => Some("tag"),
+            PackObjectType::OfsDelta | PackObjectType::RefDelta => None,
+        }
+    }
+
+    /// Check if this entry is a delta against another object
+    pub fn is_delta(&self) -> bool {
+        matches!(self, PackObjectType::OfsDelta | PackObjectType::RefDelta)
+    }
+}
+
 impl From<u8> for PackObjectType {
     fn from(value: u8) -> Self {
         match value {
@@ -125,6 +147,17 @@ impl From<u8> for PackObjectType {
     }
 }
 
+impl From<&GitObjectType> for PackObjectType {
+    fn from(object_type: &GitObjectType) -> Self {
+        match object_type {
+            GitObjectType::Commit => PackObjectType::Commit,
+            GitObjectType::Tree => PackObjectType::Tree,
+            GitObjectType::Blob => PackObjectType::Blob,
+            GitObjectType::Tag => PackObjectType::Tag,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,4 +217,13 @@ mod tests {
         assert_eq!(PackObjectType::from(7), PackObjectType::RefDelta);
         assert_eq!(PackObjectType::from(99), PackObjectType::Blob); // Unknown defaults to blob
     }
+
+    #[test]
+    fn test_pack_object_type_names() {
+        assert_eq!(PackObjectType::Commit.type_name(), Some("commit"));
+        assert_eq!(PackObjectType::Tag.type_name(), Some("tag"));
+        assert_eq!(PackObjectType::OfsDelta.type_name(), None);
+        assert!(PackObjectType::RefDelta.is_delta());
+        assert!(!PackObjectType::Blob.is_delta());
+    }
 }
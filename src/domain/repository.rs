@@ -1,5 +1,5 @@
 use crate::domain::{index::*, objects::*, references::*};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 /// Git compatibility mode
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,6 +17,8 @@ pub struct RepositoryConfig {
     pub user_email: String,
     /// Git compatibility mode: if true, use .git and index, if false use .git-rs and git-rs-index
     pub git_compat: GitCompatMode,
+    /// Hash algorithm used for object ids (`extensions.objectFormat`)
+    pub object_format: ObjectFormat,
 }
 
 impl RepositoryConfig {
@@ -25,6 +27,7 @@ impl RepositoryConfig {
             user_name: "Git User".to_string(),
             user_email: "user@example.com".to_string(),
             git_compat,
+            object_format: ObjectFormat::Sha1,
         }
     }
 }
@@ -35,6 +38,7 @@ impl Default for RepositoryConfig {
             user_name: "Git User".to_string(),
             user_email: "user@example.com".to_string(),
             git_compat: GitCompatMode::Educational, // Default to safe .git-rs mode
+            object_format: ObjectFormat::Sha1,
         }
     }
 }
@@ -204,6 +208,37 @@ impl GitRepository {
         }
     }
 
+    /// Resolve a path argument to a path relative to the repository root,
+    /// `.` and `..` resolved; `.` itself becomes the empty path
+    pub fn normalize_path(&self, path: &str) -> crate::Result<PathBuf> {
+        let outside = || {
+            format!(
+                "'{}' is outside repository at '{}'",
+                path,
+                self.root_path.display()
+            )
+        };
+        let absolute = self.to_absolute_path(path);
+        let relative = absolute
+            .strip_prefix(&self.root_path)
+            .map_err(|_| outside())?;
+
+        let mut normalized = PathBuf::new();
+        for component in relative.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !normalized.pop() {
+                        return Err(outside().into());
+                    }
+                }
+                Component::Normal(name) => normalized.push(name),
+                _ => return Err(outside().into()),
+            }
+        }
+        Ok(normalized)
+    }
+
     /// Check if a file should be ignored
     /// For now, this is a simple implementation that ignores .git directory,
     /// common temporary files, and patterns from .gitignore.
@@ -213,8 +248,12 @@ impl GitRepository {
         // Convert to string for easier pattern matching
         let path_str = path.to_string_lossy();
 
-        // Always ignore .git directory
-        if path_str.contains(".git") {
+        // Always ignore the repository directories, but not files such as
+        // .gitignore that merely start with the same name
+        if path
+            .components()
+            .any(|c| c.as_os_str() == ".git" || c.as_os_str() == ".git-rs")
+        {
             return true;
         }
 
@@ -315,7 +354,7 @@ mod tests {
         assert_eq!(repo.index_path(), repo_path.join(".git-rs/git-rs-index"));
         assert_eq!(repo.head_path(), repo_path.join(".git-rs/HEAD"));
 
-        let hash = ObjectHash::new("1234567890abcdef1234567890abcdef12345678".to_string());
+        let hash = ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap();
         let expected_path =
             repo_path.join(".git-rs/objects/12/34567890abcdef1234567890abcdef12345678");
         assert_eq!(repo.object_path(&hash), expected_path);
@@ -345,6 +384,8 @@ mod tests {
         let repo = GitRepository::new(temp_dir.path());
 
         assert!(repo.is_ignored(".git/objects"));
+        assert!(repo.is_ignored(".git-rs/index"));
+        assert!(!repo.is_ignored(".gitignore"));
         assert!(repo.is_ignored("file.tmp"));
         assert!(repo.is_ignored("backup~"));
         assert!(repo.is_ignored(".file.swp"));
@@ -0,0 +1,229 @@
+use crate::domain::objects::GitObjectType;
+
+/// One step applied to an object after its name has been looked up
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevisionStep {
+    /// `~<n>`: follow first parents `n` times (`~` alone is `~1`)
+    Ancestor(usize),
+    /// `^<n>`: the n-th parent (`^` alone is `^1`, `^0` is the commit itself)
+    Parent(usize),
+    /// `^{<type>}`: peel tags (and commits to trees) until reaching `type`
+    PeelTo(GitObjectType),
+    /// `^{}`: peel tags until reaching a non-tag object
+    PeelTags,
+}
+
+/// A single revision: `<name><steps>[:<path>]`
+///
+/// ```text
+/// v1.0^{commit}~2^2:src/main.rs
+/// ──┬─ ────┬─── ─┬ ┬─ ─────┬────
+///   │      │     │ │       └── path inside the resulting tree
+///   │      │     │ └── second parent
+///   │      │     └── two generations back
+///   │      └── peel the tag to its commit
+///   └── name: id, abbreviated id, HEAD, branch, tag or ref
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    /// The object name the steps start from
+    pub name: String,
+    /// Navigation applied left to right
+    pub steps: Vec<RevisionStep>,
+    /// `<rev>:<path>`: look `path` up in the tree the revision ends at
+    pub path: Option<String>,
+}
+
+/// A revision expression as accepted by `rev-parse` and `log`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevisionRange {
+    /// `<rev>`
+    Single(Revision),
+    /// `A..B`: commits reachable from B but not from A
+    Range {
+        exclude: Revision,
+        include: Revision,
+    },
+    /// `A...B`: commits reachable from either side but not from both
+    SymmetricDifference { left: Revision, right: Revision },
+}
+
+impl Revision {
+    /// Parse a single revision such as `HEAD~3`, `main^2` or `v1.0^{tree}`
+    ///
+    /// `@` is shorthand for `HEAD`, as in Git.
+    pub fn parse(spec: &str) -> crate::Result<Self> {
+        let (spec, path) = match spec.find(':') {
+            Some(0) => {
+                return Err(
+                    format!("'{}': looking paths up in the index is not supported", spec).into(),
+                )
+            }
+            Some(i) => (&spec[..i], Some(spec[i + 1..].to_string())),
+            None => (spec, None),
+        };
+
+        let name_end = spec.find(['~', '^']).unwrap_or(spec.len());
+        let name = match &spec[..name_end] {
+            "" => return Err(format!("'{}': missing object name", spec).into()),
+            "@" => "HEAD",
+            name => name,
+        };
+
+        let mut steps = Vec::new();
+        let mut rest = &spec[name_end..];
+        while let Some(operator) = rest.chars().next() {
+            if operator != '~' && operator != '^' {
+                return Err(format!("'{}': unexpected '{}'", spec, operator).into());
+            }
+            rest = &rest[operator.len_utf8()..];
+
+            if operator == '^' && rest.starts_with('{') {
+                let close = rest
+                    .find('}')
+                    .ok_or_else(|| format!("'{}': unterminated ^{{...}}", spec))?;
+                let step = match &rest[1..close] {
+                    "" => Some(RevisionStep::PeelTags),
+                    // ^{object} only requires the object to exist
+                    "object" => None,
+                    type_name => {
+                        Some(RevisionStep::PeelTo(type_name.parse().map_err(|_| {
+                            format!("'{}': unknown type '{}'", spec, type_name)
+                        })?))
+                    }
+                };
+                steps.extend(step);
+                rest = &rest[close + 1..];
+                continue;
+            }
+
+            let digits_end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let count = match &rest[..digits_end] {
+                "" => 1,
+                digits => digits
+                    .parse()
+                    .map_err(|_| format!("'{}': number out of range", spec))?,
+            };
+            rest = &rest[digits_end..];
+
+            steps.push(match operator {
+                '~' => RevisionStep::Ancestor(count),
+                _ => RevisionStep::Parent(count),
+            });
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            steps,
+            path,
+        })
+    }
+}
+
+impl RevisionRange {
+    /// Parse `<rev>`, `A..B` or `A...B`; an empty side of a range means `HEAD`
+    pub fn parse(spec: &str) -> crate::Result<Self> {
+        // Paths may legitimately contain "..", so only look for ranges before a ':'
+        let range_part = &spec[..spec.find(':').unwrap_or(spec.len())];
+
+        let side = |s: &str| -> crate::Result<Revision> {
+            if s.is_empty() {
+                Revision::parse("HEAD")
+            } else {
+                Revision::parse(s)
+            }
+        };
+
+        if let Some(i) = range_part.find("...") {
+            let (left, right) = (&spec[..i], &spec[i + 3..]);
+            return Ok(Self::SymmetricDifference {
+                left: side(left)?,
+                right: side(right)?,
+            });
+        }
+        if let Some(i) = range_part.find("..") {
+            let (exclude, include) = (&spec[..i], &spec[i + 2..]);
+            return Ok(Self::Range {
+                exclude: side(exclude)?,
+                include: side(include)?,
+            });
+        }
+
+        Ok(Self::Single(Revision::parse(spec)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_revision_steps() {
+        let revision = Revision::parse("v1.0^{commit}~2^2^^{tree}:src/main.rs").unwrap();
+        assert_eq!(revision.name, "v1.0");
+        assert_eq!(
+            revision.steps,
+            vec![
+                RevisionStep::PeelTo(GitObjectType::Commit),
+                RevisionStep::Ancestor(2),
+                RevisionStep::Parent(2),
+                RevisionStep::Parent(1),
+                RevisionStep::PeelTo(GitObjectType::Tree),
+            ]
+        );
+        assert_eq!(revision.path.as_deref(), Some("src/main.rs"));
+
+        let revision = Revision::parse("@~^0^{}^{object}").unwrap();
+        assert_eq!(revision.name, "HEAD");
+        assert_eq!(
+            revision.steps,
+            vec![
+                RevisionStep::Ancestor(1),
+                RevisionStep::Parent(0),
+                RevisionStep::PeelTags,
+            ]
+        );
+
+        assert_eq!(Revision::parse("HEAD:").unwrap().path.as_deref(), Some(""));
+        assert!(Revision::parse("~1").is_err());
+        assert!(Revision::parse("HEAD^{").is_err());
+        assert!(Revision::parse("HEAD^{bogus}").is_err());
+        assert!(Revision::parse(":README").is_err());
+
+        // Characters after an operator may be several bytes long
+        let error = Revision::parse("HEAD^é").unwrap_err();
+        assert!(error.to_string().contains("unexpected 'é'"), "{}", error);
+        assert!(Revision::parse("HEAD~2€").is_err());
+    }
+
+    #[test]
+    fn test_parse_revision_ranges() {
+        let head = Revision::parse("HEAD").unwrap();
+        let main = Revision::parse("main").unwrap();
+
+        assert_eq!(
+            RevisionRange::parse("main..").unwrap(),
+            RevisionRange::Range {
+                exclude: main.clone(),
+                include: head.clone()
+            }
+        );
+        assert_eq!(
+            RevisionRange::parse("...main").unwrap(),
+            RevisionRange::SymmetricDifference {
+                left: head,
+                right: main.clone()
+            }
+        );
+        assert_eq!(
+            RevisionRange::parse("main").unwrap(),
+            RevisionRange::Single(main)
+        );
+        assert!(matches!(
+            RevisionRange::parse("HEAD:docs/../README").unwrap(),
+            RevisionRange::Single(_)
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::domain::objects::ObjectFormat;
+use crate::infrastructure::object_cache::CacheLimits;
+
+/// Default for `core.bigFileThreshold`
+const DEFAULT_BIG_FILE_THRESHOLD: u64 = 512 << 20;
+
+/// Config Storage Implementation
+///
+/// Reads values from the repository's INI-style `config` file:
+///
+/// ```text
+/// [core]
+///     repositoryformatversion = 1
+/// [extensions]
+///     objectFormat = sha256
+/// [remote "origin"]
+///     url = https://example.com/repo.git
+/// ```
+///
+/// Values are looked up as `section.key` or `section.subsection.key`.
+/// Section and key names are case-insensitive, subsection names are not.
+pub struct ConfigStore {
+    config_path: PathBuf,
+}
+
+impl ConfigStore {
+    /// Create a config store for the given config file
+    pub fn new(config_path: PathBuf) -> Self {
+        Self { config_path }
+    }
+
+    /// Look up a value; the last occurrence wins, like in Git
+    pub fn get(&self, key: &str) -> crate::Result<Option<String>> {
+        if !self.config_path.exists() {
+            return Ok(None);
+        }
+
+        let (section, name) = key
+            .rsplit_once('.')
+            .ok_or_else(|| format!("Invalid config key: {}", key))?;
+        let section = Self::normalize_section(section);
+        let name = name.to_ascii_lowercase();
+
+        let content = fs::read_to_string(&self.config_path)?;
+        let mut current_section = String::new();
+        let mut value = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current_section = match header.split_once(' ') {
+                    Some((section, subsection)) => format!(
+                        "{}.{}",
+                        section.to_ascii_lowercase(),
+                        subsection.trim().trim_matches('"')
+                    ),
+                    None => Self::normalize_section(header),
+                };
+                continue;
+            }
+
+            if current_section != section {
+                continue;
+            }
+
+            let (entry_name, entry_value) = match line.split_once('=') {
+                Some((entry_name, entry_value)) => (entry_name.trim(), entry_value.trim()),
+                // A bare key means "true"
+                None => (line, "true"),
+            };
+            if entry_name.to_ascii_lowercase() == name {
+                value = Some(entry_value.trim_matches('"').to_string());
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// The repository's object format (`extensions.objectFormat`, default sha1)
+    pub fn object_format(&self) -> crate::Result<ObjectFormat> {
+        match self.get("extensions.objectFormat")? {
+            Some(format) => Ok(format.parse()?),
+            None => Ok(ObjectFormat::Sha1),
+        }
+    }
+
+    /// Files larger than this are treated as binary and never loaded whole
+    /// (`core.bigFileThreshold`, default 512 MiB)
+    pub fn big_file_threshold(&self) -> crate::Result<u64> {
+        match self.get("core.bigFileThreshold")? {
+            Some(value) => Self::parse_size(&value),
+            None => Ok(DEFAULT_BIG_FILE_THRESHOLD),
+        }
+    }
+
+    /// Sizes of the in-process object caches (`core.objectCacheLimit` for
+    /// parsed objects, `core.deltaBaseCacheLimit` for pack delta bases)
+    pub fn cache_limits(&self) -> crate::Result<CacheLimits> {
+        let mut limits = CacheLimits::default();
+        if let Some(value) = self.get("core.objectCacheLimit")? {
+            limits.objects = Self::parse_size(&value)? as usize;
+        }
+        if let Some(value) = self.get("core.deltaBaseCacheLimit")? {
+            limits.delta_bases = Self::parse_size(&value)? as usize;
+        }
+        Ok(limits)
+    }
+
+    /// Parse a size with an optional k/m/g suffix, e.g. "512m"
+    fn parse_size(value: &str) -> crate::Result<u64> {
+        let value = value.trim().to_ascii_lowercase();
+        let (digits, unit) = match value.chars().last() {
+            Some('k') => (&value[..value.len() - 1], 1 << 10),
+            Some('m') => (&value[..value.len() - 1], 1 << 20),
+            Some('g') => (&value[..value.len() - 1], 1 << 30),
+            _ => (value.as_str(), 1),
+        };
+        let number: u64 = digits
+            .parse()
+            .map_err(|_| format!("Invalid size in config: {}", value))?;
+        Ok(number * unit)
+    }
+
+    /// Lowercase the section name, keeping a dotted subsection as written
+    fn normalize_section(section: &str) -> String {
+        match section.split_once('.') {
+            Some((section, subsection)) => {
+                format!("{}.{}", section.to_ascii_lowercase(), subsection)
+            }
+            None => section.to_ascii_lowercase(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_get_config_values() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config");
+        fs::write(
+            &config_path,
+            "[core]\n\trepositoryformatversion = 1\n\tbare\n\
+             [extensions]\n\tobjectFormat = sha256\n\
+             [core]\n\tbigFileThreshold = 64k\n\tdeltaBaseCacheLimit = 0\n\
+             [remote \"origin\"]\n\turl = https://example.com/repo.git\n",
+        )
+        .unwrap();
+
+        let store = ConfigStore::new(config_path);
+        assert_eq!(
+            store
+                .get("core.repositoryFormatVersion")
+                .unwrap()
+                .as_deref(),
+            Some("1")
+        );
+        assert_eq!(store.get("core.bare").unwrap().as_deref(), Some("true"));
+        assert_eq!(
+            store.get("remote.origin.url").unwrap().as_deref(),
+            Some("https://example.com/repo.git")
+        );
+        assert_eq!(store.get("user.name").unwrap(), None);
+        assert_eq!(store.object_format().unwrap(), ObjectFormat::Sha256);
+        assert_eq!(store.big_file_threshold().unwrap(), 64 * 1024);
+        assert_eq!(
+            store.cache_limits().unwrap(),
+            CacheLimits {
+                delta_bases: 0,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_config_defaults_to_sha1() {
+        let temp_dir = tempdir().unwrap();
+        let store = ConfigStore::new(temp_dir.path().join("config"));
+        assert_eq!(store.object_format().unwrap(), ObjectFormat::Sha1);
+        assert_eq!(store.big_file_threshold().unwrap(), 512 * 1024 * 1024);
+        assert_eq!(store.cache_limits().unwrap(), CacheLimits::default());
+    }
+}
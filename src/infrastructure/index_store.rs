@@ -1,24 +1,72 @@
+use chrono::{DateTime, Utc};
 use std::fs;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::domain::index::*;
+use crate::domain::objects::{FileMode, ObjectFormat, ObjectHash};
+use crate::infrastructure::config_store::ConfigStore;
+use crate::infrastructure::lock_file::LockFile;
+
+/// Signature at the start of a binary index ("DIRectory Cache")
+const INDEX_SIGNATURE: &[u8; 4] = b"DIRC";
+
+/// Stat fields before the object id in an entry: ctime, mtime (seconds and
+/// nanoseconds each), dev, ino, mode, uid, gid and size, 32 bits apiece
+const ENTRY_STAT_LEN: usize = 40;
+
+/// Entry flags: assume-valid, extended, 2-bit stage and 12-bit name length
+const FLAG_ASSUME_VALID: u16 = 0x8000;
+const FLAG_EXTENDED: u16 = 0x4000;
+const FLAG_STAGE_SHIFT: u16 = 12;
+const FLAG_NAME_MASK: u16 = 0x0fff;
+
+/// Extended flags (version 3 and later)
+const EXTENDED_SKIP_WORKTREE: u16 = 0x4000;
+const EXTENDED_INTENT_TO_ADD: u16 = 0x2000;
+
+/// How the index is stored on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    /// Pretty-printed JSON, easy to inspect with `jq` (`git-rs-index`)
+    Json,
+    /// Git's own binary format, readable by real Git (`index`)
+    Dirc,
+}
+
+impl IndexFormat {
+    /// The format for an index file: Git's `index` is binary, `git-rs-index` is JSON
+    pub fn for_path(index_path: &Path) -> Self {
+        if index_path.file_name().is_some_and(|name| name == "index") {
+            IndexFormat::Dirc
+        } else {
+            IndexFormat::Json
+        }
+    }
+}
 
 /// Index Storage Implementation
 ///
 /// This handles the physical storage and retrieval of the Git index (staging area).
-/// The index is stored in .git/index as a binary file containing staged file information.
 ///
-/// For simplicity, we'll use JSON format instead of Git's complex binary format.
-/// In a real implementation, you'd want to use Git's actual index format.
+/// In educational mode the index is `.git-rs/git-rs-index`, stored as JSON so
+/// it can be read with `jq`. In compatible mode it is `.git/index` in Git's
+/// binary format (see [`BinaryIndexStore`]), so real Git and git-rs can take
+/// turns on the same repository. Loading recognizes either format.
 pub struct IndexStore {
     index_path: PathBuf,
+    format: IndexFormat,
 }
 
 impl IndexStore {
-    /// Create a new index store
+    /// Create a new index store, picking the format from the file name
     pub fn new(index_path: PathBuf) -> Self {
-        Self { index_path }
+        let format = IndexFormat::for_path(&index_path);
+        Self::with_format(index_path, format)
+    }
+
+    /// Create a new index store that saves in the given format
+    pub fn with_format(index_path: PathBuf, format: IndexFormat) -> Self {
+        Self { index_path, format }
     }
 
     /// Load the index from disk
@@ -27,20 +75,61 @@ impl IndexStore {
             return Ok(GitIndex::new());
         }
 
-        let content = fs::read_to_string(&self.index_path)?;
-        if content.trim().is_empty() {
-            return Ok(GitIndex::new());
-        }
-
-        let index: GitIndex = serde_json::from_str(&content)?;
+        let content = fs::read(&self.index_path)?;
+        let mut index = if content.starts_with(INDEX_SIGNATURE) {
+            self.binary_store().parse_index(&content)?
+        } else if content.iter().all(u8::is_ascii_whitespace) {
+            GitIndex::new()
+        } else {
+            let index: GitIndex = serde_json::from_slice(&content)?;
+            Self::check_stages(&index)?;
+            index
+        };
+
+        index.timestamp = modified_time(&self.index_path);
         Ok(index)
     }
 
+    /// Reject a JSON index whose entries are filed under the wrong stage,
+    /// which the binary format cannot even express
+    fn check_stages(index: &GitIndex) -> crate::Result<()> {
+        let merged = index.entries.values().map(|entry| (entry, 0..=0));
+        let conflicted = index
+            .conflicts
+            .values()
+            .flat_map(|conflict| conflict.entries())
+            .map(|entry| (entry, 1..=3));
+        for (entry, stages) in merged.chain(conflicted) {
+            if !stages.contains(&entry.stage) {
+                return Err(
+                    format!("{}: invalid stage {}", entry.path.display(), entry.stage).into(),
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Save the index to disk
+    ///
+    /// The new index is written to `<index>.lock` and renamed over the old
+    /// one; saving fails while another process holds that lock.
     pub fn save_index(&self, index: &GitIndex) -> crate::Result<()> {
-        let content = serde_json::to_string_pretty(index)?;
-        fs::write(&self.index_path, content)?;
-        Ok(())
+        match self.format {
+            IndexFormat::Json => {
+                let content = serde_json::to_string_pretty(index)?;
+                LockFile::write(&self.index_path, content.as_bytes())
+            }
+            IndexFormat::Dirc => self.binary_store().save_index(index),
+        }
+    }
+
+    /// Save the index unless that fails, e.g. because another process
+    /// holds its lock
+    ///
+    /// For opportunistic updates such as refreshed stat data: a lost update
+    /// only means the next command reads a few files again.
+    pub fn update_index_if_able(&self, index: &GitIndex) {
+        let _ = self.save_index(index);
     }
 
     /// Clear the index (remove all staged files)
@@ -66,20 +155,72 @@ impl IndexStore {
     pub fn index_path(&self) -> &PathBuf {
         &self.index_path
     }
+
+    /// The format `save_index` writes
+    pub fn format(&self) -> IndexFormat {
+        self.format
+    }
+
+    fn binary_store(&self) -> BinaryIndexStore {
+        BinaryIndexStore::new(self.index_path.clone())
+    }
 }
 
 /// Binary Index Store Implementation
 ///
-/// This is a more advanced implementation that uses a binary format
-/// similar to Git's actual index format, but simplified for educational purposes.
+/// Reads and writes Git's binary index ("dircache") format, versions 2 to 4:
+///
+/// ```text
+/// +------------------------------------------+
+/// | "DIRC" | version | number of entries     |  12-byte header
+/// +------------------------------------------+
+/// | entry 1                                  |  sorted by path, then stage
+/// | ...                                      |
+/// +------------------------------------------+
+/// | extension: signature | size | data       |  optional (TREE, REUC, ...)
+/// | ...                                      |
+/// +------------------------------------------+
+/// | checksum of everything above             |  SHA-1 (SHA-256 in sha256 repos)
+/// +------------------------------------------+
+/// ```
+///
+/// Each entry is a fixed header followed by its path:
+///
+/// ```text
+/// ctime s | ctime ns | mtime s | mtime ns | dev | ino | mode | uid | gid | size
+///   (ten 32-bit fields: what `stat` reported when the file was staged)
+/// object id (20 or 32 bytes)
+/// flags: assume-valid(1) | extended(1) | stage(2) | name length(12)
+/// extended flags (v3+, only when the extended bit is set):
+///        reserved(1) | skip-worktree(1) | intent-to-add(1) | unused(13)
+/// path, then 1-8 NUL bytes padding the entry to a multiple of 8   (v2, v3)
+/// path as (bytes to drop from the previous path, suffix NUL)      (v4)
+/// ```
+///
+/// Version 2 is the default; version 3 is written only when an entry needs
+/// extended flags, and version 4 when `GitIndex::version` asks for it. The
+/// prefix compression of version 4 makes large indexes much smaller, since
+/// neighbouring paths share long directory prefixes.
 pub struct BinaryIndexStore {
     index_path: PathBuf,
+    format: ObjectFormat,
 }
 
 impl BinaryIndexStore {
-    /// Create a new binary index store
+    /// Create a new binary index store, using the object format from the
+    /// repository config next to the index
     pub fn new(index_path: PathBuf) -> Self {
-        Self { index_path }
+        let format = index_path
+            .parent()
+            .map(|git_dir| ConfigStore::new(git_dir.join("config")))
+            .and_then(|config| config.object_format().ok())
+            .unwrap_or_default();
+        Self::with_format(index_path, format)
+    }
+
+    /// Create a binary index store for an explicit object format
+    pub fn with_format(index_path: PathBuf, format: ObjectFormat) -> Self {
+        Self { index_path, format }
     }
 
     /// Load the index from binary format
@@ -88,131 +229,369 @@ impl BinaryIndexStore {
             return Ok(GitIndex::new());
         }
 
-        let mut file = fs::File::open(&self.index_path)?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
-
-        self.deserialize_index(&buffer)
+        let mut index = self.parse_index(&fs::read(&self.index_path)?)?;
+        index.timestamp = modified_time(&self.index_path);
+        Ok(index)
     }
 
     /// Save the index in binary format
     pub fn save_index(&self, index: &GitIndex) -> crate::Result<()> {
         let serialized = self.serialize_index(index)?;
-        let mut file = fs::File::create(&self.index_path)?;
-        file.write_all(&serialized)?;
-        Ok(())
+        LockFile::write(&self.index_path, &serialized)
     }
 
-    /// Serialize index to binary format
+    /// Serialize an index, checksum included
     ///
-    /// Simplified format:
-    /// - 4 bytes: signature "DIRC" (DIRectory Cache)
-    /// - 4 bytes: version number
-    /// - 4 bytes: number of entries
-    /// - For each entry:
-    ///   - Entry data (simplified)
-    fn serialize_index(&self, index: &GitIndex) -> crate::Result<Vec<u8>> {
-        let mut buffer = Vec::new();
-
-        // Write signature
-        buffer.extend_from_slice(b"DIRC");
-
-        // Write version (big-endian)
-        buffer.extend_from_slice(&index.version.to_be_bytes());
-
-        // Write number of entries
-        let entry_count = index.entries.len() as u32;
-        buffer.extend_from_slice(&entry_count.to_be_bytes());
-
-        // Write entries (simplified - just use JSON for each entry)
-        for entry in index.get_sorted_entries() {
-            let entry_json = serde_json::to_string(entry)?;
-            let entry_bytes = entry_json.as_bytes();
+    /// Extensions that describe the entries (such as the `TREE` cache) are
+    /// dropped, as they may no longer match; Git rebuilds them when needed.
+    pub fn serialize_index(&self, index: &GitIndex) -> crate::Result<Vec<u8>> {
+        let mut entries: Vec<(Vec<u8>, &IndexEntry)> = index
+            .entries
+            .values()
+            .chain(index.conflicts.values().flat_map(Conflict::entries))
+            .map(|entry| (path_bytes(&entry.path), entry))
+            .collect();
+        // Byte order, not component order: "a.b" sorts before "a/b"
+        entries.sort_by(|(a, x), (b, y)| a.cmp(b).then(x.stage.cmp(&y.stage)));
+
+        let version: u32 = match index.version {
+            4 => 4,
+            2 | 3 if entries.iter().any(|(_, entry)| entry.has_extended_flags()) => 3,
+            2 | 3 => 2,
+            version => return Err(format!("index version {} is not supported", version).into()),
+        };
 
-            // Write entry length
-            let entry_len = entry_bytes.len() as u32;
-            buffer.extend_from_slice(&entry_len.to_be_bytes());
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(INDEX_SIGNATURE);
+        buffer.extend_from_slice(&version.to_be_bytes());
+        buffer.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+
+        let mut previous_path: &[u8] = &[];
+        for (path, entry) in &entries {
+            self.write_entry(&mut buffer, entry, path, previous_path, version)?;
+            previous_path = path;
+        }
 
-            // Write entry data
-            buffer.extend_from_slice(entry_bytes);
+        for extension in &index.extensions {
+            if extension.depends_on_entries() {
+                continue;
+            }
+            buffer.extend_from_slice(&extension.signature);
+            buffer.extend_from_slice(&(extension.data.len() as u32).to_be_bytes());
+            buffer.extend_from_slice(&extension.data);
         }
 
+        let checksum = self.format.digest(&buffer);
+        buffer.extend_from_slice(&checksum);
         Ok(buffer)
     }
 
-    /// Deserialize index from binary format
-    fn deserialize_index(&self, data: &[u8]) -> crate::Result<GitIndex> {
-        let mut pos = 0;
-
-        // Check signature
-        if data.len() < 4 || &data[0..4] != b"DIRC" {
-            return Err("Invalid index file signature".into());
+    /// Parse a binary index, verifying its checksum
+    pub fn parse_index(&self, data: &[u8]) -> crate::Result<GitIndex> {
+        let hash_len = self.format.raw_len();
+        if data.len() < 12 + hash_len || &data[..4] != INDEX_SIGNATURE {
+            return Err("index file corrupt: bad signature".into());
         }
-        pos += 4;
 
-        // Read version
-        if data.len() < pos + 4 {
-            return Err("Invalid index file: missing version".into());
+        let (content, checksum) = data.split_at(data.len() - hash_len);
+        // An all-zero checksum means it was skipped (`index.skipHash`)
+        if checksum.iter().any(|&b| b != 0) && self.format.digest(content) != checksum {
+            return Err("index file corrupt: bad checksum".into());
         }
-        let version = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
-        pos += 4;
 
-        // Read entry count
-        if data.len() < pos + 4 {
-            return Err("Invalid index file: missing entry count".into());
+        let version = read_u32(content, 4)?;
+        if !(2..=4).contains(&version) {
+            return Err(format!("index file version {} is not supported", version).into());
         }
-        let entry_count =
-            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
-        pos += 4;
+        let entry_count = read_u32(content, 8)?;
 
         let mut index = GitIndex::new();
         index.version = version;
 
-        // Read entries
+        let mut pos = 12;
+        let mut previous_path = Vec::new();
         for _ in 0..entry_count {
-            // Read entry length
-            if data.len() < pos + 4 {
-                return Err("Invalid index file: missing entry length".into());
+            let (entry, path) = self.read_entry(content, &mut pos, &previous_path, version)?;
+            index.add_entry(entry);
+            previous_path = path;
+        }
+
+        while pos < content.len() {
+            let header = content
+                .get(pos..pos + 8)
+                .ok_or("index file corrupt: truncated extension")?;
+            let signature: [u8; 4] = header[..4].try_into()?;
+            let size = read_u32(header, 4)? as usize;
+            let data = content
+                .get(pos + 8..pos + 8 + size)
+                .ok_or("index file corrupt: truncated extension")?;
+
+            let extension = IndexExtension::new(signature, data.to_vec());
+            if !extension.is_optional() {
+                return Err(format!(
+                    "index uses {} extension, which git-rs does not understand",
+                    extension.name()
+                )
+                .into());
             }
-            let entry_len =
-                u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
-                    as usize;
-            pos += 4;
-
-            // Read entry data
-            if data.len() < pos + entry_len {
-                return Err("Invalid index file: truncated entry data".into());
+            index.extensions.push(extension);
+            pos += 8 + size;
+        }
+
+        Ok(index)
+    }
+
+    fn write_entry(
+        &self,
+        buffer: &mut Vec<u8>,
+        entry: &IndexEntry,
+        path: &[u8],
+        previous_path: &[u8],
+        version: u32,
+    ) -> crate::Result<()> {
+        if entry.hash.as_bytes().len() != self.format.raw_len() {
+            return Err(format!(
+                "{}: object id {} is not a {} id",
+                entry.path.display(),
+                entry.hash,
+                self.format
+            )
+            .into());
+        }
+        if entry.stage > 3 {
+            return Err(format!("{}: invalid stage {}", entry.path.display(), entry.stage).into());
+        }
+
+        let start = buffer.len();
+        for field in [
+            entry.ctime.timestamp() as u32,
+            entry.ctime.timestamp_subsec_nanos(),
+            entry.mtime.timestamp() as u32,
+            entry.mtime.timestamp_subsec_nanos(),
+            entry.dev,
+            entry.ino,
+            entry.mode.as_u32(),
+            entry.uid,
+            entry.gid,
+            // Like Git, only the low 32 bits of the size are kept
+            entry.size as u32,
+        ] {
+            buffer.extend_from_slice(&field.to_be_bytes());
+        }
+        buffer.extend_from_slice(entry.hash.as_bytes());
+
+        let mut flags = (entry.stage << FLAG_STAGE_SHIFT) | path.len().min(0xfff) as u16;
+        if entry.assume_valid {
+            flags |= FLAG_ASSUME_VALID;
+        }
+        let extended = entry.has_extended_flags();
+        if extended {
+            flags |= FLAG_EXTENDED;
+        }
+        buffer.extend_from_slice(&flags.to_be_bytes());
+        if extended {
+            let mut extended_flags = 0u16;
+            if entry.skip_worktree {
+                extended_flags |= EXTENDED_SKIP_WORKTREE;
+            }
+            if entry.intent_to_add {
+                extended_flags |= EXTENDED_INTENT_TO_ADD;
             }
-            let entry_bytes = &data[pos..pos + entry_len];
-            let entry_json = String::from_utf8(entry_bytes.to_vec())?;
-            let entry: IndexEntry = serde_json::from_str(&entry_json)?;
+            buffer.extend_from_slice(&extended_flags.to_be_bytes());
+        }
 
-            index.add_entry(entry);
-            pos += entry_len;
+        if version == 4 {
+            let common = path
+                .iter()
+                .zip(previous_path)
+                .take_while(|(a, b)| a == b)
+                .count();
+            write_varint(buffer, (previous_path.len() - common) as u64);
+            buffer.extend_from_slice(&path[common..]);
+            buffer.push(0);
+        } else {
+            buffer.extend_from_slice(path);
+            // At least one NUL, up to the next multiple of 8
+            let padding = 8 - (buffer.len() - start) % 8;
+            buffer.resize(buffer.len() + padding, 0);
         }
 
-        Ok(index)
+        Ok(())
+    }
+
+    fn read_entry(
+        &self,
+        data: &[u8],
+        pos: &mut usize,
+        previous_path: &[u8],
+        version: u32,
+    ) -> crate::Result<(IndexEntry, Vec<u8>)> {
+        let start = *pos;
+        let hash_len = self.format.raw_len();
+        let truncated = || "index file corrupt: truncated entry";
+
+        let stat = data
+            .get(start..start + ENTRY_STAT_LEN)
+            .ok_or_else(truncated)?;
+        let field = |i: usize| read_u32(stat, i * 4);
+        let hash = data
+            .get(start + ENTRY_STAT_LEN..start + ENTRY_STAT_LEN + hash_len)
+            .ok_or_else(truncated)?;
+        *pos = start + ENTRY_STAT_LEN + hash_len;
+
+        let flags = read_u16(data, *pos)?;
+        *pos += 2;
+        let extended_flags = if flags & FLAG_EXTENDED != 0 {
+            if version < 3 {
+                return Err("index file corrupt: extended flags in a version 2 index".into());
+            }
+            let extended_flags = read_u16(data, *pos)?;
+            *pos += 2;
+            extended_flags
+        } else {
+            0
+        };
+
+        let path = if version == 4 {
+            let (strip, used) = read_varint(&data[*pos..])?;
+            *pos += used;
+            let keep = previous_path
+                .len()
+                .checked_sub(strip as usize)
+                .ok_or("index file corrupt: bad path prefix")?;
+            let suffix = read_until_nul(data, *pos)?;
+            *pos += suffix.len() + 1;
+            [&previous_path[..keep], suffix].concat()
+        } else {
+            let path = read_until_nul(data, *pos)?.to_vec();
+            // The entry is padded with NULs to a multiple of 8 bytes
+            let entry_len = *pos - start + path.len();
+            *pos = start + entry_len + (8 - entry_len % 8);
+            path
+        };
+        if (flags & FLAG_NAME_MASK) as usize != path.len().min(0xfff) {
+            return Err(format!(
+                "index file corrupt: bad name length for {}",
+                String::from_utf8_lossy(&path)
+            )
+            .into());
+        }
+
+        let mode = field(6)?;
+        let entry = IndexEntry {
+            ctime: IndexEntry::timestamp(field(0)? as i64, field(1)? as i64),
+            mtime: IndexEntry::timestamp(field(2)? as i64, field(3)? as i64),
+            dev: field(4)?,
+            ino: field(5)?,
+            mode: FileMode::from_u32(mode)
+                .ok_or_else(|| format!("index file corrupt: invalid mode {:o}", mode))?,
+            uid: field(7)?,
+            gid: field(8)?,
+            size: field(9)? as u64,
+            hash: ObjectHash::from_bytes(hash)?,
+            stage: (flags >> FLAG_STAGE_SHIFT) & 0x3,
+            path: PathBuf::from(String::from_utf8(path.clone())?),
+            assume_valid: flags & FLAG_ASSUME_VALID != 0,
+            skip_worktree: extended_flags & EXTENDED_SKIP_WORKTREE != 0,
+            intent_to_add: extended_flags & EXTENDED_INTENT_TO_ADD != 0,
+        };
+
+        Ok((entry, path))
+    }
+}
+
+/// When the index file was written; entries modified at or after it are
+/// racily clean
+fn modified_time(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::from)
+}
+
+/// An entry path as stored in the index
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+fn read_u32(data: &[u8], pos: usize) -> crate::Result<u32> {
+    let bytes = data
+        .get(pos..pos + 4)
+        .ok_or("index file corrupt: unexpected end of data")?;
+    Ok(u32::from_be_bytes(bytes.try_into()?))
+}
+
+fn read_u16(data: &[u8], pos: usize) -> crate::Result<u16> {
+    let bytes = data
+        .get(pos..pos + 2)
+        .ok_or("index file corrupt: unexpected end of data")?;
+    Ok(u16::from_be_bytes(bytes.try_into()?))
+}
+
+fn read_until_nul(data: &[u8], pos: usize) -> crate::Result<&[u8]> {
+    let rest = data.get(pos..).unwrap_or_default();
+    let len = rest
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or("index file corrupt: unterminated path")?;
+    Ok(&rest[..len])
+}
+
+/// Write a number in the variable-length encoding of version 4 paths
+///
+/// Seven bits per byte, most significant first, with the high bit set on
+/// all but the last byte; each continuation also adds one, so no value has
+/// two encodings (the same scheme as OFS_DELTA offsets in packs).
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    let mut bytes = vec![(value & 0x7f) as u8];
+    value >>= 7;
+    while value > 0 {
+        value -= 1;
+        bytes.push(0x80 | (value & 0x7f) as u8);
+        value >>= 7;
     }
+    buffer.extend(bytes.iter().rev());
+}
+
+/// Read a number written by `write_varint`, returning it and its length
+fn read_varint(data: &[u8]) -> crate::Result<(u64, usize)> {
+    let mut used = 0;
+    let mut next = || -> crate::Result<u8> {
+        let byte = *data
+            .get(used)
+            .ok_or("index file corrupt: truncated path prefix")?;
+        used += 1;
+        Ok(byte)
+    };
+
+    let mut byte = next()?;
+    let mut value = (byte & 0x7f) as u64;
+    while byte & 0x80 != 0 {
+        byte = next()?;
+        value = ((value + 1) << 7) | (byte & 0x7f) as u64;
+    }
+    Ok((value, used))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::objects::{FileMode, ObjectHash};
+    use crate::domain::objects::{FileMode, ObjectFormat, ObjectHash};
     use std::path::PathBuf;
     use tempfile::tempdir;
 
     #[test]
     fn test_json_index_store() {
         let temp_dir = tempdir().unwrap();
-        let index_path = temp_dir.path().join("index");
-        let store = IndexStore::new(index_path);
+        let index_path = temp_dir.path().join("git-rs-index");
+        let store = IndexStore::new(index_path.clone());
+        assert_eq!(store.format(), IndexFormat::Json);
 
         // Create test index
         let mut index = GitIndex::new();
         let entry = IndexEntry::new(
             PathBuf::from("test.txt"),
-            ObjectHash::new("1234567890abcdef1234567890abcdef12345678".to_string()),
+            ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap(),
             13,
             FileMode::Regular,
         );
@@ -224,6 +603,36 @@ mod tests {
 
         assert_eq!(index.len(), loaded_index.len());
         assert_eq!(index.version, loaded_index.version);
+        assert!(fs::read_to_string(&index_path).unwrap().starts_with('{'));
+    }
+
+    #[test]
+    fn test_invalid_stage_is_refused() {
+        let temp_dir = tempdir().unwrap();
+        let index_path = temp_dir.path().join("git-rs-index");
+        let store = IndexStore::new(index_path.clone());
+
+        let mut index = GitIndex::new();
+        let entry = IndexEntry::new(
+            PathBuf::from("test.txt"),
+            ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap(),
+            13,
+            FileMode::Regular,
+        );
+        index.add_entry(entry.clone());
+
+        // Adding an entry with no such stage changes nothing
+        let mut invalid = entry;
+        invalid.stage = 5;
+        let before = index.clone();
+        index.add_entry(invalid.clone());
+        assert_eq!(index, before);
+
+        // A hand-edited JSON index with one is rejected on load
+        index.entries.insert(invalid.path.clone(), invalid);
+        store.save_index(&index).unwrap();
+        let error = store.load_index().unwrap_err();
+        assert!(error.to_string().contains("invalid stage 5"), "{}", error);
     }
 
     #[test]
@@ -236,7 +645,7 @@ mod tests {
         let mut index = GitIndex::new();
         let entry = IndexEntry::new(
             PathBuf::from("test.txt"),
-            ObjectHash::new("abcdef1234567890abcdef1234567890abcdef12".to_string()),
+            ObjectHash::from_hex("abcdef1234567890abcdef1234567890abcdef12").unwrap(),
             13,
             FileMode::Regular,
         );
@@ -272,7 +681,7 @@ mod tests {
         let mut index = GitIndex::new();
         let entry = IndexEntry::new(
             PathBuf::from("test.txt"),
-            ObjectHash::new("1234567890abcdef1234567890abcdef12345678".to_string()),
+            ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap(),
             13,
             FileMode::Regular,
         );
@@ -284,4 +693,163 @@ mod tests {
         let cleared_index = store.load_index().unwrap();
         assert!(cleared_index.is_empty());
     }
+
+    #[test]
+    fn test_save_index_respects_lock() {
+        let temp_dir = tempdir().unwrap();
+        let index_path = temp_dir.path().join("index");
+        let store = IndexStore::new(index_path.clone());
+
+        let mut index = GitIndex::new();
+        index.add_entry(IndexEntry::new(
+            PathBuf::from("test.txt"),
+            ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap(),
+            13,
+            FileMode::Regular,
+        ));
+        store.save_index(&index).unwrap();
+        assert!(!temp_dir.path().join("index.lock").exists());
+
+        let lock = LockFile::acquire(&index_path).unwrap();
+        assert!(store.save_index(&GitIndex::new()).is_err());
+        assert_eq!(store.load_index().unwrap().entries.len(), 1);
+
+        drop(lock);
+        store.save_index(&GitIndex::new()).unwrap();
+        assert!(store.load_index().unwrap().is_empty());
+    }
+
+    fn entry(path: &str, content: &str) -> IndexEntry {
+        let mut entry = IndexEntry::new(
+            PathBuf::from(path),
+            ObjectFormat::Sha1.hash_object("blob", content.as_bytes()),
+            content.len() as u64,
+            FileMode::Regular,
+        );
+        entry.mtime = IndexEntry::timestamp(1_700_000_000, 123_456_789);
+        entry.ctime = entry.mtime;
+        entry
+    }
+
+    #[test]
+    fn test_binary_entry_layout() {
+        let temp_dir = tempdir().unwrap();
+        let store =
+            BinaryIndexStore::with_format(temp_dir.path().join("index"), ObjectFormat::Sha1);
+        let mut index = GitIndex::new();
+        index.add_entry(entry("a.txt", "a\n"));
+
+        let data = store.serialize_index(&index).unwrap();
+        assert_eq!(&data[..12], b"DIRC\0\0\0\x02\0\0\0\x01");
+        // 62-byte header + 5-byte path, NUL-padded to 72, then the checksum
+        assert_eq!(data.len(), 12 + 72 + 20);
+        assert_eq!(&data[12 + 8..12 + 12], &1_700_000_000u32.to_be_bytes());
+        assert_eq!(&data[12 + 24..12 + 28], &0o100644u32.to_be_bytes());
+        assert_eq!(&data[12 + 60..12 + 62], &5u16.to_be_bytes());
+        assert_eq!(&data[12 + 62..12 + 72], b"a.txt\0\0\0\0\0");
+        assert_eq!(
+            data[84..],
+            ObjectFormat::Sha1.digest(&data[..84])[..],
+            "trailing checksum"
+        );
+
+        let loaded = store.parse_index(&data).unwrap();
+        assert_eq!(loaded, index);
+
+        // A flipped bit anywhere is caught by the checksum
+        let mut corrupt = data.clone();
+        corrupt[40] ^= 1;
+        assert!(store.parse_index(&corrupt).is_err());
+    }
+
+    #[test]
+    fn test_binary_index_versions_and_extensions() {
+        let temp_dir = tempdir().unwrap();
+        let store =
+            BinaryIndexStore::with_format(temp_dir.path().join("index"), ObjectFormat::Sha1);
+
+        let mut index = GitIndex::new();
+        for path in ["a/b", "a.b", "src/deep/dir/one.rs", "src/deep/dir/two.rs"] {
+            index.add_entry(entry(path, path));
+        }
+        for (stage, content) in [(1, "base"), (3, "theirs")] {
+            let mut conflicted = entry("conflict.txt", content);
+            conflicted.stage = stage;
+            conflicted.assume_valid = true;
+            index.add_entry(conflicted);
+        }
+        index.extensions = vec![
+            IndexExtension::new(*b"TREE", vec![1, 2, 3]),
+            IndexExtension::new(*b"REUC", vec![4, 5]),
+        ];
+
+        // Version 2, entries in byte order ("a.b" before "a/b"), stale TREE dropped
+        let v2 = store.serialize_index(&index).unwrap();
+        let loaded = store.parse_index(&v2).unwrap();
+        assert_eq!(loaded.version, 2);
+        assert_eq!(loaded.entries, index.entries);
+        assert_eq!(loaded.conflicts, index.conflicts);
+        assert_eq!(loaded.len(), 6);
+        assert_eq!(
+            loaded.extensions,
+            vec![IndexExtension::new(*b"REUC", vec![4, 5])]
+        );
+        let position = |data: &[u8], path: &[u8]| {
+            data.windows(path.len())
+                .position(|window| window == path)
+                .unwrap()
+        };
+        assert!(position(&v2, b"a.b\0") < position(&v2, b"a/b\0"));
+
+        // Extended flags need version 3
+        index
+            .entries
+            .get_mut(&PathBuf::from("a/b"))
+            .unwrap()
+            .skip_worktree = true;
+        index
+            .entries
+            .get_mut(&PathBuf::from("a.b"))
+            .unwrap()
+            .intent_to_add = true;
+        let v3 = store.serialize_index(&index).unwrap();
+        let loaded = store.parse_index(&v3).unwrap();
+        assert_eq!(loaded.version, 3);
+        assert_eq!(loaded.entries, index.entries);
+
+        // Version 4 shares path prefixes with the previous entry
+        index.version = 4;
+        let v4 = store.serialize_index(&index).unwrap();
+        let loaded = store.parse_index(&v4).unwrap();
+        assert_eq!(loaded.version, 4);
+        assert_eq!(loaded.entries, index.entries);
+        assert!(v4.len() < v3.len());
+        assert!(v4.windows(19).all(|w| w != b"src/deep/dir/two.rs"));
+
+        // Extensions that must be understood are refused
+        index.version = 2;
+        index.extensions = vec![IndexExtension::new(*b"link", vec![0; 4])];
+        let required = store.serialize_index(&index).unwrap();
+        let error = store.parse_index(&required).unwrap_err();
+        assert!(error.to_string().contains("link extension"), "{}", error);
+    }
+
+    #[test]
+    fn test_index_store_format_follows_file_name() {
+        let temp_dir = tempdir().unwrap();
+        let mut index = GitIndex::new();
+        index.add_entry(entry("README.md", "readme"));
+
+        // Git's index file is binary, and loading recognizes either format
+        let git_index = IndexStore::new(temp_dir.path().join("index"));
+        assert_eq!(git_index.format(), IndexFormat::Dirc);
+        git_index.save_index(&index).unwrap();
+        assert!(fs::read(temp_dir.path().join("index"))
+            .unwrap()
+            .starts_with(b"DIRC"));
+        assert_eq!(git_index.load_index().unwrap().entries, index.entries);
+
+        let json = IndexStore::with_format(temp_dir.path().join("index"), IndexFormat::Json);
+        assert_eq!(json.load_index().unwrap().entries, index.entries);
+    }
 }
@@ -0,0 +1,228 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter making temporary file names unique within this process
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Lock File - Exclusive, All-or-Nothing Update of a File
+///
+/// Git never writes the index or a ref in place. It creates `<file>.lock`
+/// next to it (failing if that already exists), writes the new content
+/// there and renames the lock over the file:
+///
+/// ```text
+/// refs/heads/main.lock  ◀── create_new: fails while another writer holds it
+///        │ write new content
+///        ▼
+/// refs/heads/main       ◀── rename: readers see the old or the new content,
+///                           never a half-written file
+/// ```
+///
+/// A lock that is dropped without [`commit`](Self::commit), e.g. because
+/// an error was returned while preparing the content, is removed and the
+/// file is left untouched. A process that is killed while holding a lock
+/// leaves it behind; like Git, later writers then refuse to proceed until
+/// it is removed by hand.
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    lock_path: PathBuf,
+    file: Option<File>,
+}
+
+impl LockFile {
+    /// Take the lock for `path` by creating `<path>.lock`
+    pub fn acquire(path: &Path) -> crate::Result<Self> {
+        let lock_path = Self::lock_path_for(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                return Err(format!(
+                    "Unable to create '{}': File exists.\n\n\
+                     Another git-rs process seems to be running in this repository. \
+                     If it has finished or crashed, remove the file manually to continue.",
+                    lock_path.display()
+                )
+                .into())
+            }
+            Err(e) => {
+                return Err(format!("Unable to create '{}': {}", lock_path.display(), e).into())
+            }
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            lock_path,
+            file: Some(file),
+        })
+    }
+
+    /// Replace the content of `path` under its lock in one step
+    pub fn write(path: &Path, content: &[u8]) -> crate::Result<()> {
+        let mut lock = Self::acquire(path)?;
+        lock.write_all(content)?;
+        lock.commit()
+    }
+
+    /// The lock file for `path`: `index` is locked by `index.lock`
+    pub fn lock_path_for(path: &Path) -> PathBuf {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        path.with_file_name(name)
+    }
+
+    /// The file this lock will replace
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append to the new content
+    pub fn write_all(&mut self, data: &[u8]) -> crate::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => Ok(file.write_all(data)?),
+            None => Err(format!("{} is already committed", self.lock_path.display()).into()),
+        }
+    }
+
+    /// Move the new content into place and release the lock
+    ///
+    /// The content is synced to disk before the rename, so a crash can
+    /// never leave a renamed but truncated file. If anything fails, the
+    /// lock is removed on drop and the file is left untouched.
+    pub fn commit(mut self) -> crate::Result<()> {
+        if let Some(file) = self.file.as_mut() {
+            file.flush()?;
+            file.sync_all()?;
+        }
+        fs::rename(&self.lock_path, &self.path)?;
+
+        // The lock file is now the file itself; nothing is left to remove
+        self.file = None;
+        Ok(())
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        // Still holding the file means the lock was never committed
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
+
+/// Create a uniquely named temporary file in `dir`, e.g. `tmp_obj_1234_0`
+///
+/// Files that end up under a name derived from their content (objects,
+/// packs) are written to one of these first and renamed into place once
+/// complete. Keeping it in the same directory makes the rename atomic.
+pub fn create_temp_file(dir: &Path, prefix: &str) -> crate::Result<(PathBuf, File)> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "{}_{}_{}",
+        prefix,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    Ok((path, file))
+}
+
+/// Rename a finished temporary file to `path` and make it read-only
+///
+/// Objects and packs never change once written; making them read-only
+/// guards against tools (or people) editing them in place.
+pub fn install_read_only(temp_path: &Path, path: &Path) -> crate::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut permissions = fs::metadata(temp_path)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(temp_path, permissions)?;
+
+    if let Err(e) = fs::rename(temp_path, path) {
+        let _ = fs::remove_file(temp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_file_replaces_content_atomically() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("refs/heads/main");
+        let lock_path = temp_dir.path().join("refs/heads/main.lock");
+
+        LockFile::write(&path, b"first\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\n");
+        assert!(!lock_path.exists());
+
+        // A held lock keeps other writers out and the old content visible
+        let mut lock = LockFile::acquire(&path).unwrap();
+        lock.write_all(b"second\n").unwrap();
+        let error = LockFile::write(&path, b"other\n").unwrap_err();
+        assert!(error.to_string().contains("main.lock': File exists"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\n");
+        lock.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+
+        // Dropping an uncommitted lock releases it and changes nothing
+        let mut lock = LockFile::acquire(&path).unwrap();
+        lock.write_all(b"abandoned\n").unwrap();
+        drop(lock);
+        assert!(!lock_path.exists());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+    }
+
+    #[test]
+    fn test_failed_commit_releases_lock() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("index");
+        let lock_path = temp_dir.path().join("index.lock");
+
+        // A directory in the way makes the rename fail
+        fs::create_dir_all(path.join("in-the-way")).unwrap();
+        let mut lock = LockFile::acquire(&path).unwrap();
+        lock.write_all(b"content").unwrap();
+        assert!(lock.commit().is_err());
+        assert!(!lock_path.exists());
+
+        // Later writers are not locked out
+        fs::remove_dir_all(&path).unwrap();
+        LockFile::write(&path, b"content").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"content");
+    }
+
+    #[test]
+    fn test_install_read_only() {
+        let temp_dir = tempdir().unwrap();
+        let (temp_path, mut file) = create_temp_file(temp_dir.path(), "tmp_obj").unwrap();
+        file.write_all(b"content").unwrap();
+        drop(file);
+
+        let path = temp_dir.path().join("ab/cdef");
+        install_read_only(&temp_path, &path).unwrap();
+        assert!(!temp_path.exists());
+        assert_eq!(fs::read(&path).unwrap(), b"content");
+        assert!(fs::metadata(&path).unwrap().permissions().readonly());
+    }
+}
@@ -1,9 +1,25 @@
+pub mod config_store;
 pub mod index_store;
+pub mod lock_file;
+pub mod object_cache;
+pub mod object_database;
 pub mod object_store;
+pub mod pack;
+pub mod pack_writer;
 pub mod ref_store;
 pub mod remote_client;
+pub mod revision;
+pub mod working_tree;
 
+pub use config_store::*;
 pub use index_store::*;
+pub use lock_file::*;
+pub use object_cache::*;
+pub use object_database::*;
 pub use object_store::*;
+pub use pack::*;
+pub use pack_writer::*;
 pub use ref_store::*;
 pub use remote_client::*;
+pub use revision::*;
+pub use working_tree::*;
@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+/// Default for `core.objectCacheLimit`: parsed objects kept by an `ObjectStore`
+pub const DEFAULT_OBJECT_CACHE_LIMIT: usize = 16 << 20;
+
+/// Default for `core.deltaBaseCacheLimit`: inflated delta bases kept per pack
+pub const DEFAULT_DELTA_BASE_CACHE_LIMIT: usize = 96 << 20;
+
+/// How many bytes the in-process object caches may hold
+///
+/// A limit of 0 turns the cache off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// Parsed objects returned by `ObjectStore::load_object`
+    pub objects: usize,
+    /// Fully resolved pack entries that deltas are applied on top of
+    pub delta_bases: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            objects: DEFAULT_OBJECT_CACHE_LIMIT,
+            delta_bases: DEFAULT_DELTA_BASE_CACHE_LIMIT,
+        }
+    }
+}
+
+/// Least-Recently-Used Cache with a Size Budget
+///
+/// Walking history reads the same commits and trees over and over: every
+/// `log` step loads a commit that was already loaded to queue it, and
+/// consecutive commits share most of their trees. Objects never change once
+/// written, so a cached copy can never be stale; the only question is how
+/// much memory to spend. Every entry is charged a cost (its size in bytes)
+/// and the least recently used entries are evicted once the total would
+/// exceed the limit:
+///
+/// ```text
+/// recency:  oldest ─────────────────────▶ newest
+///           [tree a] [commit 1] [tree b] [commit 2]
+///              ▲                              ▲
+///              └── evicted first              └── just read (get) or inserted
+/// ```
+///
+/// An entry costing more than the whole limit is not cached at all.
+#[derive(Clone)]
+pub struct LruCache<K, V> {
+    limit: usize,
+    used: usize,
+    /// Bumped on every access; orders entries by recency
+    clock: u64,
+    entries: HashMap<K, CacheEntry<V>>,
+    /// Last access time of every entry, oldest first
+    recency: BTreeMap<u64, K>,
+}
+
+#[derive(Clone)]
+struct CacheEntry<V> {
+    value: V,
+    cost: usize,
+    last_used: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Create an empty cache holding entries worth at most `limit` bytes
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Look an entry up, marking it as the most recently used
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let entry = self.entries.get_mut(key)?;
+        self.clock += 1;
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(self.clock, key.clone());
+        entry.last_used = self.clock;
+        Some(&entry.value)
+    }
+
+    /// Add an entry, evicting the least recently used ones to make room
+    pub fn insert(&mut self, key: K, value: V, cost: usize) {
+        self.remove(&key);
+        if self.limit == 0 || cost > self.limit {
+            return;
+        }
+
+        while self.used + cost > self.limit {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.used -= evicted.cost;
+            }
+        }
+
+        self.clock += 1;
+        self.recency.insert(self.clock, key.clone());
+        self.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                cost,
+                last_used: self.clock,
+            },
+        );
+        self.used += cost;
+    }
+
+    /// Drop an entry if it is cached
+    pub fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+            self.used -= entry.cost;
+        }
+    }
+
+    /// Drop every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.used = 0;
+    }
+
+    /// Number of cached entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Total cost of the cached entries
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// The most the cached entries may cost together
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl<K, V> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruCache")
+            .field("entries", &self.entries.len())
+            .field("used", &self.used)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used_within_limit() {
+        let mut cache = LruCache::new(10);
+        cache.insert("a", 1, 4);
+        cache.insert("b", 2, 4);
+
+        // Reading "a" makes "b" the oldest, so "b" goes to make room for "c"
+        assert_eq!(cache.get(&"a"), Some(&1));
+        cache.insert("c", 3, 4);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"c"), Some(&3));
+        assert_eq!(cache.used(), 8);
+
+        // Replacing an entry releases its old cost
+        cache.insert("c", 4, 6);
+        assert_eq!((cache.len(), cache.used()), (2, 10));
+
+        // Too large to ever fit: not cached, and nothing is evicted for it
+        cache.insert("huge", 5, 11);
+        assert_eq!(cache.get(&"huge"), None);
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.used(), 0);
+
+        let mut disabled = LruCache::new(0);
+        disabled.insert("a", 1, 0);
+        assert!(disabled.is_empty());
+    }
+}
@@ -0,0 +1,544 @@
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::domain::objects::*;
+use crate::infrastructure::lock_file::{create_temp_file, install_read_only};
+use crate::infrastructure::pack::PackReader;
+
+/// Chunk size used when streaming file contents in and out of a database
+pub(crate) const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Longest loose object header accepted ("commit 18446744073709551615\0")
+const MAX_HEADER_LEN: usize = 32;
+
+/// A place objects are kept: the backend behind an [`ObjectStore`]
+///
+/// Databases only deal in object types and raw content bytes; parsing trees,
+/// commits and tags is left to the store on top. The store consults its
+/// databases in order, so backends can be layered:
+///
+/// ```text
+///                    ObjectStore (parse, peel, serialize)
+///                     │ read: first database that has the id
+///                     │ write: first database
+///       ┌─────────────┼──────────────────┐
+///       ▼             ▼                  ▼
+///  loose files    pack files       in memory (tests, tools)
+///  objects/ab/..  objects/pack/
+/// ```
+///
+/// [`ObjectStore`]: crate::infrastructure::object_store::ObjectStore
+pub trait ObjectDatabase {
+    /// The hash function that names objects in this database
+    fn format(&self) -> ObjectFormat;
+
+    /// Read an object's type and content, or `None` when it is not stored here
+    fn read(&self, hash: &ObjectHash) -> crate::Result<Option<(GitObjectType, Vec<u8>)>>;
+
+    /// Store content of the given type and return its id
+    fn write(&self, object_type: &GitObjectType, content: &[u8]) -> crate::Result<ObjectHash>;
+
+    /// Check if an object is stored here
+    fn exists(&self, hash: &ObjectHash) -> bool;
+
+    /// The ids of all objects stored here
+    fn iter(&self) -> crate::Result<Box<dyn Iterator<Item = ObjectHash> + '_>>;
+
+    /// Open an object for streaming reads, or `None` when it is not stored here
+    ///
+    /// The default reads the whole object into memory; backends that can do
+    /// better (loose files are inflated chunk by chunk) override it.
+    fn open(&self, hash: &ObjectHash) -> crate::Result<Option<ObjectReader>> {
+        Ok(self
+            .read(hash)?
+            .map(|(object_type, content)| ObjectReader::from_content(object_type, content)))
+    }
+
+    /// Store a file's content as a blob
+    ///
+    /// The default reads the file into memory; the loose database streams it.
+    fn write_blob_from_file(&self, path: &Path) -> crate::Result<ObjectHash> {
+        self.write(&GitObjectType::Blob, &fs::read(path)?)
+    }
+
+    /// The pack files behind this database, for commands that work on packs
+    /// directly (`fsck`, `repack`); empty for backends without packs
+    fn packs(&self) -> crate::Result<&[PackReader]> {
+        Ok(&[])
+    }
+}
+
+/// Streaming access to one object's content (header already consumed)
+pub struct ObjectReader {
+    object_type: GitObjectType,
+    size: u64,
+    content: Box<dyn Read>,
+}
+
+impl ObjectReader {
+    /// Wrap a reader positioned at the start of an object's content
+    pub fn new(object_type: GitObjectType, size: u64, content: Box<dyn Read>) -> Self {
+        Self {
+            object_type,
+            size,
+            content,
+        }
+    }
+
+    /// Read from content that is already in memory
+    pub fn from_content(object_type: GitObjectType, content: Vec<u8>) -> Self {
+        Self::new(
+            object_type,
+            content.len() as u64,
+            Box::new(Cursor::new(content)),
+        )
+    }
+
+    pub fn object_type(&self) -> &GitObjectType {
+        &self.object_type
+    }
+
+    /// Content size from the object header
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.content.read(buf)
+    }
+}
+
+/// Objects stored one per file under `objects/`
+///
+/// Each object is zlib-compressed `"{type} {size}\0{content}"`, stored at a
+/// path derived from its id: first 2 hex digits = directory, rest = filename.
+///
+/// ```text
+/// 557db03de997c86a4a028e1ebd3a1ceb225be238 ──▶ objects/55/7db03de997c86a...
+/// ```
+///
+/// Objects are written to a temporary `objects/tmp_obj_*` file and renamed
+/// into place, so an interrupted write never leaves a truncated object
+/// behind, and are made read-only since they never change.
+pub struct LooseObjectDatabase {
+    objects_dir: PathBuf,
+    format: ObjectFormat,
+}
+
+impl LooseObjectDatabase {
+    pub fn new(objects_dir: PathBuf, format: ObjectFormat) -> Self {
+        Self {
+            objects_dir,
+            format,
+        }
+    }
+
+    /// Get the objects directory this database reads from
+    pub fn objects_dir(&self) -> &Path {
+        &self.objects_dir
+    }
+
+    /// Get the file system path for an object
+    pub fn object_path(&self, hash: &ObjectHash) -> PathBuf {
+        self.objects_dir
+            .join(hash.dir_name())
+            .join(hash.file_name())
+    }
+
+    /// Compress serialized object data (header included) into a loose file
+    fn write_serialized(&self, serialized: &[u8]) -> crate::Result<ObjectHash> {
+        let hash = ObjectHash::from_bytes(&self.format.digest(serialized))?;
+        if self.exists(&hash) {
+            return Ok(hash);
+        }
+
+        let (temp_path, temp_file) = create_temp_file(&self.objects_dir, "tmp_obj")?;
+        let written = Self::deflate_into(serialized, temp_file).map(|()| hash);
+        self.install(&temp_path, written)
+    }
+
+    /// Move a finished temporary file into place as the loose object it holds
+    ///
+    /// The temporary file is removed instead when writing it failed or the
+    /// object already exists.
+    fn install(
+        &self,
+        temp_path: &Path,
+        written: crate::Result<ObjectHash>,
+    ) -> crate::Result<ObjectHash> {
+        let hash = match written {
+            Ok(hash) => hash,
+            Err(e) => {
+                let _ = fs::remove_file(temp_path);
+                return Err(e);
+            }
+        };
+        if self.exists(&hash) {
+            fs::remove_file(temp_path)?;
+            return Ok(hash);
+        }
+
+        install_read_only(temp_path, &self.object_path(&hash))?;
+        Ok(hash)
+    }
+
+    /// Write serialized object data, deflated, into `output`
+    fn deflate_into(serialized: &[u8], output: File) -> crate::Result<()> {
+        let mut encoder = ZlibEncoder::new(BufWriter::new(output), Compression::default());
+        encoder.write_all(serialized)?;
+        Self::finish(encoder)
+    }
+
+    /// Hash a file while writing it, deflated, into `output`
+    fn deflate_file_into(
+        &self,
+        path: &Path,
+        size: u64,
+        header: &str,
+        output: File,
+    ) -> crate::Result<ObjectHash> {
+        let mut encoder = ZlibEncoder::new(BufWriter::new(output), Compression::default());
+        encoder.write_all(header.as_bytes())?;
+        let hash = stream_blob_content(self.format, path, size, header, &mut encoder)?;
+        Self::finish(encoder)?;
+        Ok(hash)
+    }
+
+    /// Flush the deflated data and sync it to disk, so a crash can never
+    /// leave a renamed but truncated object behind
+    fn finish(encoder: ZlibEncoder<BufWriter<File>>) -> crate::Result<()> {
+        let file = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Split a decompressed loose object into its type and content
+    fn deserialize(mut data: Vec<u8>) -> crate::Result<(GitObjectType, Vec<u8>)> {
+        // Find the null terminator that separates header from content
+        let null_pos = data
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("Invalid object format: no null terminator")?;
+
+        let header = String::from_utf8(data[0..null_pos].to_vec())?;
+        let content = data.split_off(null_pos + 1);
+
+        // Parse header: "type size"
+        let parts: Vec<&str> = header.split_whitespace().collect();
+        if parts.len() != 2 {
+            return Err("Invalid object header format".into());
+        }
+
+        let object_type: GitObjectType = parts[0].parse()?;
+        let size: usize = parts[1].parse()?;
+
+        if content.len() != size {
+            return Err("Object size mismatch".into());
+        }
+
+        Ok((object_type, content))
+    }
+}
+
+impl ObjectDatabase for LooseObjectDatabase {
+    fn format(&self) -> ObjectFormat {
+        self.format
+    }
+
+    fn read(&self, hash: &ObjectHash) -> crate::Result<Option<(GitObjectType, Vec<u8>)>> {
+        let object_path = self.object_path(hash);
+        if !object_path.exists() {
+            return Ok(None);
+        }
+
+        // Read and decompress the object
+        let compressed = fs::read(&object_path)?;
+        let mut decoder = ZlibDecoder::new(&compressed[..]);
+        let mut decompressed = Vec::new();
+        decoder.read_to_end(&mut decompressed)?;
+
+        Self::deserialize(decompressed).map(Some)
+    }
+
+    fn write(&self, object_type: &GitObjectType, content: &[u8]) -> crate::Result<ObjectHash> {
+        let mut serialized = format!("{} {}\0", object_type, content.len()).into_bytes();
+        serialized.extend_from_slice(content);
+        self.write_serialized(&serialized)
+    }
+
+    fn exists(&self, hash: &ObjectHash) -> bool {
+        self.object_path(hash).exists()
+    }
+
+    fn iter(&self) -> crate::Result<Box<dyn Iterator<Item = ObjectHash> + '_>> {
+        let mut objects = Vec::new();
+
+        if !self.objects_dir.exists() {
+            return Ok(Box::new(objects.into_iter()));
+        }
+
+        for entry in fs::read_dir(&self.objects_dir)? {
+            let entry = entry?;
+            let dir_name = entry.file_name();
+            let dir_name_str = dir_name.to_string_lossy();
+
+            // Must be exactly 2 characters for a valid object directory
+            // (this also skips the info and pack directories)
+            if dir_name_str.len() != 2 {
+                continue;
+            }
+
+            let dir_path = entry.path();
+            if dir_path.is_dir() {
+                for file_entry in fs::read_dir(&dir_path)? {
+                    let file_entry = file_entry?;
+                    let file_name = file_entry.file_name();
+                    let file_name_str = file_name.to_string_lossy();
+
+                    // Combine directory and file name to get full hash; temporary
+                    // and other stray files do not parse as an id and are skipped
+                    let full_hash = format!("{}{}", dir_name_str, file_name_str);
+                    if let Ok(hash) = ObjectHash::from_hex(&full_hash) {
+                        objects.push(hash);
+                    }
+                }
+            }
+        }
+
+        Ok(Box::new(objects.into_iter()))
+    }
+
+    /// Loose objects are inflated on the fly, so only one chunk is held in
+    /// memory at a time
+    fn open(&self, hash: &ObjectHash) -> crate::Result<Option<ObjectReader>> {
+        let object_path = self.object_path(hash);
+        if !object_path.exists() {
+            return Ok(None);
+        }
+
+        let mut decoder = ZlibDecoder::new(BufReader::new(File::open(&object_path)?));
+
+        let mut header = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            decoder.read_exact(&mut byte)?;
+            if byte[0] == 0 {
+                break;
+            }
+            if header.len() == MAX_HEADER_LEN {
+                return Err(format!("Invalid object header in {}", hash).into());
+            }
+            header.push(byte[0]);
+        }
+
+        let header = String::from_utf8(header)?;
+        let (type_name, size) = header
+            .split_once(' ')
+            .ok_or("Invalid object header format")?;
+        let size: u64 = size.parse()?;
+
+        Ok(Some(ObjectReader::new(
+            type_name.parse()?,
+            size,
+            Box::new(decoder.take(size)),
+        )))
+    }
+
+    /// Store a file as a blob without reading it into memory
+    ///
+    /// The file is hashed and deflated in fixed-size chunks into a temporary
+    /// file inside `objects/`, which is renamed into place once the id is
+    /// known. Memory use stays constant no matter how large the file is:
+    ///
+    /// ```text
+    /// file ──chunk──┬──▶ hasher ──────────────▶ object id
+    ///               └──▶ zlib ──▶ objects/tmp_obj_* ──rename──▶ objects/ab/cdef...
+    /// ```
+    fn write_blob_from_file(&self, path: &Path) -> crate::Result<ObjectHash> {
+        let size = fs::metadata(path)?.len();
+        let header = format!("blob {}\0", size);
+
+        let (temp_path, temp_file) = create_temp_file(&self.objects_dir, "tmp_obj")?;
+        let written = self.deflate_file_into(path, size, &header, temp_file);
+        self.install(&temp_path, written)
+    }
+}
+
+/// Objects kept in a map in memory and lost when it is dropped
+///
+/// Useful for tests and tools that build objects without a repository on
+/// disk:
+///
+/// ```text
+/// let store = ObjectStore::with_database(InMemoryObjectDatabase::new(ObjectFormat::Sha1));
+/// store.store_object(&blob)?;   // nothing touches the file system
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemoryObjectDatabase {
+    format: ObjectFormat,
+    objects: RwLock<BTreeMap<ObjectHash, (GitObjectType, Vec<u8>)>>,
+}
+
+impl InMemoryObjectDatabase {
+    pub fn new(format: ObjectFormat) -> Self {
+        Self {
+            format,
+            objects: RwLock::default(),
+        }
+    }
+
+    /// Number of objects stored
+    pub fn len(&self) -> usize {
+        self.objects
+            .read()
+            .map(|objects| objects.len())
+            .unwrap_or(0)
+    }
+
+    /// Check if no objects are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ObjectDatabase for InMemoryObjectDatabase {
+    fn format(&self) -> ObjectFormat {
+        self.format
+    }
+
+    fn read(&self, hash: &ObjectHash) -> crate::Result<Option<(GitObjectType, Vec<u8>)>> {
+        let objects = self
+            .objects
+            .read()
+            .map_err(|_| "in-memory object database lock poisoned")?;
+        Ok(objects.get(hash).cloned())
+    }
+
+    fn write(&self, object_type: &GitObjectType, content: &[u8]) -> crate::Result<ObjectHash> {
+        let hash = self.format.hash_object(&object_type.to_string(), content);
+        let mut objects = self
+            .objects
+            .write()
+            .map_err(|_| "in-memory object database lock poisoned")?;
+        objects
+            .entry(hash)
+            .or_insert_with(|| (object_type.clone(), content.to_vec()));
+        Ok(hash)
+    }
+
+    fn exists(&self, hash: &ObjectHash) -> bool {
+        self.objects
+            .read()
+            .map(|objects| objects.contains_key(hash))
+            .unwrap_or(false)
+    }
+
+    fn iter(&self) -> crate::Result<Box<dyn Iterator<Item = ObjectHash> + '_>> {
+        let objects = self
+            .objects
+            .read()
+            .map_err(|_| "in-memory object database lock poisoned")?;
+        let hashes: Vec<ObjectHash> = objects.keys().copied().collect();
+        Ok(Box::new(hashes.into_iter()))
+    }
+}
+
+/// Copy a file's content through the object hasher into `sink`
+pub(crate) fn stream_blob_content(
+    format: ObjectFormat,
+    path: &Path,
+    size: u64,
+    header: &str,
+    sink: &mut impl Write,
+) -> crate::Result<ObjectHash> {
+    let mut hasher = format.hasher();
+    hasher.update(header.as_bytes());
+
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE];
+    let mut total = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        sink.write_all(&buffer[..read])?;
+        total += read as u64;
+    }
+
+    // The header was written with the size seen up front
+    if total != size {
+        return Err(format!("{} changed while it was being read", path.display()).into());
+    }
+
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// The same operations behave the same on every backend
+    fn exercise(database: &dyn ObjectDatabase) {
+        let hash = database
+            .write(&GitObjectType::Blob, b"Hello World\n")
+            .unwrap();
+        assert_eq!(hash.to_hex(), "557db03de997c86a4a028e1ebd3a1ceb225be238");
+        assert!(database.exists(&hash));
+        assert_eq!(
+            database.read(&hash).unwrap(),
+            Some((GitObjectType::Blob, b"Hello World\n".to_vec()))
+        );
+
+        // Writing the same content again is a no-op
+        assert_eq!(
+            database
+                .write(&GitObjectType::Blob, b"Hello World\n")
+                .unwrap(),
+            hash
+        );
+        let tree = database.write(&GitObjectType::Tree, b"").unwrap();
+        let mut listed: Vec<ObjectHash> = database.iter().unwrap().collect();
+        listed.sort();
+        let mut expected = vec![hash, tree];
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        let mut reader = database.open(&tree).unwrap().unwrap();
+        assert_eq!(reader.object_type(), &GitObjectType::Tree);
+        assert_eq!(reader.size(), 0);
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert!(content.is_empty());
+
+        let missing = ObjectHash::null(ObjectFormat::Sha1);
+        assert!(!database.exists(&missing));
+        assert!(database.read(&missing).unwrap().is_none());
+        assert!(database.open(&missing).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_loose_and_in_memory_databases_agree() {
+        let temp_dir = tempdir().unwrap();
+        let loose = LooseObjectDatabase::new(temp_dir.path().join("objects"), ObjectFormat::Sha1);
+        exercise(&loose);
+        assert!(temp_dir
+            .path()
+            .join("objects/55/7db03de997c86a4a028e1ebd3a1ceb225be238")
+            .is_file());
+
+        let memory = InMemoryObjectDatabase::new(ObjectFormat::Sha1);
+        exercise(&memory);
+        assert_eq!(memory.len(), 2);
+    }
+}
@@ -1,28 +1,151 @@
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
-use sha1::{Digest, Sha1};
-use std::fs;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 use crate::domain::objects::*;
+use crate::domain::repository::GitRepository;
+use crate::infrastructure::config_store::ConfigStore;
+use crate::infrastructure::object_cache::{CacheLimits, LruCache};
+use crate::infrastructure::object_database::{
+    stream_blob_content, InMemoryObjectDatabase, LooseObjectDatabase, ObjectDatabase, ObjectReader,
+    STREAM_CHUNK_SIZE,
+};
+use crate::infrastructure::pack::{PackObjectDatabase, PackReader};
+
+/// Maximum number of nested annotated tags followed when peeling
+const MAX_TAG_CHAIN: usize = 100;
+
+/// Deepest chain of alternates followed (an alternate's own alternates, ...),
+/// the same limit Git uses
+const MAX_ALTERNATE_DEPTH: usize = 5;
 
 /// Git Object Storage Implementation
 ///
-/// This handles the physical storage and retrieval of Git objects in the filesystem.
-/// Git stores objects in .git/objects/ directory using the following format:
-/// - Objects are compressed using zlib
-/// - Object hash determines the storage path: first 2 chars = directory, rest = filename
-/// - Object content format: "{type} {size}\0{content}"
+/// This is what commands use to read and write Git objects: it turns
+/// [`GitObject`]s into bytes and back, and follows tags and trees. Where the
+/// bytes live is up to the [`ObjectDatabase`]s behind it, consulted in order:
+///
+/// - A repository on disk (`ObjectStore::new`) writes loose objects under
+///   `objects/` and falls back to pack files under `objects/pack/` (written
+///   by `git gc` or received from a remote) when no loose file exists
+/// - `ObjectStore::in_memory` keeps everything in memory, for tests and tools
+///
+/// A repository on disk can also borrow objects from other repositories
+/// listed in `objects/info/alternates`, one objects directory per line.
+/// Their loose objects and packs are searched after the repository's own,
+/// but never written to:
+///
+/// ```text
+/// clone/.git-rs/objects/info/alternates:
+///     /srv/mirror/.git-rs/objects      ──▶ read-only, may list more alternates
+/// ```
+///
+/// Object ids are SHA-1 unless the repository config (next to `objects/`)
+/// sets `extensions.objectFormat = sha256`.
+///
+/// Parsed objects are kept in an in-process LRU cache, so walking history
+/// does not inflate and parse the same commits and trees again. Its size is
+/// `core.objectCacheLimit` (16 MiB by default); packs additionally cache
+/// resolved delta bases up to `core.deltaBaseCacheLimit` (96 MiB).
 pub struct ObjectStore {
     objects_dir: PathBuf,
+    format: ObjectFormat,
+    /// Backends in lookup order; new objects are written to the first
+    databases: Vec<Box<dyn ObjectDatabase>>,
+    /// How many of `databases` belong to this store; the rest are alternates
+    local_databases: usize,
+    /// Objects directories borrowed from, in lookup order
+    alternates: Vec<PathBuf>,
+    cache_limits: CacheLimits,
+    /// Recently loaded objects; ids name immutable content, so never stale
+    cache: RefCell<LruCache<ObjectHash, GitObject>>,
 }
 
 impl ObjectStore {
-    /// Create a new object store
+    /// Create a new object store, using the object format and cache limits
+    /// from the repository config
     pub fn new(objects_dir: PathBuf) -> Self {
-        Self { objects_dir }
+        let config = objects_dir
+            .parent()
+            .map(|git_dir| ConfigStore::new(git_dir.join("config")));
+        let format = config
+            .as_ref()
+            .and_then(|config| config.object_format().ok())
+            .unwrap_or_default();
+        let cache_limits = config
+            .as_ref()
+            .and_then(|config| config.cache_limits().ok())
+            .unwrap_or_default();
+        Self::with_cache_limits(objects_dir, format, cache_limits)
+    }
+
+    /// Open the object store of a repository
+    pub fn for_repository(repo: &GitRepository) -> Self {
+        Self::new(repo.objects_dir())
+    }
+
+    /// Create a new object store with an explicit object format
+    pub fn with_format(objects_dir: PathBuf, format: ObjectFormat) -> Self {
+        Self::with_cache_limits(objects_dir, format, CacheLimits::default())
+    }
+
+    /// Create a new object store with an explicit object format and cache sizes
+    pub fn with_cache_limits(
+        objects_dir: PathBuf,
+        format: ObjectFormat,
+        cache_limits: CacheLimits,
+    ) -> Self {
+        let mut databases = Self::directory_databases(&objects_dir, format, cache_limits);
+        let local_databases = databases.len();
+
+        let alternates = Self::find_alternates(&objects_dir);
+        for alternate in &alternates {
+            databases.extend(Self::directory_databases(alternate, format, cache_limits));
+        }
+
+        Self {
+            objects_dir,
+            format,
+            databases,
+            local_databases,
+            alternates,
+            cache_limits,
+            cache: RefCell::new(LruCache::new(cache_limits.objects)),
+        }
+    }
+
+    /// Create an object store backed by a single database
+    ///
+    /// The store has no objects directory; file system helpers such as
+    /// `list_loose_objects` see nothing.
+    pub fn with_database(database: impl ObjectDatabase + 'static) -> Self {
+        let cache_limits = CacheLimits::default();
+        Self {
+            objects_dir: PathBuf::new(),
+            format: database.format(),
+            databases: vec![Box::new(database)],
+            local_databases: 1,
+            alternates: Vec::new(),
+            cache_limits,
+            cache: RefCell::new(LruCache::new(cache_limits.objects)),
+        }
+    }
+
+    /// Create an object store that keeps every object in memory
+    pub fn in_memory(format: ObjectFormat) -> Self {
+        Self::with_database(InMemoryObjectDatabase::new(format))
+    }
+
+    /// The hash function used to name objects
+    pub fn format(&self) -> ObjectFormat {
+        self.format
+    }
+
+    /// How much the in-process object caches may hold
+    pub fn cache_limits(&self) -> CacheLimits {
+        self.cache_limits
     }
 
     /// Initialize the objects directory structure
@@ -35,80 +158,208 @@ impl ObjectStore {
 
     /// Store a Git object and return its hash
     pub fn store_object(&self, object: &GitObject) -> crate::Result<ObjectHash> {
-        let serialized = self.serialize_object(object)?;
-        let hash = self.calculate_hash(&serialized);
-        let object_path = self.get_object_path(&hash);
+        let (object_type, content) = self.serialize_object(object)?;
+        self.store_raw_object(&object_type, &content)
+    }
+
+    /// Store already-serialized content of the given type as-is
+    ///
+    /// Unlike `store_object`, the bytes are not parsed and re-serialized, so
+    /// they are stored exactly as given.
+    pub fn store_raw_object(
+        &self,
+        object_type: &GitObjectType,
+        content: &[u8],
+    ) -> crate::Result<ObjectHash> {
+        // Objects an alternate already has are not copied into this store
+        if !self.alternates.is_empty() {
+            let hash = self.format.hash_object(&object_type.to_string(), content);
+            if self.object_exists(&hash) {
+                return Ok(hash);
+            }
+        }
+        self.primary()?.write(object_type, content)
+    }
+
+    /// Calculate the id an object would be stored under, without storing it
+    pub fn hash_object(&self, object: &GitObject) -> crate::Result<ObjectHash> {
+        let (object_type, content) = self.serialize_object(object)?;
+        Ok(self.format.hash_object(&object_type.to_string(), &content))
+    }
 
-        // Create directory if it doesn't exist
-        if let Some(parent) = object_path.parent() {
-            fs::create_dir_all(parent)?;
+    /// Store the contents of a file as a blob
+    ///
+    /// With loose objects the file is streamed, so memory use stays constant
+    /// no matter how large it is.
+    pub fn store_blob_from_file(&self, path: &Path) -> crate::Result<ObjectHash> {
+        self.primary()?.write_blob_from_file(path)
+    }
+
+    /// Calculate the blob id a file would get, without storing it
+    pub fn hash_file(&self, path: &Path) -> crate::Result<ObjectHash> {
+        let size = fs::metadata(path)?.len();
+        let header = format!("blob {}\0", size);
+        stream_blob_content(self.format, path, size, &header, &mut std::io::sink())
+    }
+
+    /// Open an object for streaming reads of its content
+    ///
+    /// Loose objects are inflated on the fly, so only one chunk is held in
+    /// memory at a time. Packed objects may be stored as deltas, which have
+    /// to be resolved in full, so they are read into memory first.
+    pub fn open_object(&self, hash: &ObjectHash) -> crate::Result<ObjectReader> {
+        for database in &self.databases {
+            if let Some(reader) = database.open(hash)? {
+                return Ok(reader);
+            }
         }
 
-        // Compress and write the object
-        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(&serialized)?;
-        let compressed = encoder.finish()?;
+        Err(format!("Object {} not found", hash).into())
+    }
+
+    /// Write a blob's content to `path` without holding it in memory
+    pub fn write_blob_to_file(&self, hash: &ObjectHash, path: &Path) -> crate::Result<u64> {
+        let mut reader = self.open_object(hash)?;
+        if reader.object_type() != &GitObjectType::Blob {
+            return Err(format!("Object {} is not a blob", hash).into());
+        }
 
-        fs::write(&object_path, compressed)?;
+        let mut file = BufWriter::with_capacity(STREAM_CHUNK_SIZE, File::create(path)?);
+        let copied = std::io::copy(&mut reader, &mut file)?;
+        file.flush()?;
 
-        Ok(hash)
+        if copied != reader.size() {
+            return Err(format!("Object {} is truncated", hash).into());
+        }
+        Ok(copied)
     }
 
     /// Retrieve a Git object by its hash
+    ///
+    /// Served from the object cache when the object was loaded recently.
     pub fn load_object(&self, hash: &ObjectHash) -> crate::Result<GitObject> {
-        let object_path = self.get_object_path(hash);
+        if let Some(object) = self.cache.borrow_mut().get(hash) {
+            return Ok(object.clone());
+        }
+
+        let (object_type, content) = self.load_raw_object(hash)?;
+        let object = self.parse_object_content(&object_type, &content)?;
+        self.cache
+            .borrow_mut()
+            .insert(*hash, object.clone(), content.len());
+        Ok(object)
+    }
 
-        if !object_path.exists() {
-            return Err(format!("Object {} not found", hash).into());
+    /// Retrieve an object's type and content bytes without parsing the content
+    pub fn load_raw_object(&self, hash: &ObjectHash) -> crate::Result<(GitObjectType, Vec<u8>)> {
+        for database in &self.databases {
+            if let Some(object) = database.read(hash)? {
+                return Ok(object);
+            }
         }
 
-        // Read and decompress the object
-        let compressed = fs::read(&object_path)?;
-        let mut decoder = ZlibDecoder::new(&compressed[..]);
-        let mut decompressed = Vec::new();
-        decoder.read_to_end(&mut decompressed)?;
+        Err(format!("Object {} not found", hash).into())
+    }
 
-        self.deserialize_object(&decompressed)
+    /// Get the objects directory this store reads from
+    pub fn objects_dir(&self) -> &Path {
+        &self.objects_dir
     }
 
     /// Check if an object exists
     pub fn object_exists(&self, hash: &ObjectHash) -> bool {
-        self.get_object_path(hash).exists()
+        self.databases.iter().any(|database| database.exists(hash))
     }
 
-    /// List all object hashes
-    pub fn list_objects(&self) -> crate::Result<Vec<ObjectHash>> {
-        let mut objects = Vec::new();
+    /// Get the pack files in this object database
+    ///
+    /// Packs of alternates are not included: they belong to another
+    /// repository, which `repack` must never delete.
+    pub fn packs(&self) -> crate::Result<Vec<&PackReader>> {
+        let mut packs = Vec::new();
+        for database in &self.databases[..self.local_databases] {
+            packs.extend(database.packs()?);
+        }
+        Ok(packs)
+    }
+
+    /// The objects directories this store borrows from, nested ones included
+    pub fn alternates(&self) -> &[PathBuf] {
+        &self.alternates
+    }
 
-        if !self.objects_dir.exists() {
-            return Ok(objects);
+    /// Borrow objects from another objects directory from now on
+    ///
+    /// The directory is appended to `objects/info/alternates`, so stores
+    /// opened later see it too.
+    pub fn add_alternate(&mut self, objects_dir: &Path) -> crate::Result<()> {
+        if !objects_dir.is_dir() {
+            return Err(format!(
+                "alternate object directory {} does not exist",
+                objects_dir.display()
+            )
+            .into());
+        }
+        let objects_dir = objects_dir.canonicalize()?;
+
+        let info_dir = self.objects_dir.join("info");
+        fs::create_dir_all(&info_dir)?;
+        let alternates_path = info_dir.join("alternates");
+        let mut content = fs::read_to_string(&alternates_path).unwrap_or_default();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
         }
+        content.push_str(&format!("{}\n", objects_dir.display()));
+        fs::write(&alternates_path, content)?;
 
-        for entry in fs::read_dir(&self.objects_dir)? {
-            let entry = entry?;
-            let dir_name = entry.file_name();
-            let dir_name_str = dir_name.to_string_lossy();
+        *self = Self::with_cache_limits(self.objects_dir.clone(), self.format, self.cache_limits);
+        Ok(())
+    }
 
-            // Skip info and pack directories
-            if dir_name_str == "info" || dir_name_str == "pack" {
-                continue;
-            }
+    /// Parse an `objects/info/alternates` file
+    ///
+    /// One objects directory per line; relative paths are relative to the
+    /// objects directory holding the file, and blank lines and `#` comments
+    /// are ignored. A missing file means no alternates.
+    pub fn read_alternates(objects_dir: &Path) -> crate::Result<Vec<PathBuf>> {
+        let path = objects_dir.join("info").join("alternates");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
 
-            // Must be exactly 2 characters for a valid object directory
-            if dir_name_str.len() != 2 {
-                continue;
+        Ok(fs::read_to_string(&path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| objects_dir.join(line))
+            .collect())
+    }
+
+    /// List all object hashes, alternates included
+    pub fn list_objects(&self) -> crate::Result<Vec<ObjectHash>> {
+        // An object stored in several databases is listed once
+        let mut seen = HashSet::new();
+        let mut objects = Vec::new();
+        for database in &self.databases {
+            for hash in database.iter()? {
+                if seen.insert(hash) {
+                    objects.push(hash);
+                }
             }
+        }
 
-            let dir_path = entry.path();
-            if dir_path.is_dir() {
-                for file_entry in fs::read_dir(&dir_path)? {
-                    let file_entry = file_entry?;
-                    let file_name = file_entry.file_name();
-                    let file_name_str = file_name.to_string_lossy();
+        Ok(objects)
+    }
 
-                    // Combine directory and file name to get full hash
-                    let full_hash = format!("{}{}", dir_name_str, file_name_str);
-                    objects.push(ObjectHash::new(full_hash));
+    /// List the objects stored in this repository itself, leaving out those
+    /// only found in alternates
+    pub fn list_local_objects(&self) -> crate::Result<Vec<ObjectHash>> {
+        let mut seen = HashSet::new();
+        let mut objects = Vec::new();
+        for database in &self.databases[..self.local_databases] {
+            for hash in database.iter()? {
+                if seen.insert(hash) {
+                    objects.push(hash);
                 }
             }
         }
@@ -116,66 +367,167 @@ impl ObjectStore {
         Ok(objects)
     }
 
+    /// List the hashes of objects stored as loose files
+    pub fn list_loose_objects(&self) -> crate::Result<Vec<ObjectHash>> {
+        Ok(
+            LooseObjectDatabase::new(self.objects_dir.clone(), self.format)
+                .iter()?
+                .collect(),
+        )
+    }
+
     /// Get the file system path for an object
-    fn get_object_path(&self, hash: &ObjectHash) -> PathBuf {
-        self.objects_dir
-            .join(hash.dir_name())
-            .join(hash.file_name())
-    }
-
-    /// Calculate SHA-1 hash of object content
-    fn calculate_hash(&self, content: &[u8]) -> ObjectHash {
-        let mut hasher = Sha1::new();
-        hasher.update(content);
-        let result = hasher.finalize();
-        ObjectHash::new(hex::encode(result))
-    }
-
-    /// Serialize a Git object to bytes
-    fn serialize_object(&self, object: &GitObject) -> crate::Result<Vec<u8>> {
-        let (object_type, content) = match object {
-            GitObject::Blob(blob) => ("blob", blob.content.clone()),
-            GitObject::Tree(tree) => ("tree", self.serialize_tree(tree)?),
-            GitObject::Commit(commit) => ("commit", self.serialize_commit(commit)?),
-        };
+    pub fn get_object_path(&self, hash: &ObjectHash) -> PathBuf {
+        LooseObjectDatabase::new(self.objects_dir.clone(), self.format).object_path(hash)
+    }
 
-        let header = format!("{} {}\0", object_type, content.len());
-        let mut result = header.into_bytes();
-        result.extend_from_slice(&content);
+    /// Loose objects and packs of one objects directory
+    fn directory_databases(
+        objects_dir: &Path,
+        format: ObjectFormat,
+        cache_limits: CacheLimits,
+    ) -> Vec<Box<dyn ObjectDatabase>> {
+        vec![
+            Box::new(LooseObjectDatabase::new(objects_dir.to_path_buf(), format)),
+            Box::new(
+                PackObjectDatabase::new(objects_dir.join("pack"), format)
+                    .with_delta_base_cache_limit(cache_limits.delta_bases),
+            ),
+        ]
+    }
 
-        Ok(result)
+    /// Every alternate reachable from `objects_dir`, depth first
+    ///
+    /// Alternates may list alternates of their own. Each directory is used
+    /// once (so `a -> b -> a` does not loop), chains deeper than
+    /// `MAX_ALTERNATE_DEPTH` are cut off, and entries that cannot be read
+    /// are skipped, like Git does with a warning.
+    fn find_alternates(objects_dir: &Path) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        let mut visited: HashSet<PathBuf> = objects_dir.canonicalize().into_iter().collect();
+        Self::collect_alternates(objects_dir, 0, &mut visited, &mut found);
+        found
+    }
+
+    fn collect_alternates(
+        objects_dir: &Path,
+        depth: usize,
+        visited: &mut HashSet<PathBuf>,
+        found: &mut Vec<PathBuf>,
+    ) {
+        if depth >= MAX_ALTERNATE_DEPTH {
+            return;
+        }
+
+        for alternate in Self::read_alternates(objects_dir).unwrap_or_default() {
+            let Ok(alternate) = alternate.canonicalize() else {
+                continue;
+            };
+            if !alternate.is_dir() || !visited.insert(alternate.clone()) {
+                continue;
+            }
+            found.push(alternate.clone());
+            Self::collect_alternates(&alternate, depth + 1, visited, found);
+        }
     }
 
-    /// Deserialize bytes to a Git object
-    fn deserialize_object(&self, data: &[u8]) -> crate::Result<GitObject> {
-        // Find the null terminator that separates header from content
-        let null_pos = data
-            .iter()
-            .position(|&b| b == 0)
-            .ok_or("Invalid object format: no null terminator")?;
+    /// The database new objects are written to
+    fn primary(&self) -> crate::Result<&dyn ObjectDatabase> {
+        self.databases
+            .first()
+            .map(|database| database.as_ref())
+            .ok_or_else(|| "object store has no database to write to".into())
+    }
 
-        let header = String::from_utf8(data[0..null_pos].to_vec())?;
-        let content = &data[null_pos + 1..];
+    /// Serialize a Git object to its type and content bytes
+    fn serialize_object(&self, object: &GitObject) -> crate::Result<(GitObjectType, Vec<u8>)> {
+        Ok(match object {
+            GitObject::Blob(blob) => (GitObjectType::Blob, blob.content.clone()),
+            GitObject::Tree(tree) => (GitObjectType::Tree, self.serialize_tree(tree)?),
+            GitObject::Commit(commit) => (GitObjectType::Commit, self.serialize_commit(commit)?),
+            GitObject::Tag(tag) => (GitObjectType::Tag, self.serialize_tag(tag)),
+        })
+    }
 
-        // Parse header: "type size"
-        let parts: Vec<&str> = header.split_whitespace().collect();
-        if parts.len() != 2 {
-            return Err("Invalid object header format".into());
+    /// Parse object content (without header) of a given type
+    pub fn parse_object_content(
+        &self,
+        object_type: &GitObjectType,
+        content: &[u8],
+    ) -> crate::Result<GitObject> {
+        match object_type {
+            GitObjectType::Blob => Ok(GitObject::Blob(BlobObject::new(content.to_vec()))),
+            GitObjectType::Tree => Ok(GitObject::Tree(self.deserialize_tree(content)?)),
+            GitObjectType::Commit => Ok(GitObject::Commit(self.deserialize_commit(content)?)),
+            GitObjectType::Tag => Ok(GitObject::Tag(self.deserialize_tag(content)?)),
         }
+    }
 
-        let object_type = parts[0];
-        let size: usize = parts[1].parse()?;
+    /// Follow annotated tags until reaching a non-tag object
+    ///
+    /// Tags can point at other tags, so this loops (with a bound in case of a
+    /// corrupt, cyclic chain) and returns the hash of the first non-tag object.
+    pub fn peel(&self, hash: &ObjectHash) -> crate::Result<(ObjectHash, GitObject)> {
+        let mut current = *hash;
+
+        for _ in 0..MAX_TAG_CHAIN {
+            match self.load_object(&current)? {
+                GitObject::Tag(tag) => current = tag.object,
+                object => return Ok((current, object)),
+            }
+        }
 
-        if content.len() != size {
-            return Err("Object size mismatch".into());
+        Err(format!("Tag chain starting at {} is too long", hash).into())
+    }
+
+    /// Peel `hash` (which may be an annotated tag) to the commit it refers to
+    pub fn peel_to_commit(&self, hash: &ObjectHash) -> crate::Result<ObjectHash> {
+        match self.peel(hash)? {
+            (commit_hash, GitObject::Commit(_)) => Ok(commit_hash),
+            (other, object) => {
+                Err(format!("{} is a {}, not a commit", other, object.object_type()).into())
+            }
         }
+    }
 
-        match object_type {
-            "blob" => Ok(GitObject::Blob(BlobObject::new(content.to_vec()))),
-            "tree" => Ok(GitObject::Tree(self.deserialize_tree(content)?)),
-            "commit" => Ok(GitObject::Commit(self.deserialize_commit(content)?)),
-            _ => Err(format!("Unknown object type: {}", object_type).into()),
+    /// List every non-tree entry reachable from a tree, keyed by its full path
+    ///
+    /// Subtrees (mode 040000) are walked recursively, so `src/main.rs` comes
+    /// back as one path even though it is stored as `src` -> `main.rs`.
+    pub fn flatten_tree(
+        &self,
+        tree_hash: &ObjectHash,
+    ) -> crate::Result<BTreeMap<PathBuf, TreeEntry>> {
+        let mut files = BTreeMap::new();
+        self.flatten_tree_into(tree_hash, Path::new(""), &mut files)?;
+        Ok(files)
+    }
+
+    fn flatten_tree_into(
+        &self,
+        tree_hash: &ObjectHash,
+        prefix: &Path,
+        files: &mut BTreeMap<PathBuf, TreeEntry>,
+    ) -> crate::Result<()> {
+        let tree = match self.load_object(tree_hash)? {
+            GitObject::Tree(tree) => tree,
+            other => {
+                return Err(
+                    format!("{} is a {}, not a tree", tree_hash, other.object_type()).into(),
+                )
+            }
+        };
+
+        for entry in tree.entries {
+            let path = prefix.join(&entry.name);
+            if entry.mode == FileMode::Directory {
+                self.flatten_tree_into(&entry.hash, &path, files)?;
+            } else {
+                files.insert(path, entry);
+            }
         }
+
+        Ok(())
     }
 
     /// Serialize a tree object
@@ -183,16 +535,21 @@ impl ObjectStore {
         let mut result = Vec::new();
 
         for entry in &tree.entries {
-            // Format: "{mode} {name}\0{20-byte-hash}"
-            let mode_str = format!("{:o}", entry.mode.as_u32());
-            result.extend_from_slice(mode_str.as_bytes());
+            // Format: "{mode} {name}\0{binary hash}" (20 bytes for SHA-1, 32 for SHA-256)
+            result.extend_from_slice(entry.mode.as_tree_mode().as_bytes());
             result.push(b' ');
             result.extend_from_slice(entry.name.as_bytes());
             result.push(0); // null terminator
 
-            // Convert hex hash to binary
-            let hash_bytes = hex::decode(&entry.hash.0)?;
-            result.extend_from_slice(&hash_bytes);
+            let hash_bytes = entry.hash.as_bytes();
+            if hash_bytes.len() != self.format.raw_len() {
+                return Err(format!(
+                    "Tree entry '{}' has a hash that is not {}",
+                    entry.name, self.format
+                )
+                .into());
+            }
+            result.extend_from_slice(hash_bytes);
         }
 
         Ok(result)
@@ -226,16 +583,17 @@ impl ObjectStore {
             let name = String::from_utf8(data[pos..pos + null_pos].to_vec())?;
             pos += null_pos + 1; // Skip past null
 
-            // Read 20-byte hash
-            if pos + 20 > data.len() {
+            // Read the binary hash
+            let hash_len = self.format.raw_len();
+            if pos + hash_len > data.len() {
                 return Err("Invalid tree format: truncated hash".into());
             }
 
-            let hash_bytes = &data[pos..pos + 20];
-            let hash = ObjectHash::new(hex::encode(hash_bytes));
-            pos += 20;
+            let hash = ObjectHash::from_bytes(&data[pos..pos + hash_len])?;
+            pos += hash_len;
 
-            tree.add_entry(TreeEntry::new(mode, name, hash));
+            // Keep the stored order so the tree re-serializes byte for byte
+            tree.entries.push(TreeEntry::new(mode, name, hash));
         }
 
         Ok(tree)
@@ -253,40 +611,86 @@ impl ObjectStore {
 
         result.push_str(&format!("author {}\n", commit.author));
         result.push_str(&format!("committer {}\n", commit.committer));
+        for (name, value) in &commit.extra_headers {
+            // Every line after the first of a multi-line value starts with a space
+            result.push_str(&format!("{} {}\n", name, value.replace('\n', "\n ")));
+        }
         result.push('\n');
         result.push_str(&commit.message);
 
+        if commit.has_legacy_encoding() {
+            return result
+                .chars()
+                .map(|c| u8::try_from(c as u32).map_err(|_| c))
+                .collect::<Result<Vec<u8>, char>>()
+                .map_err(|c| {
+                    format!(
+                        "Character '{}' cannot be stored in a {} commit",
+                        c,
+                        commit.encoding().unwrap_or_default()
+                    )
+                    .into()
+                });
+        }
+
         Ok(result.into_bytes())
     }
 
     /// Deserialize a commit object
+    ///
+    /// Headers Git adds after `committer` (`encoding`, `mergetag`, `gpgsig`,
+    /// ...) are kept in order, and the message is kept verbatim, so that
+    /// `serialize_commit` reproduces the original bytes.
+    ///
+    /// Commits with an `encoding` header other than UTF-8 may contain bytes
+    /// that are not valid UTF-8. Their bytes are mapped one-to-one onto the
+    /// characters U+0000..=U+00FF (exactly right for ISO-8859-1), which
+    /// `serialize_commit` reverses.
     fn deserialize_commit(&self, data: &[u8]) -> crate::Result<CommitObject> {
-        let content = String::from_utf8(data.to_vec())?;
-        let lines: Vec<&str> = content.lines().collect();
+        let header_end = data
+            .windows(2)
+            .position(|pair| pair == b"\n\n")
+            .unwrap_or(data.len());
+        let legacy_encoding = data[..header_end]
+            .split(|&b| b == b'\n')
+            .filter_map(|line| line.strip_prefix(b"encoding "))
+            .any(|encoding| {
+                !encoding.eq_ignore_ascii_case(b"utf-8") && !encoding.eq_ignore_ascii_case(b"utf8")
+            });
+
+        let content = if legacy_encoding {
+            data.iter().map(|&b| b as char).collect()
+        } else {
+            String::from_utf8(data.to_vec())?
+        };
+        let (headers, message) = match content.split_once("\n\n") {
+            Some((headers, message)) => (headers, message.to_string()),
+            None => (content.trim_end_matches('\n'), String::new()),
+        };
 
         let mut tree: Option<ObjectHash> = None;
         let mut parents = Vec::new();
         let mut author: Option<Signature> = None;
         let mut committer: Option<Signature> = None;
-        let mut message_start = 0;
-
-        for (i, line) in lines.iter().enumerate() {
-            if line.is_empty() {
-                message_start = i + 1;
-                break;
-            }
-
-            let parts: Vec<&str> = line.splitn(2, ' ').collect();
-            if parts.len() != 2 {
+        let mut extra_headers: Vec<(String, String)> = Vec::new();
+
+        for line in headers.split('\n') {
+            if let Some(continuation) = line.strip_prefix(' ') {
+                let (_, value) = extra_headers
+                    .last_mut()
+                    .ok_or("Invalid commit format: continuation line without a header")?;
+                value.push('\n');
+                value.push_str(continuation);
                 continue;
             }
 
-            match parts[0] {
-                "tree" => tree = Some(ObjectHash::new(parts[1].to_string())),
-                "parent" => parents.push(ObjectHash::new(parts[1].to_string())),
-                "author" => author = Some(self.parse_signature(parts[1])?),
-                "committer" => committer = Some(self.parse_signature(parts[1])?),
-                _ => {} // Ignore unknown fields
+            let (name, value) = line.split_once(' ').unwrap_or((line, ""));
+            match name {
+                "tree" => tree = Some(ObjectHash::from_hex(value)?),
+                "parent" => parents.push(ObjectHash::from_hex(value)?),
+                "author" => author = Some(self.parse_signature(value)?),
+                "committer" => committer = Some(self.parse_signature(value)?),
+                _ => extra_headers.push((name.to_string(), value.to_string())),
             }
         }
 
@@ -294,24 +698,95 @@ impl ObjectStore {
         let author = author.ok_or("Missing author in commit")?;
         let committer = committer.ok_or("Missing committer in commit")?;
 
-        let message = if message_start < lines.len() {
-            lines[message_start..].join("\n")
-        } else {
-            String::new()
-        };
-
         Ok(CommitObject {
             tree,
             parents,
             author,
             committer,
+            extra_headers,
             message,
         })
     }
 
-    /// Parse a signature from "name \<email\> timestamp" format
+    /// Serialize a tag object
+    fn serialize_tag(&self, tag: &TagObject) -> Vec<u8> {
+        let mut result = String::new();
+
+        result.push_str(&format!("object {}\n", tag.object));
+        result.push_str(&format!("type {}\n", tag.object_type));
+        result.push_str(&format!("tag {}\n", tag.tag));
+        if let Some(tagger) = &tag.tagger {
+            result.push_str(&format!("tagger {}\n", tagger));
+        }
+        for (name, value) in &tag.extra_headers {
+            result.push_str(&format!("{} {}\n", name, value.replace('\n', "\n ")));
+        }
+        result.push('\n');
+        result.push_str(&tag.message);
+
+        result.into_bytes()
+    }
+
+    /// Deserialize a tag object
+    ///
+    /// The message is everything after the first blank line, kept verbatim.
+    /// Unknown headers are kept in order, as for commits.
+    fn deserialize_tag(&self, data: &[u8]) -> crate::Result<TagObject> {
+        let content = String::from_utf8(data.to_vec())?;
+        let (headers, message) = match content.split_once("\n\n") {
+            Some((headers, message)) => (headers, message),
+            None => (content.trim_end_matches('\n'), ""),
+        };
+
+        let mut object = None;
+        let mut object_type = None;
+        let mut tag_name = None;
+        let mut tagger = None;
+        let mut extra_headers: Vec<(String, String)> = Vec::new();
+
+        for line in headers.split('\n') {
+            if let Some(continuation) = line.strip_prefix(' ') {
+                let (_, value) = extra_headers
+                    .last_mut()
+                    .ok_or("Invalid tag format: continuation line without a header")?;
+                value.push('\n');
+                value.push_str(continuation);
+                continue;
+            }
+
+            let (name, value) = line.split_once(' ').unwrap_or((line, ""));
+            match name {
+                "object" => object = Some(ObjectHash::from_hex(value)?),
+                "type" => object_type = Some(value.parse::<GitObjectType>()?),
+                "tag" => tag_name = Some(value.to_string()),
+                "tagger" => tagger = Some(self.parse_signature(value)?),
+                _ => extra_headers.push((name.to_string(), value.to_string())),
+            }
+        }
+
+        Ok(TagObject {
+            object: object.ok_or("Missing object in tag")?,
+            object_type: object_type.ok_or("Missing type in tag")?,
+            tag: tag_name.ok_or("Missing tag name in tag")?,
+            tagger,
+            extra_headers,
+            message: message.to_string(),
+        })
+    }
+
+    /// Parse a signature from "name \<email\> timestamp timezone" format
     fn parse_signature(&self, sig_str: &str) -> crate::Result<Signature> {
-        // Format: "Name \<email\> timestamp"
+        // Format: "Name \<email\> timestamp +hhmm"
+        // Objects written by older git-rs versions have no timezone
+        let (sig_str, timezone) = match sig_str.rsplit_once(' ') {
+            Some((rest, tz)) if tz.starts_with(['+', '-']) => (rest, Some(tz)),
+            _ => (sig_str, None),
+        };
+        let timezone_offset = timezone.map(Self::parse_timezone).transpose()?.unwrap_or(0);
+        // Git writes "-0000" for UTC with an unknown local time; the sign is
+        // part of the object's bytes, so remember it
+        let negative_zero_offset = timezone == Some("-0000");
+
         // Find the last space to separate timestamp from name/email
         if let Some(last_space) = sig_str.rfind(' ') {
             let name_email_part = &sig_str[..last_space];
@@ -332,6 +807,8 @@ impl ObjectStore {
                         name,
                         email,
                         timestamp: datetime,
+                        timezone_offset,
+                        negative_zero_offset,
                     });
                 }
             }
@@ -339,11 +816,26 @@ impl ObjectStore {
 
         Err(format!("Invalid signature format: '{}'", sig_str).into())
     }
+
+    /// Parse a `+hhmm` / `-hhmm` timezone into minutes east of UTC
+    fn parse_timezone(tz: &str) -> crate::Result<i32> {
+        let digits = &tz[1..];
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Invalid timezone: '{}'", tz).into());
+        }
+        let minutes = digits[..2].parse::<i32>()? * 60 + digits[2..].parse::<i32>()?;
+        Ok(if tz.starts_with('-') {
+            -minutes
+        } else {
+            minutes
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Read;
     use tempfile::tempdir;
 
     #[test]
@@ -370,7 +862,7 @@ mod tests {
         tree.add_entry(TreeEntry::new(
             FileMode::Regular,
             "file.txt".to_string(),
-            ObjectHash::new("1234567890abcdef1234567890abcdef12345678".to_string()),
+            ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap(),
         ));
 
         let tree_object = GitObject::Tree(tree);
@@ -380,6 +872,52 @@ mod tests {
         assert_eq!(tree_object, loaded);
     }
 
+    #[test]
+    fn test_tree_entry_order_and_modes() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        let blob = store
+            .store_object(&GitObject::Blob(BlobObject::new(b"y\n".to_vec())))
+            .unwrap();
+        let subtree = store
+            .store_object(&GitObject::Tree(TreeObject::new()))
+            .unwrap();
+
+        let mut tree = TreeObject::new();
+        tree.add_entry(TreeEntry::new(FileMode::Regular, "foo0".to_string(), blob));
+        tree.add_entry(TreeEntry::new(
+            FileMode::Directory,
+            "foo".to_string(),
+            subtree,
+        ));
+        tree.add_entry(TreeEntry::new(
+            FileMode::Regular,
+            "foo.txt".to_string(),
+            blob,
+        ));
+        let names: Vec<&str> = tree.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["foo.txt", "foo", "foo0"]);
+
+        let serialized = store.serialize_tree(&tree).unwrap();
+        let mut expected = Vec::new();
+        for (mode, name, hash) in [
+            ("100644", "foo.txt", &blob),
+            ("40000", "foo", &subtree),
+            ("100644", "foo0", &blob),
+        ] {
+            expected.extend_from_slice(format!("{} {}\0", mode, name).as_bytes());
+            expected.extend_from_slice(hash.as_bytes());
+        }
+        assert_eq!(serialized, expected);
+
+        // Loading keeps the stored order
+        let tree_hash = store.store_object(&GitObject::Tree(tree.clone())).unwrap();
+        assert_eq!(
+            store.load_object(&tree_hash).unwrap(),
+            GitObject::Tree(tree)
+        );
+    }
+
     #[test]
     fn test_store_and_load_commit() {
         let temp_dir = tempdir().unwrap();
@@ -392,13 +930,16 @@ mod tests {
             name: "Test User".to_string(),
             email: "test@example.com".to_string(),
             timestamp,
+            timezone_offset: 0,
+            negative_zero_offset: false,
         };
 
         let commit = CommitObject {
-            tree: ObjectHash::new("abcdef1234567890abcdef1234567890abcdef12".to_string()),
+            tree: ObjectHash::from_hex("abcdef1234567890abcdef1234567890abcdef12").unwrap(),
             parents: vec![],
             author: signature.clone(),
             committer: signature,
+            extra_headers: vec![],
             message: "Initial commit".to_string(),
         };
 
@@ -408,4 +949,435 @@ mod tests {
 
         assert_eq!(commit_object, loaded);
     }
+
+    #[test]
+    fn test_commit_round_trip_is_byte_exact() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+
+        // A signed merge of a signed tag, with timezones on both sides of UTC
+        // and a message whose trailing blank lines must survive
+        let content = concat!(
+            "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+            "parent 6d22cdea99cc7d2dc707e3ebc48868cd00edd633\n",
+            "parent 5cdabcb14fca7abb93792461b8832573dac32e8f\n",
+            "author A U Thor <author@example.com> 1692960000 +0530\n",
+            "committer C O Mitter <committer@example.com> 1692960001 -0700\n",
+            "mergetag object 5cdabcb14fca7abb93792461b8832573dac32e8f\n",
+            " type commit\n",
+            " tag v1.0\n",
+            " tagger A U Thor <author@example.com> 1692960000 +0530\n",
+            " \n",
+            " Release 1.0\n",
+            "gpgsig -----BEGIN PGP SIGNATURE-----\n",
+            " \n",
+            " iQEzBAABCAAdFiEE0123456789abcdef\n",
+            " =abcd\n",
+            " -----END PGP SIGNATURE-----\n",
+            "\n",
+            "Merge tag 'v1.0'\n",
+            "\n",
+            "Body line\r\n",
+            "\n",
+            "\n",
+        )
+        .as_bytes();
+
+        let object = store
+            .parse_object_content(&GitObjectType::Commit, content)
+            .unwrap();
+        let commit = object.as_commit().unwrap();
+        assert_eq!(commit.parents.len(), 2);
+        assert_eq!(commit.author.timezone_offset, 330);
+        assert_eq!(commit.committer.timezone_offset, -420);
+        assert_eq!(commit.extra_headers.len(), 2);
+        assert!(commit
+            .extra_header("mergetag")
+            .unwrap()
+            .ends_with("\n\nRelease 1.0"));
+        assert!(commit
+            .gpg_signature()
+            .unwrap()
+            .starts_with("-----BEGIN PGP SIGNATURE-----\n\niQEz"));
+        assert_eq!(commit.message, "Merge tag 'v1.0'\n\nBody line\r\n\n\n");
+
+        let hash = store.store_object(&object).unwrap();
+        assert_eq!(hash, ObjectFormat::Sha1.hash_object("commit", content));
+    }
+
+    #[test]
+    fn test_negative_zero_timezone_round_trips() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+
+        // "-0000" and "+0000" are the same offset but different bytes
+        let content = concat!(
+            "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+            "author A U Thor <author@example.com> 1692960000 -0000\n",
+            "committer C O Mitter <committer@example.com> 1692960001 +0000\n",
+            "\n",
+            "Imported from elsewhere\n",
+        )
+        .as_bytes();
+
+        let object = store
+            .parse_object_content(&GitObjectType::Commit, content)
+            .unwrap();
+        let commit = object.as_commit().unwrap();
+        assert_eq!(commit.author.timezone_offset, 0);
+        assert_eq!(commit.author.format_timezone(), "-0000");
+        assert_eq!(commit.committer.format_timezone(), "+0000");
+
+        let hash = store.store_object(&object).unwrap();
+        assert_eq!(hash, ObjectFormat::Sha1.hash_object("commit", content));
+        assert_eq!(store.load_object(&hash).unwrap(), object);
+    }
+
+    #[test]
+    fn test_commit_with_legacy_encoding_round_trips() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+
+        // `git -c i18n.commitEncoding=ISO-8859-1 commit`: the message is not UTF-8
+        let content = b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+author A U Thor <author@example.com> 1692960000 +0000\n\
+committer A U Thor <author@example.com> 1692960000 +0000\n\
+encoding ISO-8859-1\n\
+\n\
+caf\xe9\n";
+
+        let object = store
+            .parse_object_content(&GitObjectType::Commit, content)
+            .unwrap();
+        let commit = object.as_commit().unwrap();
+        assert_eq!(commit.encoding(), Some("ISO-8859-1"));
+        assert_eq!(commit.message, "caf\u{e9}\n");
+
+        let hash = store.store_object(&object).unwrap();
+        assert_eq!(hash, ObjectFormat::Sha1.hash_object("commit", content));
+
+        // Characters outside the declared encoding cannot be stored
+        let mut commit = commit.clone();
+        commit.message = "\u{2603}\n".to_string();
+        assert!(store.store_object(&GitObject::Commit(commit)).is_err());
+    }
+
+    #[test]
+    fn test_tag_round_trip_is_byte_exact() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        store.init().unwrap();
+
+        // Content as written by `git tag -a v1.0 -m "Release 1.0"`
+        let content = b"object 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+type commit\n\
+tag v1.0\n\
+tagger A U Thor <author@example.com> 1692960000 +0530\n\
+\n\
+Release 1.0\n";
+
+        let object = store
+            .parse_object_content(&GitObjectType::Tag, content)
+            .unwrap();
+        let tag = object.as_tag().unwrap();
+        assert_eq!(tag.tag, "v1.0");
+        assert_eq!(tag.object_type, GitObjectType::Commit);
+        assert_eq!(tag.tagger.as_ref().unwrap().timezone_offset, 330);
+        assert_eq!(tag.message, "Release 1.0\n");
+
+        let hash = store.store_object(&object).unwrap();
+        let (object_type, stored) = store.load_raw_object(&hash).unwrap();
+        assert_eq!(object_type, GitObjectType::Tag);
+        assert_eq!(stored, content.to_vec());
+    }
+
+    #[test]
+    fn test_tag_round_trip_keeps_unknown_headers() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        store.init().unwrap();
+
+        let content = b"object 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+type commit\n\
+tag v1.0\n\
+tagger A U Thor <author@example.com> 1692960000 +0000\n\
+x-release-channel stable\n\
+x-notes first line\n second line\n\
+\n\
+Release 1.0\n";
+
+        let object = store
+            .parse_object_content(&GitObjectType::Tag, content)
+            .unwrap();
+        let tag = object.as_tag().unwrap();
+        assert_eq!(
+            tag.extra_headers,
+            vec![
+                ("x-release-channel".to_string(), "stable".to_string()),
+                ("x-notes".to_string(), "first line\nsecond line".to_string()),
+            ]
+        );
+
+        let hash = store.store_object(&object).unwrap();
+        assert_eq!(hash, ObjectFormat::Sha1.hash_object("tag", content));
+        assert_eq!(store.load_raw_object(&hash).unwrap().1, content.to_vec());
+    }
+
+    #[test]
+    fn test_peel_tags_to_commit() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        store.init().unwrap();
+
+        let tree = store
+            .store_object(&GitObject::Tree(TreeObject::new()))
+            .unwrap();
+        let signature = Signature::new("Test".to_string(), "test@example.com".to_string());
+        let commit = CommitObject::new(tree, vec![], signature.clone(), "msg\n".into());
+        let commit_hash = store.store_object(&GitObject::Commit(commit)).unwrap();
+
+        let inner = TagObject::new(
+            commit_hash,
+            GitObjectType::Commit,
+            "inner".to_string(),
+            signature.clone(),
+            "inner\n".to_string(),
+        );
+        let inner_hash = store.store_object(&GitObject::Tag(inner)).unwrap();
+        let outer = TagObject::new(
+            inner_hash,
+            GitObjectType::Tag,
+            "outer".to_string(),
+            signature,
+            "outer\n".to_string(),
+        );
+        let outer_hash = store.store_object(&GitObject::Tag(outer)).unwrap();
+
+        assert_eq!(store.peel_to_commit(&outer_hash).unwrap(), commit_hash);
+        assert_eq!(store.peel_to_commit(&commit_hash).unwrap(), commit_hash);
+        assert!(store.peel_to_commit(&tree).is_err());
+    }
+
+    #[test]
+    fn test_streamed_blob_matches_in_memory_blob() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        store.init().unwrap();
+
+        // Several chunks plus a partial one
+        let content: Vec<u8> = (0..(STREAM_CHUNK_SIZE * 3 + 123))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let file_path = temp_dir.path().join("large.bin");
+        fs::write(&file_path, &content).unwrap();
+
+        let hash = store.store_blob_from_file(&file_path).unwrap();
+        assert_eq!(hash, ObjectFormat::Sha1.hash_object("blob", &content));
+        assert_eq!(store.hash_file(&file_path).unwrap(), hash);
+
+        let loaded = store.load_object(&hash).unwrap();
+        assert_eq!(loaded, GitObject::Blob(BlobObject::new(content.clone())));
+
+        // Objects never change once written
+        let object_path = store.get_object_path(&hash);
+        assert!(fs::metadata(&object_path).unwrap().permissions().readonly());
+
+        // Storing again reuses the existing object and leaves no temp files
+        assert_eq!(store.store_blob_from_file(&file_path).unwrap(), hash);
+        assert_eq!(store.store_object(&loaded).unwrap(), hash);
+        let leftovers: Vec<_> = fs::read_dir(store.objects_dir())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("tmp_obj_"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_open_object_streams_content() {
+        let temp_dir = tempdir().unwrap();
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        store.init().unwrap();
+
+        let content = b"streamed content\n".repeat(10_000);
+        let hash = store
+            .store_object(&GitObject::Blob(BlobObject::new(content.clone())))
+            .unwrap();
+
+        let mut reader = store.open_object(&hash).unwrap();
+        assert_eq!(reader.object_type(), &GitObjectType::Blob);
+        assert_eq!(reader.size(), content.len() as u64);
+        let mut read_back = Vec::new();
+        reader.read_to_end(&mut read_back).unwrap();
+        assert_eq!(read_back, content);
+
+        let out_path = temp_dir.path().join("checkout.txt");
+        assert_eq!(
+            store.write_blob_to_file(&hash, &out_path).unwrap(),
+            content.len() as u64
+        );
+        assert_eq!(fs::read(&out_path).unwrap(), content);
+
+        let tree = store
+            .store_object(&GitObject::Tree(TreeObject::new()))
+            .unwrap();
+        assert!(store.write_blob_to_file(&tree, &out_path).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_store_needs_no_file_system() {
+        let store = ObjectStore::in_memory(ObjectFormat::Sha1);
+
+        let blob = store
+            .store_object(&GitObject::Blob(BlobObject::new(b"Hello World\n".to_vec())))
+            .unwrap();
+        let mut tree = TreeObject::new();
+        tree.add_entry(TreeEntry::new(
+            FileMode::Regular,
+            "hello.txt".to_string(),
+            blob,
+        ));
+        let tree = store.store_object(&GitObject::Tree(tree)).unwrap();
+        let author = Signature::new("A".to_string(), "a@example.com".to_string());
+        let commit = store
+            .store_object(&GitObject::Commit(CommitObject::new(
+                tree,
+                vec![],
+                author,
+                "Initial\n".to_string(),
+            )))
+            .unwrap();
+
+        assert_eq!(store.peel_to_commit(&commit).unwrap(), commit);
+        let files = store.flatten_tree(&tree).unwrap();
+        assert_eq!(files[Path::new("hello.txt")].hash, blob);
+        assert_eq!(store.list_objects().unwrap().len(), 3);
+        assert!(store.list_loose_objects().unwrap().is_empty());
+        assert!(store.packs().unwrap().is_empty());
+
+        let mut content = String::new();
+        store
+            .open_object(&blob)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "Hello World\n");
+    }
+
+    #[test]
+    fn test_alternates_are_followed_recursively() {
+        let temp_dir = tempdir().unwrap();
+        let objects = |name: &str| temp_dir.path().join(name).join("objects");
+        for name in ["clone", "mirror", "upstream"] {
+            ObjectStore::new(objects(name)).init().unwrap();
+        }
+
+        // clone -> mirror (absolute) -> upstream (relative) -> clone (a cycle)
+        fs::write(
+            objects("clone").join("info/alternates"),
+            format!("# shared mirror\n\n{}\n", objects("mirror").display()),
+        )
+        .unwrap();
+        fs::write(
+            objects("mirror").join("info/alternates"),
+            "../../upstream/objects\n/does/not/exist\n",
+        )
+        .unwrap();
+        fs::write(
+            objects("upstream").join("info/alternates"),
+            format!("{}\n", objects("clone").display()),
+        )
+        .unwrap();
+
+        let blob = |content: &str| GitObject::Blob(BlobObject::new(content.as_bytes().to_vec()));
+        let upstream_hash = ObjectStore::new(objects("upstream"))
+            .store_object(&blob("upstream"))
+            .unwrap();
+        let mirror_hash = ObjectStore::new(objects("mirror"))
+            .store_object(&blob("mirror"))
+            .unwrap();
+
+        let store = ObjectStore::new(objects("clone"));
+        assert_eq!(
+            store.alternates(),
+            [
+                objects("mirror").canonicalize().unwrap(),
+                objects("upstream").canonicalize().unwrap()
+            ]
+        );
+        assert_eq!(store.load_object(&upstream_hash).unwrap(), blob("upstream"));
+        assert!(store.object_exists(&mirror_hash));
+        assert_eq!(store.list_objects().unwrap().len(), 2);
+        assert!(store.list_local_objects().unwrap().is_empty());
+
+        // Borrowed objects are not copied, new ones are written locally
+        assert_eq!(store.store_object(&blob("mirror")).unwrap(), mirror_hash);
+        let local_hash = store.store_object(&blob("local")).unwrap();
+        assert_eq!(store.list_loose_objects().unwrap(), vec![local_hash]);
+        assert_eq!(
+            ObjectStore::new(objects("mirror"))
+                .list_loose_objects()
+                .unwrap(),
+            vec![mirror_hash]
+        );
+    }
+
+    #[test]
+    fn test_add_alternate() {
+        let temp_dir = tempdir().unwrap();
+        let source = ObjectStore::new(temp_dir.path().join("source/objects"));
+        source.init().unwrap();
+        let hash = source
+            .store_object(&GitObject::Blob(BlobObject::new(b"shared".to_vec())))
+            .unwrap();
+
+        let mut store = ObjectStore::new(temp_dir.path().join("clone/objects"));
+        store.init().unwrap();
+        assert!(!store.object_exists(&hash));
+        assert!(store
+            .add_alternate(&temp_dir.path().join("missing/objects"))
+            .is_err());
+
+        store.add_alternate(source.objects_dir()).unwrap();
+        assert!(store.object_exists(&hash));
+        assert!(ObjectStore::new(temp_dir.path().join("clone/objects")).object_exists(&hash));
+        assert_eq!(
+            ObjectStore::read_alternates(store.objects_dir()).unwrap(),
+            vec![source.objects_dir().canonicalize().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_loaded_objects_are_cached() {
+        let temp_dir = tempdir().unwrap();
+        let objects_dir = temp_dir.path().join("objects");
+        let blob = GitObject::Blob(BlobObject::new(b"cached".to_vec()));
+
+        let store = ObjectStore::new(objects_dir.clone());
+        store.init().unwrap();
+        let hash = store.store_object(&blob).unwrap();
+        let uncached = ObjectStore::with_cache_limits(
+            objects_dir.clone(),
+            ObjectFormat::Sha1,
+            CacheLimits {
+                objects: 0,
+                delta_bases: 0,
+            },
+        );
+        assert_eq!(store.load_object(&hash).unwrap(), blob);
+        assert_eq!(uncached.load_object(&hash).unwrap(), blob);
+
+        // Once loaded, the object no longer needs its file
+        fs::remove_file(store.get_object_path(&hash)).unwrap();
+        assert_eq!(store.load_object(&hash).unwrap(), blob);
+        assert!(uncached.load_object(&hash).is_err());
+
+        // The limits come from the repository config
+        fs::write(
+            temp_dir.path().join("config"),
+            "[core]\n\tobjectCacheLimit = 1k\n",
+        )
+        .unwrap();
+        assert_eq!(ObjectStore::new(objects_dir).cache_limits().objects, 1024);
+    }
 }
@@ -0,0 +1,942 @@
+use std::cell::{OnceCell, RefCell};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::domain::objects::{GitObjectType, ObjectFormat, ObjectHash};
+use crate::domain::remote::PackObjectType;
+use crate::infrastructure::object_cache::{LruCache, DEFAULT_DELTA_BASE_CACHE_LIMIT};
+use crate::infrastructure::object_database::ObjectDatabase;
+
+/// Magic bytes at the start of a version 2 pack index ("\377tOc")
+const IDX_MAGIC: [u8; 4] = [0xff, b't', b'O', b'c'];
+
+/// Pack Index (.idx version 2)
+///
+/// A pack index lets Git find an object inside a `.pack` file without
+/// scanning it. The layout of a version 2 index is:
+///
+/// ```text
+/// +--------------------------------+
+/// | magic "\377tOc" | version = 2  |
+/// +--------------------------------+
+/// | fanout[256] (u32 each)         |  fanout[b] = number of objects whose
+/// |                                |  first byte is <= b
+/// +--------------------------------+
+/// | N object ids (sorted)          |  binary search happens here
+/// +--------------------------------+
+/// | N CRC32 values                 |
+/// +--------------------------------+
+/// | N 32-bit offsets               |  MSB set = index into 64-bit table
+/// +--------------------------------+
+/// | 64-bit offsets (large packs)   |
+/// +--------------------------------+
+/// | pack checksum | idx checksum   |
+/// +--------------------------------+
+/// ```
+///
+/// Object ids and checksums are 20 bytes in SHA-1 repositories and 32 bytes
+/// in SHA-256 repositories.
+#[derive(Debug, Clone)]
+pub struct PackIndex {
+    format: ObjectFormat,
+    fanout: [u32; 256],
+    hashes: Vec<u8>,
+    crc32: Vec<u32>,
+    offsets: Vec<u64>,
+    pack_checksum: Vec<u8>,
+}
+
+impl PackIndex {
+    /// Load a pack index from disk
+    pub fn load(path: &Path, format: ObjectFormat) -> crate::Result<Self> {
+        let data = fs::read(path)?;
+        Self::parse(&data, format)
+    }
+
+    /// Parse a version 2 pack index
+    pub fn parse(data: &[u8], format: ObjectFormat) -> crate::Result<Self> {
+        let hash_len = format.raw_len();
+        if data.len() < 8 + 256 * 4 || data[0..4] != IDX_MAGIC {
+            return Err("Invalid pack index: bad signature".into());
+        }
+
+        let version = read_u32(data, 4);
+        if version != 2 {
+            return Err(format!("Unsupported pack index version: {}", version).into());
+        }
+
+        let mut fanout = [0u32; 256];
+        for (i, slot) in fanout.iter_mut().enumerate() {
+            *slot = read_u32(data, 8 + i * 4);
+        }
+
+        // Lookups slice the id table between neighbouring fanout entries
+        if fanout.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err("Invalid pack index: non-monotonic fanout table".into());
+        }
+
+        let count = fanout[255] as usize;
+        let hashes_start = 8 + 256 * 4;
+        let crc_start = hashes_start + count * hash_len;
+        let offsets_start = crc_start + count * 4;
+        let large_start = offsets_start + count * 4;
+
+        if data.len() < large_start + 2 * hash_len {
+            return Err("Invalid pack index: truncated".into());
+        }
+
+        // Whatever lies between the 32-bit offsets and the trailer is the
+        // 64-bit offset table, with at most one entry per object
+        let large_table = data.len() - large_start - 2 * hash_len;
+        if !large_table.is_multiple_of(8) || large_table / 8 > count {
+            return Err("Invalid pack index: wrong file size".into());
+        }
+
+        let hashes = data[hashes_start..crc_start].to_vec();
+        let crc32 = (0..count)
+            .map(|i| read_u32(data, crc_start + i * 4))
+            .collect();
+
+        let mut offsets = Vec::with_capacity(count);
+        for i in 0..count {
+            let offset = read_u32(data, offsets_start + i * 4);
+            if offset & 0x8000_0000 == 0 {
+                offsets.push(offset as u64);
+            } else {
+                // Large offset: the low 31 bits index the 64-bit offset table
+                let large_pos = large_start + (offset & 0x7fff_ffff) as usize * 8;
+                if large_pos + 8 > data.len() - 2 * hash_len {
+                    return Err("Invalid pack index: bad 64-bit offset".into());
+                }
+                offsets.push(read_u64(data, large_pos));
+            }
+        }
+
+        let trailer_start = data.len() - 2 * hash_len;
+        let pack_checksum = data[trailer_start..trailer_start + hash_len].to_vec();
+
+        Ok(Self {
+            format,
+            fanout,
+            hashes,
+            crc32,
+            offsets,
+            pack_checksum,
+        })
+    }
+
+    /// Number of objects described by this index
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Check if the index is empty
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Find the pack offset of an object using the fanout table and binary search
+    pub fn find_offset(&self, hash: &ObjectHash) -> Option<u64> {
+        let target = hash.as_bytes();
+        if target.len() != self.format.raw_len() {
+            return None;
+        }
+
+        // The fanout table narrows the search to objects sharing the first byte
+        let first = target[0] as usize;
+        let mut low = if first == 0 {
+            0
+        } else {
+            self.fanout[first - 1] as usize
+        };
+        let mut high = self.fanout[first] as usize;
+
+        while low < high {
+            let mid = (low + high) / 2;
+            match self.hash_bytes(mid).cmp(target) {
+                std::cmp::Ordering::Equal => return Some(self.offsets[mid]),
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+            }
+        }
+
+        None
+    }
+
+    /// Get the object hash stored at a given position
+    pub fn hash_at(&self, position: usize) -> ObjectHash {
+        ObjectHash::from_bytes(self.hash_bytes(position)).expect("index ids have the pack's length")
+    }
+
+    /// Get the CRC32 of the packed data stored at a given position
+    pub fn crc32_at(&self, position: usize) -> u32 {
+        self.crc32[position]
+    }
+
+    /// Get the pack offset stored at a given position
+    pub fn offset_at(&self, position: usize) -> u64 {
+        self.offsets[position]
+    }
+
+    /// All object hashes in the index, in sorted order
+    pub fn hashes(&self) -> Vec<ObjectHash> {
+        (0..self.len()).map(|i| self.hash_at(i)).collect()
+    }
+
+    /// Checksum of the pack file this index belongs to
+    pub fn pack_checksum(&self) -> &[u8] {
+        &self.pack_checksum
+    }
+
+    /// Object format of the ids in this index
+    pub fn format(&self) -> ObjectFormat {
+        self.format
+    }
+
+    fn hash_bytes(&self, position: usize) -> &[u8] {
+        let hash_len = self.format.raw_len();
+        &self.hashes[position * hash_len..(position + 1) * hash_len]
+    }
+}
+
+/// Pack Reader - Reads objects out of a `.pack` file using its `.idx`
+///
+/// Each pack entry starts with a small header encoding the object type and
+/// inflated size, followed by zlib-compressed data. Delta entries reference a
+/// base object either by a negative offset inside the same pack (OFS_DELTA) or
+/// by its object id (REF_DELTA); the reader follows the chain down to a full
+/// object and replays the deltas on top of it.
+///
+/// Objects deltified against the same base (say, consecutive versions of a
+/// tree) would each inflate that base again, so resolved bases are kept in a
+/// small cache keyed by their pack offset (`core.deltaBaseCacheLimit`).
+#[derive(Debug, Clone)]
+pub struct PackReader {
+    pack_path: PathBuf,
+    index: PackIndex,
+    /// Entry offsets sorted ascending, used to bound each entry's compressed data
+    sorted_offsets: Vec<u64>,
+    pack_len: u64,
+    /// Resolved objects that other entries are deltas against, by offset
+    delta_bases: RefCell<LruCache<u64, (PackObjectType, Vec<u8>)>>,
+}
+
+impl PackReader {
+    /// Open a pack given the path of its `.idx` file
+    pub fn open(idx_path: &Path, format: ObjectFormat) -> crate::Result<Self> {
+        let index = PackIndex::load(idx_path, format)?;
+        let pack_path = idx_path.with_extension("pack");
+        let pack_len = fs::metadata(&pack_path)?.len();
+
+        let mut sorted_offsets = index.offsets.clone();
+        sorted_offsets.sort_unstable();
+
+        Ok(Self {
+            pack_path,
+            index,
+            sorted_offsets,
+            pack_len,
+            delta_bases: RefCell::new(LruCache::new(DEFAULT_DELTA_BASE_CACHE_LIMIT)),
+        })
+    }
+
+    /// Use a delta base cache of at most `limit` bytes (0 turns it off)
+    pub fn with_delta_base_cache_limit(mut self, limit: usize) -> Self {
+        self.delta_bases = RefCell::new(LruCache::new(limit));
+        self
+    }
+
+    /// Get the pack index
+    pub fn index(&self) -> &PackIndex {
+        &self.index
+    }
+
+    /// Get the path to the `.pack` file
+    pub fn pack_path(&self) -> &Path {
+        &self.pack_path
+    }
+
+    /// Check if the pack contains an object
+    pub fn contains(&self, hash: &ObjectHash) -> bool {
+        self.index.find_offset(hash).is_some()
+    }
+
+    /// Read and fully resolve an object, returning its type and content
+    pub fn read_object(
+        &self,
+        hash: &ObjectHash,
+    ) -> crate::Result<Option<(PackObjectType, Vec<u8>)>> {
+        match self.index.find_offset(hash) {
+            Some(offset) => {
+                let mut file = fs::File::open(&self.pack_path)?;
+                Ok(Some(self.read_at(&mut file, offset)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Read the object stored at a pack offset, resolving any delta chain
+    fn read_at(
+        &self,
+        file: &mut fs::File,
+        offset: u64,
+    ) -> crate::Result<(PackObjectType, Vec<u8>)> {
+        // Walk down the delta chain, remembering each delta on the way, until
+        // reaching a full object or a base that is already resolved
+        let mut deltas = Vec::new();
+        let mut current = offset;
+
+        let (base_type, mut data) = loop {
+            if let Some((object_type, data)) = self.delta_bases.borrow_mut().get(&current) {
+                break (*object_type, data.clone());
+            }
+
+            let raw = self.read_raw_entry(file, current)?;
+            match raw.object_type {
+                PackObjectType::OfsDelta => {
+                    let base_offset = raw
+                        .base_offset
+                        .ok_or("Invalid pack: missing delta base offset")?;
+                    deltas.push((current, raw.data));
+                    current = base_offset;
+                }
+                PackObjectType::RefDelta => {
+                    let base_hash = raw.base_hash.ok_or("Invalid pack: missing delta base")?;
+                    let base_offset = self
+                        .index
+                        .find_offset(&base_hash)
+                        .ok_or_else(|| format!("Delta base {} not found in pack", base_hash))?;
+                    deltas.push((current, raw.data));
+                    current = base_offset;
+                }
+                object_type => break (object_type, raw.data),
+            }
+
+            if deltas.len() > MAX_DELTA_CHAIN {
+                return Err("Invalid pack: delta chain too long".into());
+            }
+        };
+
+        // Apply deltas from the one closest to the base back up to the target,
+        // caching every object on the way that a delta was applied to
+        let mut cache = self.delta_bases.borrow_mut();
+        let mut base_offset = current;
+        for (delta_offset, delta) in deltas.iter().rev() {
+            cache.insert(base_offset, (base_type, data.clone()), data.len());
+            data = apply_delta(&data, delta)?;
+            base_offset = *delta_offset;
+        }
+
+        Ok((base_type, data))
+    }
+
+    /// Read a single entry without resolving deltas
+    fn read_raw_entry(&self, file: &mut fs::File, offset: u64) -> crate::Result<RawPackEntry> {
+        // The next entry (or the trailing checksum) bounds this entry's bytes
+        let end = match self.sorted_offsets.binary_search(&offset) {
+            Ok(pos) if pos + 1 < self.sorted_offsets.len() => self.sorted_offsets[pos + 1],
+            Ok(_) => self
+                .pack_len
+                .saturating_sub(self.index.format.raw_len() as u64),
+            Err(_) => return Err(format!("No pack entry at offset {}", offset).into()),
+        };
+
+        let mut buffer = vec![0u8; end.saturating_sub(offset) as usize];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut buffer)?;
+
+        let entry = parse_pack_entry(&buffer, offset, self.index.format)?;
+        Ok(entry.entry)
+    }
+}
+
+/// Maximum delta chain length we are willing to follow
+const MAX_DELTA_CHAIN: usize = 10_000;
+
+/// A pack entry before delta resolution
+#[derive(Debug, Clone)]
+pub struct RawPackEntry {
+    /// Entry type as stored in the pack (may be a delta)
+    pub object_type: PackObjectType,
+    /// Inflated size from the entry header
+    pub size: u64,
+    /// Inflated entry data (object content or delta instructions)
+    pub data: Vec<u8>,
+    /// Absolute offset of the base object for OFS_DELTA entries
+    pub base_offset: Option<u64>,
+    /// Object id of the base object for REF_DELTA entries
+    pub base_hash: Option<ObjectHash>,
+}
+
+/// A parsed pack entry together with the number of bytes it occupied
+#[derive(Debug, Clone)]
+pub struct ParsedPackEntry {
+    pub entry: RawPackEntry,
+    /// Total bytes consumed (header + delta base reference + compressed data)
+    pub consumed: usize,
+}
+
+/// Parse one pack entry starting at the beginning of `data`
+///
+/// `offset` is the absolute position of the entry in the pack, needed to turn
+/// the relative OFS_DELTA distance into an absolute base offset. `format`
+/// determines the size of REF_DELTA base ids.
+pub fn parse_pack_entry(
+    data: &[u8],
+    offset: u64,
+    format: ObjectFormat,
+) -> crate::Result<ParsedPackEntry> {
+    let (type_code, size, mut pos) = parse_entry_header(data)?;
+    let object_type = match type_code {
+        1..=4 | 6 | 7 => PackObjectType::from(type_code),
+        other => return Err(format!("Invalid pack entry type: {}", other).into()),
+    };
+
+    let mut base_offset = None;
+    let mut base_hash = None;
+
+    match object_type {
+        PackObjectType::OfsDelta => {
+            let (distance, used) = parse_ofs_distance(&data[pos..])?;
+            pos += used;
+            base_offset = Some(
+                offset
+                    .checked_sub(distance)
+                    .ok_or("Invalid pack: delta base offset before start of pack")?,
+            );
+        }
+        PackObjectType::RefDelta => {
+            let hash_len = format.raw_len();
+            if data.len() < pos + hash_len {
+                return Err("Invalid pack: truncated delta base".into());
+            }
+            base_hash = Some(ObjectHash::from_bytes(&data[pos..pos + hash_len])?);
+            pos += hash_len;
+        }
+        _ => {}
+    }
+
+    let (inflated, used) = inflate(&data[pos..], size as usize)?;
+    pos += used;
+
+    Ok(ParsedPackEntry {
+        entry: RawPackEntry {
+            object_type,
+            size,
+            data: inflated,
+            base_offset,
+            base_hash,
+        },
+        consumed: pos,
+    })
+}
+
+/// Parse a pack entry header: 3-bit type and variable-length size
+///
+/// ```text
+/// first byte:  [MSB: more] [3 bits: type] [4 bits: size low bits]
+/// next bytes:  [MSB: more] [7 bits: next size bits]
+/// ```
+pub fn parse_entry_header(data: &[u8]) -> crate::Result<(u8, u64, usize)> {
+    let first = *data.first().ok_or("Invalid pack: truncated entry header")?;
+    let type_code = (first >> 4) & 0x07;
+    let mut size = (first & 0x0f) as u64;
+    let mut shift = 4;
+    let mut pos = 1;
+    let mut byte = first;
+
+    while byte & 0x80 != 0 {
+        byte = *data
+            .get(pos)
+            .ok_or("Invalid pack: truncated entry header")?;
+        if shift > 57 {
+            return Err("Invalid pack: entry size overflow".into());
+        }
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        pos += 1;
+    }
+
+    Ok((type_code, size, pos))
+}
+
+/// Parse the negative offset encoding used by OFS_DELTA entries
+///
+/// Each continuation adds one before shifting, so that every distance has a
+/// single encoding.
+fn parse_ofs_distance(data: &[u8]) -> crate::Result<(u64, usize)> {
+    let mut pos = 0;
+    let mut byte = *data.first().ok_or("Invalid pack: truncated delta offset")?;
+    let mut distance = (byte & 0x7f) as u64;
+
+    while byte & 0x80 != 0 {
+        pos += 1;
+        byte = *data
+            .get(pos)
+            .ok_or("Invalid pack: truncated delta offset")?;
+        distance = ((distance + 1) << 7) | (byte & 0x7f) as u64;
+    }
+
+    Ok((distance, pos + 1))
+}
+
+/// Inflate a zlib stream, returning the data and the number of compressed bytes used
+fn inflate(data: &[u8], expected_size: usize) -> crate::Result<(Vec<u8>, usize)> {
+    let mut decoder = flate2::bufread::ZlibDecoder::new(data);
+    let mut output = Vec::with_capacity(expected_size);
+    decoder.read_to_end(&mut output)?;
+
+    if output.len() != expected_size {
+        return Err(format!(
+            "Invalid pack: inflated {} bytes, expected {}",
+            output.len(),
+            expected_size
+        )
+        .into());
+    }
+
+    Ok((output, decoder.total_in() as usize))
+}
+
+/// Apply a Git delta to a base object
+///
+/// A delta starts with the base and result sizes (little-endian base-128),
+/// followed by instructions:
+///
+/// ```text
+/// 1xxxxxxx  copy:   next bytes hold offset (bits 0-3) and size (bits 4-6)
+/// 0xxxxxxx  insert: copy the next xxxxxxx literal bytes from the delta
+/// ```
+pub fn apply_delta(base: &[u8], delta: &[u8]) -> crate::Result<Vec<u8>> {
+    let (base_size, mut pos) = parse_delta_size(delta, 0)?;
+    if base_size as usize != base.len() {
+        return Err("Invalid delta: base size mismatch".into());
+    }
+    let (result_size, used) = parse_delta_size(delta, pos)?;
+    pos = used;
+
+    let mut result = Vec::with_capacity(result_size as usize);
+
+    while pos < delta.len() {
+        let opcode = delta[pos];
+        pos += 1;
+
+        if opcode & 0x80 != 0 {
+            let mut copy_offset = 0usize;
+            let mut copy_size = 0usize;
+
+            for bit in 0..4 {
+                if opcode & (1 << bit) != 0 {
+                    let byte = *delta.get(pos).ok_or("Invalid delta: truncated copy")?;
+                    copy_offset |= (byte as usize) << (8 * bit);
+                    pos += 1;
+                }
+            }
+            for bit in 0..3 {
+                if opcode & (0x10 << bit) != 0 {
+                    let byte = *delta.get(pos).ok_or("Invalid delta: truncated copy")?;
+                    copy_size |= (byte as usize) << (8 * bit);
+                    pos += 1;
+                }
+            }
+            if copy_size == 0 {
+                copy_size = 0x10000;
+            }
+
+            let end = copy_offset
+                .checked_add(copy_size)
+                .filter(|end| *end <= base.len())
+                .ok_or("Invalid delta: copy outside base object")?;
+            result.extend_from_slice(&base[copy_offset..end]);
+        } else if opcode != 0 {
+            let end = pos + opcode as usize;
+            if end > delta.len() {
+                return Err("Invalid delta: truncated insert".into());
+            }
+            result.extend_from_slice(&delta[pos..end]);
+            pos = end;
+        } else {
+            return Err("Invalid delta: reserved opcode 0".into());
+        }
+    }
+
+    if result.len() != result_size as usize {
+        return Err("Invalid delta: result size mismatch".into());
+    }
+
+    Ok(result)
+}
+
+/// Parse a little-endian base-128 size from a delta header
+fn parse_delta_size(delta: &[u8], mut pos: usize) -> crate::Result<(u64, usize)> {
+    let mut size = 0u64;
+    let mut shift = 0;
+
+    loop {
+        let byte = *delta.get(pos).ok_or("Invalid delta: truncated header")?;
+        pos += 1;
+        if shift > 57 {
+            return Err("Invalid delta: size overflow".into());
+        }
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    Ok((size, pos))
+}
+
+/// Find all packs in an `objects/pack` directory
+pub fn discover_packs(pack_dir: &Path, format: ObjectFormat) -> crate::Result<Vec<PackReader>> {
+    let mut packs = Vec::new();
+
+    if !pack_dir.is_dir() {
+        return Ok(packs);
+    }
+
+    let mut idx_paths: Vec<PathBuf> = fs::read_dir(pack_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "idx"))
+        .collect();
+    idx_paths.sort();
+
+    for idx_path in idx_paths {
+        // An index without its pack is left over from an interrupted operation
+        if idx_path.with_extension("pack").exists() {
+            packs.push(PackReader::open(&idx_path, format)?);
+        }
+    }
+
+    Ok(packs)
+}
+
+/// The pack files of an `objects/pack` directory as an object database
+///
+/// Packs are discovered on first use. They are written whole by `repack` or
+/// a fetch, never one object at a time, so this database is read-only.
+pub struct PackObjectDatabase {
+    pack_dir: PathBuf,
+    format: ObjectFormat,
+    packs: OnceCell<Vec<PackReader>>,
+    delta_base_cache_limit: usize,
+}
+
+impl PackObjectDatabase {
+    pub fn new(pack_dir: PathBuf, format: ObjectFormat) -> Self {
+        Self {
+            pack_dir,
+            format,
+            packs: OnceCell::new(),
+            delta_base_cache_limit: DEFAULT_DELTA_BASE_CACHE_LIMIT,
+        }
+    }
+
+    /// Give each pack a delta base cache of at most `limit` bytes
+    pub fn with_delta_base_cache_limit(mut self, limit: usize) -> Self {
+        self.delta_base_cache_limit = limit;
+        self
+    }
+}
+
+impl ObjectDatabase for PackObjectDatabase {
+    fn format(&self) -> ObjectFormat {
+        self.format
+    }
+
+    fn read(&self, hash: &ObjectHash) -> crate::Result<Option<(GitObjectType, Vec<u8>)>> {
+        for pack in self.packs()? {
+            if let Some((pack_type, content)) = pack.read_object(hash)? {
+                let object_type = pack_type
+                    .type_name()
+                    .ok_or_else(|| format!("Unresolved delta for object {}", hash))?
+                    .parse()?;
+                return Ok(Some((object_type, content)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn write(&self, _object_type: &GitObjectType, _content: &[u8]) -> crate::Result<ObjectHash> {
+        Err(format!(
+            "cannot write single objects into packs in {}",
+            self.pack_dir.display()
+        )
+        .into())
+    }
+
+    fn exists(&self, hash: &ObjectHash) -> bool {
+        self.packs()
+            .map(|packs| packs.iter().any(|pack| pack.contains(hash)))
+            .unwrap_or(false)
+    }
+
+    fn iter(&self) -> crate::Result<Box<dyn Iterator<Item = ObjectHash> + '_>> {
+        Ok(Box::new(
+            self.packs()?.iter().flat_map(|pack| pack.index().hashes()),
+        ))
+    }
+
+    fn packs(&self) -> crate::Result<&[PackReader]> {
+        if let Some(packs) = self.packs.get() {
+            return Ok(packs);
+        }
+
+        let packs = discover_packs(&self.pack_dir, self.format)?
+            .into_iter()
+            .map(|pack| pack.with_delta_base_cache_limit(self.delta_base_cache_limit))
+            .collect();
+        Ok(self.packs.get_or_init(|| packs))
+    }
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn read_u64(data: &[u8], pos: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&data[pos..pos + 8]);
+    u64::from_be_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use sha1::{Digest, Sha1};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn entry_header(type_code: u8, size: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut byte = (type_code << 4) | (size & 0x0f) as u8;
+        let mut rest = size >> 4;
+        while rest > 0 {
+            bytes.push(byte | 0x80);
+            byte = (rest & 0x7f) as u8;
+            rest >>= 7;
+        }
+        bytes.push(byte);
+        bytes
+    }
+
+    fn object_id(object_type: &str, content: &[u8]) -> Vec<u8> {
+        ObjectFormat::Sha1
+            .hash_object(object_type, content)
+            .as_bytes()
+            .to_vec()
+    }
+
+    /// Build a pack + idx pair containing `entries` (id, raw entry bytes)
+    fn write_pack(dir: &Path, entries: &[(Vec<u8>, Vec<u8>)]) -> PathBuf {
+        let mut pack = b"PACK".to_vec();
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+
+        let mut index_entries = Vec::new();
+        for (id, bytes) in entries {
+            index_entries.push((id.clone(), pack.len() as u32));
+            pack.extend_from_slice(bytes);
+        }
+        let pack_checksum = Sha1::digest(&pack).to_vec();
+        pack.extend_from_slice(&pack_checksum);
+
+        index_entries.sort();
+        let mut idx = IDX_MAGIC.to_vec();
+        idx.extend_from_slice(&2u32.to_be_bytes());
+        for byte in 0..256usize {
+            let count = index_entries
+                .iter()
+                .filter(|(id, _)| id[0] as usize <= byte)
+                .count() as u32;
+            idx.extend_from_slice(&count.to_be_bytes());
+        }
+        for (id, _) in &index_entries {
+            idx.extend_from_slice(id);
+        }
+        for _ in &index_entries {
+            idx.extend_from_slice(&0u32.to_be_bytes());
+        }
+        for (_, offset) in &index_entries {
+            idx.extend_from_slice(&offset.to_be_bytes());
+        }
+        idx.extend_from_slice(&pack_checksum);
+        let idx_checksum = Sha1::digest(&idx).to_vec();
+        idx.extend_from_slice(&idx_checksum);
+
+        fs::write(dir.join("pack-test.pack"), pack).unwrap();
+        let idx_path = dir.join("pack-test.idx");
+        fs::write(&idx_path, idx).unwrap();
+        idx_path
+    }
+
+    /// Delta turning "Hello, World!" into "Hello, Rust World!"
+    fn sample_delta() -> Vec<u8> {
+        let mut delta = vec![13, 18];
+        delta.extend_from_slice(&[0x90, 7]); // copy 7 bytes from offset 0
+        delta.push(5);
+        delta.extend_from_slice(b"Rust ");
+        delta.extend_from_slice(&[0x91, 7, 6]); // copy 6 bytes from offset 7
+        delta
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let result = apply_delta(b"Hello, World!", &sample_delta()).unwrap();
+        assert_eq!(result, b"Hello, Rust World!");
+
+        // Base size must match the header
+        assert!(apply_delta(b"Hello", &sample_delta()).is_err());
+    }
+
+    #[test]
+    fn test_parse_entry_header() {
+        let header = entry_header(3, 1000);
+        let (type_code, size, used) = parse_entry_header(&header).unwrap();
+        assert_eq!(type_code, 3);
+        assert_eq!(size, 1000);
+        assert_eq!(used, header.len());
+    }
+
+    #[test]
+    fn test_rejects_corrupt_pack_index() {
+        let temp_dir = tempdir().unwrap();
+        let content = b"Hello, World!".to_vec();
+        let mut entry = entry_header(3, content.len());
+        entry.extend(compress(&content));
+        let idx_path = write_pack(temp_dir.path(), &[(object_id("blob", &content), entry)]);
+        let idx = fs::read(&idx_path).unwrap();
+        assert!(PackIndex::parse(&idx, ObjectFormat::Sha1).is_ok());
+        let set_fanout = |idx: &mut Vec<u8>, byte: usize, value: u32| {
+            idx[8 + byte * 4..12 + byte * 4].copy_from_slice(&value.to_be_bytes());
+        };
+
+        // A fanout entry larger than a later one
+        let mut corrupt = idx.clone();
+        set_fanout(&mut corrupt, 0, 1);
+        set_fanout(&mut corrupt, 1, 0);
+        let error = PackIndex::parse(&corrupt, ObjectFormat::Sha1).unwrap_err();
+        assert!(error.to_string().contains("non-monotonic"));
+
+        // Fewer objects than the tables in the file hold
+        let mut corrupt = idx.clone();
+        for byte in 0..256 {
+            set_fanout(&mut corrupt, byte, 0);
+        }
+        let error = PackIndex::parse(&corrupt, ObjectFormat::Sha1).unwrap_err();
+        assert!(error.to_string().contains("wrong file size"));
+
+        // More objects than the file has room for
+        let mut corrupt = idx;
+        set_fanout(&mut corrupt, 255, 2);
+        assert!(PackIndex::parse(&corrupt, ObjectFormat::Sha1).is_err());
+    }
+
+    #[test]
+    fn test_read_objects_and_deltas_from_pack() {
+        let temp_dir = tempdir().unwrap();
+        let base = b"Hello, World!".to_vec();
+        let ofs_content = b"Hello, Rust World!".to_vec();
+        let ref_content = b"Hello, Rust World!\n".to_vec();
+
+        let base_id = object_id("blob", &base);
+        let ofs_id = object_id("blob", &ofs_content);
+        let ref_id = object_id("blob", &ref_content);
+
+        // Entry 1: full blob, right after the 12-byte pack header
+        let mut base_entry = entry_header(3, base.len());
+        base_entry.extend(compress(&base));
+
+        // Entry 2: OFS_DELTA pointing back to entry 1
+        let delta = sample_delta();
+        let mut ofs_entry = entry_header(6, delta.len());
+        ofs_entry.push(base_entry.len() as u8);
+        ofs_entry.extend(compress(&delta));
+
+        // Entry 3: REF_DELTA naming entry 1 by object id
+        let mut ref_delta = vec![13, 19, 0x90, 7, 5];
+        ref_delta.extend_from_slice(b"Rust ");
+        ref_delta.extend_from_slice(&[0x91, 7, 6, 1, b'\n']);
+        let mut ref_entry = entry_header(7, ref_delta.len());
+        ref_entry.extend_from_slice(&base_id);
+        ref_entry.extend(compress(&ref_delta));
+
+        let idx_path = write_pack(
+            temp_dir.path(),
+            &[
+                (base_id.clone(), base_entry),
+                (ofs_id.clone(), ofs_entry),
+                (ref_id.clone(), ref_entry),
+            ],
+        );
+
+        let reader = PackReader::open(&idx_path, ObjectFormat::Sha1).unwrap();
+        assert_eq!(reader.index().len(), 3);
+
+        let base_hash = ObjectHash::from_bytes(&base_id).unwrap();
+        let (object_type, content) = reader.read_object(&base_hash).unwrap().unwrap();
+        assert_eq!(object_type, PackObjectType::Blob);
+        assert_eq!(content, base);
+
+        let ofs_hash = ObjectHash::from_bytes(&ofs_id).unwrap();
+        let (object_type, content) = reader.read_object(&ofs_hash).unwrap().unwrap();
+        assert_eq!(object_type, PackObjectType::Blob);
+        assert_eq!(content, ofs_content);
+
+        let ref_hash = ObjectHash::from_bytes(&ref_id).unwrap();
+        let (_, content) = reader.read_object(&ref_hash).unwrap().unwrap();
+        assert_eq!(content, ref_content);
+
+        let missing = ObjectHash::from_hex("0000000000000000000000000000000000000000").unwrap();
+        assert!(reader.read_object(&missing).unwrap().is_none());
+
+        // Both deltas were applied to the same base, which is cached once
+        let cached = reader.delta_bases.borrow_mut().get(&12).cloned();
+        assert_eq!(cached, Some((PackObjectType::Blob, base.clone())));
+        assert_eq!(reader.delta_bases.borrow().len(), 1);
+
+        let uncached = PackReader::open(&idx_path, ObjectFormat::Sha1)
+            .unwrap()
+            .with_delta_base_cache_limit(0);
+        let (_, content) = uncached.read_object(&ref_hash).unwrap().unwrap();
+        assert_eq!(content, ref_content);
+        assert!(uncached.delta_bases.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_object_store_reads_packed_objects() {
+        use crate::domain::objects::GitObject;
+        use crate::infrastructure::ObjectStore;
+
+        let temp_dir = tempdir().unwrap();
+        let objects_dir = temp_dir.path().join("objects");
+        let store = ObjectStore::new(objects_dir.clone());
+        store.init().unwrap();
+
+        let content = b"packed content".to_vec();
+        let id = object_id("blob", &content);
+        let mut entry = entry_header(3, content.len());
+        entry.extend(compress(&content));
+        write_pack(&objects_dir.join("pack"), &[(id.clone(), entry)]);
+
+        let hash = ObjectHash::from_bytes(&id).unwrap();
+        assert!(store.object_exists(&hash));
+        assert_eq!(store.list_objects().unwrap(), vec![hash]);
+
+        match store.load_object(&hash).unwrap() {
+            GitObject::Blob(blob) => assert_eq!(blob.content, content),
+            other => panic!("expected blob, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,690 @@
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::domain::objects::{ObjectFormat, ObjectHash};
+use crate::domain::remote::PackObjectType;
+use crate::infrastructure::lock_file::{create_temp_file, install_read_only};
+
+/// Magic bytes at the start of a version 2 pack index ("\377tOc")
+const IDX_MAGIC: [u8; 4] = [0xff, b't', b'O', b'c'];
+
+/// Block size used to index a delta base
+const DELTA_BLOCK_SIZE: usize = 16;
+
+/// Maximum number of base offsets remembered per block (bounds work on repetitive data)
+const MAX_BLOCK_CANDIDATES: usize = 64;
+
+/// Largest copy a single delta instruction can describe in a version 2 pack
+const MAX_COPY_SIZE: usize = 0x10000;
+
+/// Largest literal insert a single delta instruction can hold
+const MAX_INSERT_SIZE: usize = 0x7f;
+
+/// An object queued for packing
+#[derive(Debug, Clone)]
+pub struct PackInput {
+    pub hash: ObjectHash,
+    /// Commit, tree, blob or tag (never a delta type)
+    pub object_type: PackObjectType,
+    /// Object content without the loose-object header
+    pub data: Vec<u8>,
+    /// Path the object was reached by, used to group similar objects
+    pub path_hint: Option<String>,
+}
+
+impl PackInput {
+    pub fn new(hash: ObjectHash, object_type: PackObjectType, data: Vec<u8>) -> Self {
+        Self {
+            hash,
+            object_type,
+            data,
+            path_hint: None,
+        }
+    }
+
+    pub fn with_path_hint(mut self, path: impl Into<String>) -> Self {
+        self.path_hint = Some(path.into());
+        self
+    }
+}
+
+/// A pack and its index built in memory
+#[derive(Debug, Clone)]
+pub struct PackOutput {
+    /// Complete `.pack` contents including the trailing checksum
+    pub pack: Vec<u8>,
+    /// Complete `.idx` (version 2) contents
+    pub index: Vec<u8>,
+    /// Hash of the pack contents, which also names the pack on disk
+    pub checksum: Vec<u8>,
+    pub object_count: usize,
+    /// Number of objects stored as OFS_DELTA entries
+    pub delta_count: usize,
+}
+
+impl PackOutput {
+    /// Hex form of the pack checksum, as used in `pack-<checksum>.pack`
+    pub fn name(&self) -> String {
+        hex::encode(&self.checksum)
+    }
+}
+
+/// A pack written to an `objects/pack` directory
+#[derive(Debug, Clone)]
+pub struct WrittenPack {
+    pub pack_path: PathBuf,
+    pub idx_path: PathBuf,
+    pub object_count: usize,
+    pub delta_count: usize,
+}
+
+/// Pack Writer - Builds version 2 `.pack` + `.idx` files
+///
+/// Packing turns many small zlib files into one file where similar objects
+/// are stored as deltas against each other:
+///
+/// ```text
+/// 1. Sort objects by type, path and size (largest first), so that versions
+///    of the same file end up next to each other
+/// 2. Slide a window over the sorted list; try each object in the window as
+///    a delta base and keep the smallest delta
+/// 3. Write entries in sorted order (bases always come before their deltas,
+///    so every delta can be an OFS_DELTA pointing backwards)
+/// 4. Append the hash (SHA-1 or SHA-256) of everything written, then build the index
+/// ```
+///
+/// Delta chains are capped at `max_depth` so reading an object never has to
+/// replay an unbounded number of deltas.
+#[derive(Debug, Clone)]
+pub struct PackWriter {
+    window: usize,
+    max_depth: usize,
+    format: ObjectFormat,
+}
+
+impl Default for PackWriter {
+    fn default() -> Self {
+        Self {
+            window: 10,
+            max_depth: 50,
+            format: ObjectFormat::Sha1,
+        }
+    }
+}
+
+impl PackWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of preceding objects tried as delta bases (0 disables deltas)
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Maximum length of a delta chain
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Object format of the ids being packed (also used for checksums)
+    pub fn with_format(mut self, format: ObjectFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Build a pack and its index in memory
+    pub fn build(&self, objects: Vec<PackInput>) -> crate::Result<PackOutput> {
+        let mut seen = HashSet::new();
+        let mut objects: Vec<PackInput> = objects
+            .into_iter()
+            .filter(|object| seen.insert(object.hash))
+            .collect();
+
+        for object in &objects {
+            if object.object_type.is_delta() {
+                return Err(format!("Cannot pack {} as a delta entry", object.hash).into());
+            }
+        }
+
+        objects.sort_by(|a, b| {
+            (a.object_type as u8)
+                .cmp(&(b.object_type as u8))
+                .then_with(|| path_sort_key(a).cmp(&path_sort_key(b)))
+                .then_with(|| b.data.len().cmp(&a.data.len()))
+        });
+
+        let bases = self.find_deltas(&objects);
+
+        let mut pack = b"PACK".to_vec();
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+
+        let mut offsets = Vec::with_capacity(objects.len());
+        let mut index_entries = Vec::with_capacity(objects.len());
+        let mut delta_count = 0;
+
+        for (position, object) in objects.iter().enumerate() {
+            let offset = pack.len() as u64;
+            let mut entry = Vec::new();
+
+            match &bases[position] {
+                Some((base, delta)) => {
+                    write_entry_header(&mut entry, PackObjectType::OfsDelta as u8, delta.len());
+                    write_ofs_distance(&mut entry, offset - offsets[*base]);
+                    entry.extend(compress(delta)?);
+                    delta_count += 1;
+                }
+                None => {
+                    write_entry_header(&mut entry, object.object_type as u8, object.data.len());
+                    entry.extend(compress(&object.data)?);
+                }
+            }
+
+            let mut crc = Crc::new();
+            crc.update(&entry);
+            pack.extend_from_slice(&entry);
+
+            offsets.push(offset);
+            let id = object.hash.as_bytes();
+            if id.len() != self.format.raw_len() {
+                return Err(format!("Invalid {} object id: {}", self.format, object.hash).into());
+            }
+            index_entries.push((id.to_vec(), crc.sum(), offset));
+        }
+
+        let checksum = self.format.digest(&pack);
+        pack.extend_from_slice(&checksum);
+
+        let index = build_index(index_entries, &checksum, self.format);
+
+        Ok(PackOutput {
+            pack,
+            index,
+            checksum,
+            object_count: objects.len(),
+            delta_count,
+        })
+    }
+
+    /// Build a pack and write it as `pack-<checksum>.pack`/`.idx` into `pack_dir`
+    ///
+    /// Both files are written under temporary names first; the `.pack` is
+    /// renamed before the `.idx` so readers never see an index without its
+    /// pack. Like loose objects, finished packs are read-only.
+    pub fn write(&self, objects: Vec<PackInput>, pack_dir: &Path) -> crate::Result<WrittenPack> {
+        let output = self.build(objects)?;
+        fs::create_dir_all(pack_dir)?;
+
+        let name = output.name();
+        let pack_path = pack_dir.join(format!("pack-{}.pack", name));
+        let idx_path = pack_dir.join(format!("pack-{}.idx", name));
+
+        let tmp_pack = Self::write_temp(pack_dir, "tmp_pack", &output.pack)?;
+        let tmp_idx = match Self::write_temp(pack_dir, "tmp_idx", &output.index) {
+            Ok(path) => path,
+            Err(e) => {
+                let _ = fs::remove_file(&tmp_pack);
+                return Err(e);
+            }
+        };
+        install_read_only(&tmp_pack, &pack_path)?;
+        install_read_only(&tmp_idx, &idx_path)?;
+
+        Ok(WrittenPack {
+            pack_path,
+            idx_path,
+            object_count: output.object_count,
+            delta_count: output.delta_count,
+        })
+    }
+
+    /// Write `data` to a new temporary file in `dir`, returning its path
+    fn write_temp(dir: &Path, prefix: &str, data: &[u8]) -> crate::Result<PathBuf> {
+        let (path, mut file) = create_temp_file(dir, prefix)?;
+        // Synced before the rename, like loose objects
+        if let Err(e) = file.write_all(data).and_then(|()| file.sync_all()) {
+            let _ = fs::remove_file(&path);
+            return Err(e.into());
+        }
+        Ok(path)
+    }
+
+    /// Pick a delta base for each object from the preceding window
+    ///
+    /// Returns, per object, the position of its base and the delta bytes.
+    fn find_deltas(&self, objects: &[PackInput]) -> Vec<Option<(usize, Vec<u8>)>> {
+        let mut bases: Vec<Option<(usize, Vec<u8>)>> = vec![None; objects.len()];
+        let mut depths = vec![0usize; objects.len()];
+        let mut window: VecDeque<(usize, DeltaIndex)> = VecDeque::new();
+
+        if self.window == 0 || self.max_depth == 0 {
+            return bases;
+        }
+
+        for (position, object) in objects.iter().enumerate() {
+            // A delta must save at least half the object to be worth it
+            let mut max_size = (object.data.len() / 2).saturating_sub(20);
+
+            for (candidate, base_index) in window.iter().rev() {
+                let base = &objects[*candidate];
+                if base.object_type != object.object_type || depths[*candidate] >= self.max_depth {
+                    continue;
+                }
+                // Very different sizes cannot produce a useful delta
+                if base.data.len() < object.data.len() / 32 {
+                    continue;
+                }
+
+                if let Some(delta) = base_index.create_delta(&object.data, max_size) {
+                    max_size = delta.len().saturating_sub(1);
+                    depths[position] = depths[*candidate] + 1;
+                    bases[position] = Some((*candidate, delta));
+                }
+            }
+
+            window.push_back((position, DeltaIndex::new(&object.data)));
+            if window.len() > self.window {
+                window.pop_front();
+            }
+        }
+
+        bases
+    }
+}
+
+/// Group objects by file name first, then by full path
+fn path_sort_key(object: &PackInput) -> (&str, &str) {
+    match &object.path_hint {
+        Some(path) => (path.rsplit('/').next().unwrap_or(path), path.as_str()),
+        None => ("", ""),
+    }
+}
+
+/// Index of the fixed-size blocks of a delta base
+///
+/// Matching target bytes against this index finds regions to copy from the
+/// base; everything else is emitted as literal inserts.
+struct DeltaIndex<'a> {
+    base: &'a [u8],
+    blocks: HashMap<&'a [u8], Vec<usize>>,
+}
+
+impl<'a> DeltaIndex<'a> {
+    fn new(base: &'a [u8]) -> Self {
+        let mut blocks: HashMap<&[u8], Vec<usize>> = HashMap::new();
+        for offset in (0..base.len().saturating_sub(DELTA_BLOCK_SIZE - 1)).step_by(DELTA_BLOCK_SIZE)
+        {
+            let candidates = blocks
+                .entry(&base[offset..offset + DELTA_BLOCK_SIZE])
+                .or_default();
+            if candidates.len() < MAX_BLOCK_CANDIDATES {
+                candidates.push(offset);
+            }
+        }
+        Self { base, blocks }
+    }
+
+    /// Encode `target` as a delta against the base, giving up past `max_size` bytes
+    fn create_delta(&self, target: &[u8], max_size: usize) -> Option<Vec<u8>> {
+        let mut delta = Vec::new();
+        write_delta_size(&mut delta, self.base.len());
+        write_delta_size(&mut delta, target.len());
+
+        let mut pending_start = 0;
+        let mut pos = 0;
+
+        while pos + DELTA_BLOCK_SIZE <= target.len() {
+            let best = self
+                .blocks
+                .get(&target[pos..pos + DELTA_BLOCK_SIZE])
+                .and_then(|candidates| {
+                    candidates
+                        .iter()
+                        .map(|&offset| (offset, self.match_length(offset, &target[pos..])))
+                        .max_by_key(|&(_, length)| length)
+                });
+
+            match best {
+                Some((mut offset, mut length)) => {
+                    // Extend the match backwards over bytes not yet emitted
+                    let mut start = pos;
+                    while start > pending_start
+                        && offset > 0
+                        && self.base[offset - 1] == target[start - 1]
+                    {
+                        start -= 1;
+                        offset -= 1;
+                        length += 1;
+                    }
+
+                    emit_inserts(&mut delta, &target[pending_start..start]);
+                    emit_copies(&mut delta, offset, length);
+                    pos = start + length;
+                    pending_start = pos;
+                }
+                None => pos += 1,
+            }
+
+            if delta.len() > max_size {
+                return None;
+            }
+        }
+
+        emit_inserts(&mut delta, &target[pending_start..]);
+        if delta.len() > max_size {
+            return None;
+        }
+        Some(delta)
+    }
+
+    fn match_length(&self, offset: usize, target: &[u8]) -> usize {
+        self.base[offset..]
+            .iter()
+            .zip(target)
+            .take_while(|(a, b)| a == b)
+            .count()
+    }
+}
+
+/// Create a delta that turns `base` into `target`
+///
+/// The result can always be replayed with `apply_delta`, although it may be
+/// larger than `target` itself when the two have little in common.
+pub fn create_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    DeltaIndex::new(base)
+        .create_delta(target, usize::MAX)
+        .unwrap_or_default()
+}
+
+/// Emit literal insert instructions (at most 127 bytes each)
+fn emit_inserts(delta: &mut Vec<u8>, mut data: &[u8]) {
+    while !data.is_empty() {
+        let chunk = data.len().min(MAX_INSERT_SIZE);
+        delta.push(chunk as u8);
+        delta.extend_from_slice(&data[..chunk]);
+        data = &data[chunk..];
+    }
+}
+
+/// Emit copy instructions, only writing the non-zero offset/size bytes
+fn emit_copies(delta: &mut Vec<u8>, mut offset: usize, mut length: usize) {
+    while length > 0 {
+        let chunk = length.min(MAX_COPY_SIZE);
+        let mut opcode = 0x80u8;
+        let mut operands = Vec::new();
+
+        for bit in 0..4 {
+            let byte = (offset >> (8 * bit)) as u8;
+            if byte != 0 {
+                opcode |= 1 << bit;
+                operands.push(byte);
+            }
+        }
+        // A size of 0x10000 is encoded as zero (no size bytes at all)
+        if chunk != MAX_COPY_SIZE {
+            for bit in 0..3 {
+                let byte = (chunk >> (8 * bit)) as u8;
+                if byte != 0 {
+                    opcode |= 0x10 << bit;
+                    operands.push(byte);
+                }
+            }
+        }
+
+        delta.push(opcode);
+        delta.extend(operands);
+        offset += chunk;
+        length -= chunk;
+    }
+}
+
+/// Write a little-endian base-128 size for a delta header
+fn write_delta_size(delta: &mut Vec<u8>, mut size: usize) {
+    loop {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size == 0 {
+            delta.push(byte);
+            break;
+        }
+        delta.push(byte | 0x80);
+    }
+}
+
+/// Write a pack entry header: 3-bit type and variable-length size
+fn write_entry_header(entry: &mut Vec<u8>, type_code: u8, size: usize) {
+    let mut byte = (type_code << 4) | (size & 0x0f) as u8;
+    let mut rest = size >> 4;
+    while rest > 0 {
+        entry.push(byte | 0x80);
+        byte = (rest & 0x7f) as u8;
+        rest >>= 7;
+    }
+    entry.push(byte);
+}
+
+/// Write the OFS_DELTA distance (most significant group first, minus one per continuation)
+fn write_ofs_distance(entry: &mut Vec<u8>, mut distance: u64) {
+    let mut bytes = vec![(distance & 0x7f) as u8];
+    distance >>= 7;
+    while distance > 0 {
+        distance -= 1;
+        bytes.push(0x80 | (distance & 0x7f) as u8);
+        distance >>= 7;
+    }
+    bytes.reverse();
+    entry.extend(bytes);
+}
+
+fn compress(data: &[u8]) -> crate::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Build a version 2 pack index from (object id, CRC32, offset) entries
+fn build_index(
+    mut entries: Vec<(Vec<u8>, u32, u64)>,
+    pack_checksum: &[u8],
+    format: ObjectFormat,
+) -> Vec<u8> {
+    entries.sort();
+
+    let mut idx = IDX_MAGIC.to_vec();
+    idx.extend_from_slice(&2u32.to_be_bytes());
+
+    let mut fanout = [0u32; 256];
+    for (id, _, _) in &entries {
+        fanout[id[0] as usize] += 1;
+    }
+    let mut total = 0;
+    for count in fanout {
+        total += count;
+        idx.extend_from_slice(&total.to_be_bytes());
+    }
+
+    for (id, _, _) in &entries {
+        idx.extend_from_slice(id);
+    }
+    for (_, crc, _) in &entries {
+        idx.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    // Offsets that do not fit in 31 bits go to a separate 64-bit table
+    let mut large_offsets = Vec::new();
+    for (_, _, offset) in &entries {
+        if *offset < 0x8000_0000 {
+            idx.extend_from_slice(&(*offset as u32).to_be_bytes());
+        } else {
+            let slot = 0x8000_0000 | large_offsets.len() as u32;
+            idx.extend_from_slice(&slot.to_be_bytes());
+            large_offsets.push(*offset);
+        }
+    }
+    for offset in large_offsets {
+        idx.extend_from_slice(&offset.to_be_bytes());
+    }
+
+    idx.extend_from_slice(pack_checksum);
+    let idx_checksum = format.digest(&idx);
+    idx.extend_from_slice(&idx_checksum);
+
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::pack::{apply_delta, PackReader};
+    use tempfile::tempdir;
+
+    fn blob(content: &[u8]) -> PackInput {
+        PackInput::new(
+            ObjectFormat::Sha1.hash_object("blob", content),
+            PackObjectType::Blob,
+            content.to_vec(),
+        )
+    }
+
+    #[test]
+    fn test_create_delta_round_trip() {
+        let base: Vec<u8> = (0..4000u32)
+            .flat_map(|i| i.to_string().into_bytes())
+            .collect();
+        let mut target = base.clone();
+        target.splice(100..120, b"a small edit".iter().copied());
+        target.extend_from_slice(b"and an appended tail");
+
+        let delta = create_delta(&base, &target);
+        assert!(delta.len() < target.len() / 10);
+        assert_eq!(apply_delta(&base, &delta).unwrap(), target);
+
+        // Unrelated data still round-trips (as inserts)
+        let delta = create_delta(b"abc", b"something else entirely");
+        assert_eq!(
+            apply_delta(b"abc", &delta).unwrap(),
+            b"something else entirely"
+        );
+    }
+
+    #[test]
+    fn test_ofs_distance_encoding() {
+        for distance in [1u64, 127, 128, 16511, 16512, 1 << 40] {
+            let mut bytes = Vec::new();
+            write_ofs_distance(&mut bytes, distance);
+
+            // Decode with the same rules as the reader
+            let mut value = (bytes[0] & 0x7f) as u64;
+            for byte in &bytes[1..] {
+                value = ((value + 1) << 7) | (byte & 0x7f) as u64;
+            }
+            assert_eq!(value, distance);
+        }
+    }
+
+    #[test]
+    fn test_written_pack_can_be_read_back() {
+        let temp_dir = tempdir().unwrap();
+
+        let base: Vec<u8> = (0..2000u32)
+            .flat_map(|i| format!("line {}\n", i).into_bytes())
+            .collect();
+        let mut edited = base.clone();
+        edited.extend_from_slice(b"one more line\n");
+        let mut edited_again = edited.clone();
+        edited_again.extend_from_slice(b"and another\n");
+
+        let objects = vec![
+            blob(&base).with_path_hint("src/lib.rs"),
+            blob(&edited).with_path_hint("src/lib.rs"),
+            blob(&edited_again).with_path_hint("src/lib.rs"),
+            blob(b"tiny").with_path_hint("README"),
+            blob(b"tiny").with_path_hint("README"),
+        ];
+
+        let written = PackWriter::new().write(objects, temp_dir.path()).unwrap();
+        assert_eq!(written.object_count, 4);
+        assert_eq!(written.delta_count, 2);
+        assert!(written.pack_path.exists());
+
+        let reader = PackReader::open(&written.idx_path, ObjectFormat::Sha1).unwrap();
+        assert_eq!(reader.index().len(), 4);
+        for content in [&base, &edited, &edited_again, &b"tiny".to_vec()] {
+            let hash = ObjectFormat::Sha1.hash_object("blob", content);
+            let (object_type, data) = reader.read_object(&hash).unwrap().unwrap();
+            assert_eq!(object_type, PackObjectType::Blob);
+            assert_eq!(&data, content);
+        }
+    }
+
+    #[test]
+    fn test_sha256_pack_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let format = ObjectFormat::Sha256;
+
+        let base: Vec<u8> = (0..1000u32)
+            .flat_map(|i| format!("row {}\n", i).into_bytes())
+            .collect();
+        let mut edited = base.clone();
+        edited.extend_from_slice(b"appended\n");
+
+        let objects = [&base, &edited]
+            .iter()
+            .map(|data| {
+                PackInput::new(
+                    format.hash_object("blob", data),
+                    PackObjectType::Blob,
+                    data.to_vec(),
+                )
+            })
+            .collect();
+
+        let written = PackWriter::new()
+            .with_format(format)
+            .write(objects, temp_dir.path())
+            .unwrap();
+        assert_eq!(written.delta_count, 1);
+
+        let reader = PackReader::open(&written.idx_path, format).unwrap();
+        for content in [&base, &edited] {
+            let hash = format.hash_object("blob", content);
+            let (_, data) = reader.read_object(&hash).unwrap().unwrap();
+            assert_eq!(&data, content);
+        }
+
+        // SHA-1 ids do not belong in a SHA-256 pack
+        let sha1_input = blob(b"tiny");
+        assert!(PackWriter::new()
+            .with_format(format)
+            .build(vec![sha1_input])
+            .is_err());
+    }
+
+    #[test]
+    fn test_delta_depth_is_limited() {
+        let mut content: Vec<u8> = (0..500u32)
+            .flat_map(|i| format!("{}\n", i).into_bytes())
+            .collect();
+        let mut objects = Vec::new();
+        for i in 0..5 {
+            content.extend_from_slice(format!("version {}\n", i).as_bytes());
+            objects.push(blob(&content));
+        }
+
+        let output = PackWriter::new()
+            .with_max_depth(1)
+            .build(objects.clone())
+            .unwrap();
+        assert!(output.delta_count <= 4);
+
+        let output = PackWriter::new().with_window(0).build(objects).unwrap();
+        assert_eq!(output.delta_count, 0);
+    }
+}
@@ -1,8 +1,9 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::domain::objects::ObjectHash;
+use crate::domain::objects::{ObjectFormat, ObjectHash};
 use crate::domain::references::*;
+use crate::infrastructure::lock_file::LockFile;
 
 /// Reference Storage Implementation
 ///
@@ -11,6 +12,13 @@ use crate::domain::references::*;
 /// - .git/refs/heads/* for branches
 /// - .git/refs/tags/* for tags  
 /// - .git/HEAD for the current branch/commit
+///
+/// Real Git may also move references into a single `.git/packed-refs` file
+/// (e.g. after `git gc`). Loose reference files always take precedence over
+/// packed entries with the same name.
+///
+/// Every file is replaced through a `<name>.lock` file (see [`LockFile`]), so
+/// a ref is never seen half-written and two writers cannot update it at once.
 pub struct RefStore {
     refs_dir: PathBuf,
     git_dir: PathBuf,
@@ -47,6 +55,15 @@ impl RefStore {
             self.load_refs_from_dir(&tags_dir, &mut ref_manager, RefType::Tag)?;
         }
 
+        // Add packed references that have no loose file
+        for (full_name, hash) in self.read_packed_refs()? {
+            if let Some((name, ref_type)) = Self::split_ref_name(&full_name) {
+                if ref_manager.find_ref(&full_name).is_none() {
+                    ref_manager.add_ref(GitRef::new(name.to_string(), hash, ref_type));
+                }
+            }
+        }
+
         // Load HEAD reference
         ref_manager.head = self.load_head()?;
 
@@ -71,16 +88,7 @@ impl RefStore {
     /// Save a single reference
     pub fn save_ref(&self, git_ref: &GitRef) -> crate::Result<()> {
         let ref_path = self.get_ref_path(git_ref);
-
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = ref_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        // Write the hash to the reference file
-        fs::write(&ref_path, format!("{}\n", git_ref.hash))?;
-
-        Ok(())
+        LockFile::write(&ref_path, format!("{}\n", git_ref.hash).as_bytes())
     }
 
     /// Load a single reference
@@ -92,22 +100,182 @@ impl RefStore {
         };
 
         if !ref_path.exists() {
-            return Ok(None);
+            // Fall back to packed-refs
+            let full_name = GitRef::new(
+                ref_name.to_string(),
+                ObjectHash::null(ObjectFormat::default()),
+                ref_type,
+            )
+            .full_name();
+            let packed = self
+                .read_packed_refs()?
+                .into_iter()
+                .find(|(name, _)| *name == full_name)
+                .map(|(_, hash)| GitRef::new(ref_name.to_string(), hash, ref_type));
+            return Ok(packed);
         }
 
         let content = fs::read_to_string(&ref_path)?;
-        let hash = ObjectHash::new(content.trim().to_string());
+        let hash = ObjectHash::from_hex(content.trim())?;
 
         Ok(Some(GitRef::new(ref_name.to_string(), hash, ref_type)))
     }
 
-    /// Delete a reference
+    /// Read the `packed-refs` file as (full ref name, hash) pairs
+    ///
+    /// Format:
+    /// ```text
+    /// # pack-refs with: peeled fully-peeled sorted
+    /// 4b825dc642cb6eb9a060e54bf8d69288fbee4904 refs/heads/main
+    /// ^<peeled hash>   (peeled target of the annotated tag above)
+    /// ```
+    pub fn read_packed_refs(&self) -> crate::Result<Vec<(String, ObjectHash)>> {
+        let packed_path = self.git_dir.join("packed-refs");
+        if !packed_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&packed_path)?;
+        let refs = content
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .filter_map(|line| line.split_once(' '))
+            .map(|(hash, name)| Ok((name.trim().to_string(), ObjectHash::from_hex(hash)?)))
+            .collect::<crate::Result<_>>()?;
+
+        Ok(refs)
+    }
+
+    /// Read a reference by its full name (e.g. `refs/heads/main`), loose or packed
+    pub fn read_ref(&self, full_name: &str) -> crate::Result<Option<ObjectHash>> {
+        let ref_path = self.git_dir.join(full_name);
+        if ref_path.is_file() {
+            let content = fs::read_to_string(&ref_path)?;
+            return Ok(Some(ObjectHash::from_hex(content.trim())?));
+        }
+
+        Ok(self
+            .read_packed_refs()?
+            .into_iter()
+            .find(|(name, _)| name == full_name)
+            .map(|(_, hash)| hash))
+    }
+
+    /// Write a reference by its full name, in any namespace under `refs/`
+    pub fn write_ref(&self, full_name: &str, hash: &ObjectHash) -> crate::Result<()> {
+        let name = full_name
+            .strip_prefix("refs/")
+            .filter(|name| GitRef::is_valid_name(name))
+            .ok_or_else(|| format!("'{}' is not a valid ref name", full_name))?;
+
+        let ref_path = self.refs_dir.join(name);
+        LockFile::write(&ref_path, format!("{}\n", hash).as_bytes())
+    }
+
+    /// List every reference under `refs/` as (full name, hash) pairs
+    ///
+    /// Unlike `load_refs`, this includes namespaces other than branches and
+    /// tags (e.g. `refs/remotes/`, `refs/lost-found/`). Loose files shadow
+    /// packed entries with the same name.
+    pub fn list_all_refs(&self) -> crate::Result<Vec<(String, ObjectHash)>> {
+        self.list_raw_refs()?
+            .into_iter()
+            .map(|(name, value)| {
+                let hash = ObjectHash::from_hex(&value)
+                    .map_err(|_| format!("{}: invalid object id '{}'", name, value))?;
+                Ok((name, hash))
+            })
+            .collect()
+    }
+
+    /// Like `list_all_refs`, but with each value as written in the ref file
+    ///
+    /// Nothing is parsed, so a ref holding garbage is listed instead of
+    /// failing the whole listing; `fsck` uses this to report it.
+    pub fn list_raw_refs(&self) -> crate::Result<Vec<(String, String)>> {
+        let mut names = Vec::new();
+        if self.refs_dir.exists() {
+            self.collect_ref_names(&self.refs_dir, &mut names, "refs".to_string())?;
+        }
+
+        let mut refs = Vec::new();
+        for name in names {
+            let content = fs::read_to_string(self.git_dir.join(&name))?;
+            // Symbolic refs such as refs/remotes/origin/HEAD name no object themselves
+            if content.starts_with("ref: ") {
+                continue;
+            }
+            refs.push((name, content.trim().to_string()));
+        }
+
+        for (name, hash) in self.read_packed_refs()? {
+            if !refs.iter().any(|(existing, _)| *existing == name) {
+                refs.push((name, hash.to_hex()));
+            }
+        }
+
+        refs.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(refs)
+    }
+
+    /// Remove a reference (and its peeled `^` line) from `packed-refs`
+    fn remove_packed_ref(&self, full_name: &str) -> crate::Result<()> {
+        let packed_path = self.git_dir.join("packed-refs");
+        if !packed_path.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&packed_path)?;
+        let mut kept = String::new();
+        let mut removing = false;
+
+        for line in content.lines() {
+            if line.starts_with('^') {
+                if removing {
+                    continue;
+                }
+            } else {
+                removing = line
+                    .split_once(' ')
+                    .is_some_and(|(_, name)| name.trim() == full_name);
+                if removing {
+                    continue;
+                }
+            }
+            kept.push_str(line);
+            kept.push('\n');
+        }
+
+        if kept != content {
+            LockFile::write(&packed_path, kept.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Split a full reference name into its short name and type
+    fn split_ref_name(full_name: &str) -> Option<(&str, RefType)> {
+        if let Some(name) = full_name.strip_prefix("refs/heads/") {
+            Some((name, RefType::Branch))
+        } else if let Some(name) = full_name.strip_prefix("refs/tags/") {
+            Some((name, RefType::Tag))
+        } else {
+            full_name
+                .strip_prefix("refs/remotes/")
+                .map(|name| (name, RefType::RemoteBranch))
+        }
+    }
+
+    /// Delete a reference (both the loose file and any packed entry)
     pub fn delete_ref(&self, git_ref: &GitRef) -> crate::Result<()> {
         let ref_path = self.get_ref_path(git_ref);
 
+        // Hold the ref's lock so nobody updates it while it is deleted
+        let lock = LockFile::acquire(&ref_path)?;
         if ref_path.exists() {
             fs::remove_file(&ref_path)?;
         }
+        self.remove_packed_ref(&git_ref.full_name())?;
+        drop(lock);
 
         // Try to remove empty parent directories
         if let Some(parent) = ref_path.parent() {
@@ -120,9 +288,7 @@ impl RefStore {
     /// Save HEAD reference
     pub fn save_head(&self, head: &HeadRef) -> crate::Result<()> {
         let head_path = self.git_dir.join("HEAD");
-        let content = format!("{}\n", head);
-        fs::write(&head_path, content)?;
-        Ok(())
+        LockFile::write(&head_path, format!("{}\n", head).as_bytes())
     }
 
     /// Load HEAD reference
@@ -142,7 +308,7 @@ impl RefStore {
             Ok(Some(HeadRef::Symbolic(ref_name.to_string())))
         } else {
             // Direct reference to commit hash
-            Ok(Some(HeadRef::Direct(ObjectHash::new(content.to_string()))))
+            Ok(Some(HeadRef::Direct(ObjectHash::from_hex(content)?)))
         }
     }
 
@@ -220,10 +386,13 @@ impl RefStore {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_file() {
+            if path.is_file() && !Self::is_lock_file(&path) {
                 // Read the hash from the file
                 let content = fs::read_to_string(&path)?;
-                let hash = ObjectHash::new(content.trim().to_string());
+                if content.starts_with("ref: ") {
+                    continue;
+                }
+                let hash = ObjectHash::from_hex(content.trim())?;
 
                 // Get the reference name (relative to refs/heads or refs/tags)
                 let ref_name = path
@@ -249,6 +418,12 @@ impl RefStore {
         Ok(())
     }
 
+    /// `<ref>.lock` files belong to a writer updating `<ref>`, they are not refs
+    fn is_lock_file(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension == "lock")
+    }
+
     /// Recursively collect reference names from a directory
     #[allow(clippy::only_used_in_recursion)]
     fn collect_ref_names(
@@ -270,7 +445,9 @@ impl RefStore {
             };
 
             if path.is_file() {
-                names.push(full_name);
+                if !Self::is_lock_file(&path) {
+                    names.push(full_name);
+                }
             } else if path.is_dir() {
                 self.collect_ref_names(&path, names, full_name)?;
             }
@@ -318,7 +495,7 @@ impl RefStore {
         // If this is the current branch, update HEAD to point to it
         if let Some(current_branch) = self.get_current_branch()? {
             if current_branch == git_ref.name && git_ref.ref_type == RefType::Branch {
-                let head = HeadRef::symbolic(&format!("refs/heads/{}", git_ref.name));
+                let head = HeadRef::symbolic(&git_ref.name);
                 self.save_head(&head)?;
             }
         }
@@ -338,8 +515,8 @@ mod tests {
         let store = RefStore::new(temp_dir.path().to_path_buf());
         store.init().unwrap();
 
-        let hash = ObjectHash::new("1234567890abcdef1234567890abcdef12345678".to_string());
-        let branch = store.create_branch("main", hash.clone()).unwrap();
+        let hash = ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap();
+        let branch = store.create_branch("main", hash).unwrap();
 
         let loaded = store.load_ref("main", RefType::Branch).unwrap().unwrap();
         assert_eq!(branch, loaded);
@@ -359,21 +536,44 @@ mod tests {
         assert_eq!(head, loaded_head);
 
         // Test direct HEAD
-        let hash = ObjectHash::new("abcdef1234567890abcdef1234567890abcdef12".to_string());
-        let direct_head = HeadRef::direct(hash.clone());
+        let hash = ObjectHash::from_hex("abcdef1234567890abcdef1234567890abcdef12").unwrap();
+        let direct_head = HeadRef::direct(hash);
         store.save_head(&direct_head).unwrap();
         let loaded_direct = store.load_head().unwrap().unwrap();
         assert_eq!(direct_head, loaded_direct);
     }
 
+    #[test]
+    fn test_store_ref_keeps_head_on_current_branch() {
+        let temp_dir = tempdir().unwrap();
+        let store = RefStore::new(temp_dir.path().to_path_buf());
+        store.init().unwrap();
+        store.set_head_to_branch("main").unwrap();
+
+        // Committing on the current branch must not rewrite HEAD as
+        // refs/heads/refs/heads/main
+        for hex in [
+            "1234567890abcdef1234567890abcdef12345678",
+            "abcdef1234567890abcdef1234567890abcdef12",
+        ] {
+            let hash = ObjectHash::from_hex(hex).unwrap();
+            let branch = GitRef::new("main".to_string(), hash, RefType::Branch);
+            store.store_ref(&branch).unwrap();
+
+            assert_eq!(store.load_head().unwrap(), Some(HeadRef::symbolic("main")));
+            assert_eq!(store.get_current_branch().unwrap().as_deref(), Some("main"));
+            assert_eq!(store.get_head().unwrap(), Some(hash));
+        }
+    }
+
     #[test]
     fn test_list_branches() {
         let temp_dir = tempdir().unwrap();
         let store = RefStore::new(temp_dir.path().to_path_buf());
         store.init().unwrap();
 
-        let hash = ObjectHash::new("1234567890abcdef1234567890abcdef12345678".to_string());
-        store.create_branch("main", hash.clone()).unwrap();
+        let hash = ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap();
+        store.create_branch("main", hash).unwrap();
         store.create_branch("develop", hash).unwrap();
 
         let branches = store.list_branches().unwrap();
@@ -381,4 +581,63 @@ mod tests {
         assert!(branches.contains(&"main".to_string()));
         assert!(branches.contains(&"develop".to_string()));
     }
+
+    #[test]
+    fn test_load_packed_refs() {
+        let temp_dir = tempdir().unwrap();
+        let store = RefStore::new(temp_dir.path().to_path_buf());
+        store.init().unwrap();
+
+        fs::write(
+            temp_dir.path().join("packed-refs"),
+            "# pack-refs with: peeled fully-peeled sorted\n\
+             1234567890abcdef1234567890abcdef12345678 refs/heads/main\n\
+             abcdef1234567890abcdef1234567890abcdef12 refs/tags/v1.0\n\
+             ^1234567890abcdef1234567890abcdef12345678\n",
+        )
+        .unwrap();
+
+        let main = store.load_ref("main", RefType::Branch).unwrap().unwrap();
+        assert_eq!(
+            main.hash.to_hex(),
+            "1234567890abcdef1234567890abcdef12345678"
+        );
+
+        // A loose ref overrides the packed entry
+        let loose = ObjectHash::from_hex("fedcba0987654321fedcba0987654321fedcba09").unwrap();
+        store.create_branch("main", loose).unwrap();
+
+        let refs = store.load_refs().unwrap();
+        assert_eq!(refs.find_ref("refs/heads/main").unwrap().hash, loose);
+        assert_eq!(refs.tags().len(), 1);
+    }
+
+    #[test]
+    fn test_held_lock_blocks_ref_updates() {
+        let temp_dir = tempdir().unwrap();
+        let store = RefStore::new(temp_dir.path().to_path_buf());
+        store.init().unwrap();
+
+        let old = ObjectHash::from_hex("1234567890abcdef1234567890abcdef12345678").unwrap();
+        let new = ObjectHash::from_hex("abcdef1234567890abcdef1234567890abcdef12").unwrap();
+        store.write_ref("refs/heads/main", &old).unwrap();
+
+        // Another writer is in the middle of updating main
+        let lock = LockFile::acquire(&temp_dir.path().join("refs/heads/main")).unwrap();
+        let error = store.write_ref("refs/heads/main", &new).unwrap_err();
+        assert!(error.to_string().contains("File exists"), "{}", error);
+        let branch = store.load_ref("main", RefType::Branch).unwrap().unwrap();
+        assert!(store.delete_ref(&branch).is_err());
+
+        // Its lock file is not mistaken for a ref named "main.lock"
+        assert_eq!(store.list_branches().unwrap(), vec!["main".to_string()]);
+        assert_eq!(
+            store.list_all_refs().unwrap(),
+            vec![("refs/heads/main".to_string(), old)]
+        );
+
+        drop(lock);
+        store.write_ref("refs/heads/main", &new).unwrap();
+        assert_eq!(store.read_ref("refs/heads/main").unwrap(), Some(new));
+    }
 }
@@ -1,6 +1,11 @@
-use crate::domain::{PackFile, PackHeader, RemoteRepository};
+use crate::domain::{
+    ObjectFormat, ObjectHash, PackFile, PackHeader, PackObject, PackObjectType, RemoteRepository,
+};
+use crate::infrastructure::pack::{apply_delta, parse_pack_entry, RawPackEntry};
 use anyhow::{anyhow, Result};
 use reqwest::blocking::Client;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
 use url::Url;
 
 /// HTTP client for communicating with remote Git repositories
@@ -178,12 +183,114 @@ impl RemoteClient {
             version, object_count
         );
 
-        // For now, return empty objects list - full pack parsing is complex
-        // In a real implementation, we would parse each object from the pack data
-        let objects = Vec::new();
+        // Decode every entry. Offsets are relative to the start of the pack,
+        // which is what OFS_DELTA distances are measured against.
+        let mut entries: Vec<(u64, RawPackEntry)> = Vec::with_capacity(object_count as usize);
+        let mut pos = 12;
+        for _ in 0..object_count {
+            if pos >= pack_data.len() {
+                return Err(anyhow!(
+                    "Truncated pack file: expected {} objects",
+                    object_count
+                ));
+            }
+            let parsed = parse_pack_entry(&pack_data[pos..], pos as u64, ObjectFormat::Sha1)
+                .map_err(|e| anyhow!("Failed to parse pack entry at offset {}: {}", pos, e))?;
+            entries.push((pos as u64, parsed.entry));
+            pos += parsed.consumed;
+        }
+
+        // The pack ends with a SHA-1 of everything before it, and nothing else
+        let trailer = &pack_data[pos..];
+        if trailer.len() < 20 {
+            return Err(anyhow!("Truncated pack file: missing checksum"));
+        }
+        if trailer.len() > 20 {
+            return Err(anyhow!(
+                "Pack file has {} unexpected byte(s) after its checksum",
+                trailer.len() - 20
+            ));
+        }
+        if Sha1::digest(&pack_data[..pos])[..] != *trailer {
+            return Err(anyhow!("Pack checksum mismatch"));
+        }
+
+        let objects = Self::resolve_pack_entries(entries)?;
 
         Ok(PackFile { header, objects })
     }
+
+    /// Resolve delta entries against their bases and compute object hashes
+    ///
+    /// OFS_DELTA bases are found by pack offset and REF_DELTA bases by object
+    /// id. Bases may themselves be deltas, so entries are resolved in passes
+    /// until nothing more can be resolved.
+    fn resolve_pack_entries(entries: Vec<(u64, RawPackEntry)>) -> Result<Vec<PackObject>> {
+        let offset_to_index: HashMap<u64, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, (offset, _))| (*offset, i))
+            .collect();
+
+        let mut resolved: Vec<Option<PackObject>> = vec![None; entries.len()];
+        let mut hash_to_index: HashMap<ObjectHash, usize> = HashMap::new();
+        let mut remaining = entries.len();
+
+        loop {
+            let mut progress = false;
+
+            for (i, (_, entry)) in entries.iter().enumerate() {
+                if resolved[i].is_some() {
+                    continue;
+                }
+
+                let (object_type, data) = match entry.object_type {
+                    PackObjectType::OfsDelta | PackObjectType::RefDelta => {
+                        let base_index = match (&entry.base_offset, &entry.base_hash) {
+                            (Some(offset), _) => offset_to_index.get(offset).copied(),
+                            (_, Some(hash)) => hash_to_index.get(hash).copied(),
+                            _ => None,
+                        };
+                        let base = match base_index.and_then(|b| resolved[b].as_ref()) {
+                            Some(base) => base,
+                            None => continue, // Base not resolved yet
+                        };
+                        let data = apply_delta(&base.data, &entry.data)
+                            .map_err(|e| anyhow!("Failed to apply delta: {}", e))?;
+                        (base.object_type, data)
+                    }
+                    object_type => (object_type, entry.data.clone()),
+                };
+
+                let type_name = object_type
+                    .type_name()
+                    .ok_or_else(|| anyhow!("Delta resolved to another delta"))?;
+                let hash = ObjectFormat::Sha1.hash_object(type_name, &data);
+
+                hash_to_index.insert(hash, i);
+                resolved[i] = Some(PackObject {
+                    object_type,
+                    size: data.len() as u64,
+                    data,
+                    hash: Some(hash),
+                });
+                remaining -= 1;
+                progress = true;
+            }
+
+            if remaining == 0 {
+                break;
+            }
+            if !progress {
+                return Err(anyhow!(
+                    "Pack has {} delta(s) whose base object is missing",
+                    remaining
+                ));
+            }
+        }
+
+        Ok(resolved.into_iter().flatten().collect())
+    }
 }
 
 impl Default for RemoteClient {
@@ -218,21 +325,123 @@ mod tests {
         assert!(remote.refs.contains_key("refs/heads/develop"));
     }
 
+    /// Build a pack from raw entry bytes, appending the trailing checksum
+    fn build_pack(entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut pack = vec![b'P', b'A', b'C', b'K'];
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for entry in entries {
+            pack.extend_from_slice(entry);
+        }
+        let checksum = Sha1::digest(&pack);
+        pack.extend_from_slice(&checksum);
+        pack
+    }
+
+    /// Encode a pack entry whose header fits in a single byte
+    fn small_entry(type_code: u8, prefix: &[u8], data: &[u8]) -> Vec<u8> {
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write;
+
+        assert!(data.len() < 16);
+        let mut entry = vec![(type_code << 4) | data.len() as u8];
+        entry.extend_from_slice(prefix);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        entry.extend(encoder.finish().unwrap());
+        entry
+    }
+
     #[test]
     fn test_pack_header_parsing() {
         let client = RemoteClient::new().unwrap();
 
-        // Create a minimal pack file header
-        let mut pack_data = vec![b'P', b'A', b'C', b'K']; // Signature
-        pack_data.extend_from_slice(&2u32.to_be_bytes()); // Version 2
-        pack_data.extend_from_slice(&5u32.to_be_bytes()); // 5 objects
+        // Create an empty pack file (header + checksum)
+        let pack_data = build_pack(&[]);
 
         let result = client.parse_pack_file(&pack_data);
         assert!(result.is_ok());
 
         let pack = result.unwrap();
         assert_eq!(pack.header.version, 2);
-        assert_eq!(pack.header.object_count, 5);
+        assert_eq!(pack.header.object_count, 0);
+        assert!(pack.objects.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_pack_is_rejected() {
+        let client = RemoteClient::new().unwrap();
+
+        // Header claims 5 objects but no entry data follows
+        let mut pack_data = vec![b'P', b'A', b'C', b'K'];
+        pack_data.extend_from_slice(&2u32.to_be_bytes());
+        pack_data.extend_from_slice(&5u32.to_be_bytes());
+
+        assert!(client.parse_pack_file(&pack_data).is_err());
+
+        // All objects present, but the checksum is cut short or missing
+        let pack_data = build_pack(&[small_entry(3, &[], b"Hello")]);
+        for cut in [1, 20] {
+            let truncated = &pack_data[..pack_data.len() - cut];
+            let error = client.parse_pack_file(truncated).unwrap_err();
+            assert!(error.to_string().contains("missing checksum"));
+        }
+
+        // Anything after the checksum is not part of the pack
+        let mut extended = pack_data.clone();
+        extended.extend_from_slice(b"0000");
+        assert!(client.parse_pack_file(&extended).is_err());
+        assert!(client.parse_pack_file(&pack_data).is_ok());
+    }
+
+    #[test]
+    fn test_parse_pack_with_deltas() {
+        let client = RemoteClient::new().unwrap();
+
+        let base = small_entry(3, &[], b"Hello, World!");
+        // Delta: copy "Hello, " then insert "Git"
+        let delta = [13, 10, 0x90, 7, 3, b'G', b'i', b't'];
+        let ofs = small_entry(6, &[base.len() as u8], &delta);
+
+        let base_hash = ObjectFormat::Sha1.hash_object("blob", b"Hello, World!");
+        let base_id = base_hash.as_bytes();
+        // Delta: copy "Hello" then insert "!"
+        let ref_delta = [13, 6, 0x90, 5, 1, b'!'];
+        let refd = small_entry(7, base_id, &ref_delta);
+
+        let pack = client
+            .parse_pack_file(&build_pack(&[base, ofs, refd]))
+            .unwrap();
+        assert_eq!(pack.objects.len(), 3);
+
+        let contents: Vec<&[u8]> = pack.objects.iter().map(|o| &o.data[..]).collect();
+        assert_eq!(
+            contents,
+            vec![&b"Hello, World!"[..], b"Hello, Git", b"Hello!"]
+        );
+
+        for object in &pack.objects {
+            assert_eq!(object.object_type, PackObjectType::Blob);
+            assert_eq!(object.size, object.data.len() as u64);
+            let expected = ObjectFormat::Sha1.hash_object("blob", &object.data);
+            assert_eq!(object.hash, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_parse_pack_with_missing_delta_base() {
+        let client = RemoteClient::new().unwrap();
+
+        // REF_DELTA against an object that is not in the pack (a "thin" pack)
+        let missing_base = [0x11u8; 20];
+        let delta = [1, 1, 1, b'x'];
+        let refd = small_entry(7, &missing_base, &delta);
+
+        let result = client.parse_pack_file(&build_pack(&[refd]));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("base object is missing"));
     }
 
     #[test]
@@ -0,0 +1,410 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::objects::*;
+use crate::domain::revision::{Revision, RevisionRange, RevisionStep};
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+
+/// Shortest abbreviated object id that is looked up
+pub const MIN_ABBREV_LEN: usize = 4;
+
+/// Merge base walk: reachable from the first commit
+const PARENT1: u8 = 1 << 0;
+/// Merge base walk: reachable from the other commits
+const PARENT2: u8 = 1 << 1;
+/// Merge base walk: below a common ancestor already found
+const STALE: u8 = 1 << 2;
+/// Merge base walk: already recorded as a common ancestor
+const RESULT: u8 = 1 << 3;
+
+/// Commits selected by a revision expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevisionSet {
+    /// Commits whose history is selected
+    pub include: Vec<ObjectHash>,
+    /// Commits whose history is left out (printed as `^<id>`)
+    pub exclude: Vec<ObjectHash>,
+}
+
+/// Commits waiting to be painted by the merge base walk, newest committer
+/// date first
+#[derive(Default)]
+struct PaintQueue {
+    heap: BinaryHeap<(DateTime<Utc>, Reverse<usize>)>,
+    commits: HashMap<usize, (ObjectHash, Vec<ObjectHash>)>,
+    queued: usize,
+}
+
+impl PaintQueue {
+    fn push(&mut self, object_store: &ObjectStore, hash: ObjectHash) -> crate::Result<()> {
+        let commit = match object_store.load_object(&hash)? {
+            GitObject::Commit(commit) => commit,
+            _ => return Err(format!("Object {} is not a commit", hash).into()),
+        };
+        // Ties keep insertion order, as in Git's priority queue
+        self.queued += 1;
+        self.heap
+            .push((commit.committer.timestamp, Reverse(self.queued)));
+        self.commits.insert(self.queued, (hash, commit.parents));
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<(ObjectHash, Vec<ObjectHash>)> {
+        let (_, Reverse(order)) = self.heap.pop()?;
+        self.commits.remove(&order)
+    }
+
+    /// Whether any queued commit can still lead to a new merge base
+    fn has_unstale(&self, flags: &HashMap<ObjectHash, u8>) -> bool {
+        self.commits
+            .values()
+            .any(|(hash, _)| flags[hash] & STALE == 0)
+    }
+}
+
+/// Turns revisions typed by a user into object ids
+///
+/// The expression is parsed by [`Revision`]; this resolves its name against
+/// the refs and object database and then applies its steps.
+///
+/// ## Name Lookup Order (same as Git):
+/// 1. A full hexadecimal object id
+/// 2. `HEAD`
+/// 3. `<name>`, `refs/<name>`, `refs/tags/<name>`, `refs/heads/<name>`,
+///    `refs/remotes/<name>`
+/// 4. An abbreviated id of at least [`MIN_ABBREV_LEN`] hex digits
+pub struct RevisionResolver<'a> {
+    object_store: &'a ObjectStore,
+    ref_store: &'a RefStore,
+}
+
+impl<'a> RevisionResolver<'a> {
+    pub fn new(object_store: &'a ObjectStore, ref_store: &'a RefStore) -> Self {
+        Self {
+            object_store,
+            ref_store,
+        }
+    }
+
+    /// Resolve a revision such as `HEAD~2` or `v1.0^{tree}` to an object id
+    ///
+    /// Returns `Ok(None)` when the name matches nothing, and an error when
+    /// the expression is malformed, an abbreviated id is ambiguous or a step
+    /// cannot be taken (e.g. `^2` of a commit with one parent).
+    pub fn resolve(&self, spec: &str) -> crate::Result<Option<ObjectHash>> {
+        self.resolve_revision(&Revision::parse(spec)?)
+    }
+
+    /// Like [`resolve`](Self::resolve), but a name that matches nothing is an error
+    pub fn require(&self, spec: &str) -> crate::Result<ObjectHash> {
+        self.resolve(spec)?
+            .ok_or_else(|| format!("Not a valid object name {}", spec).into())
+    }
+
+    /// Resolve an already parsed revision
+    pub fn resolve_revision(&self, revision: &Revision) -> crate::Result<Option<ObjectHash>> {
+        let Some(mut hash) = self.resolve_name(&revision.name)? else {
+            return Ok(None);
+        };
+
+        for step in &revision.steps {
+            hash = match step {
+                RevisionStep::Ancestor(generations) => {
+                    let mut commit = self.object_store.peel_to_commit(&hash)?;
+                    for _ in 0..*generations {
+                        commit = self.parent(&commit, 1)?;
+                    }
+                    commit
+                }
+                RevisionStep::Parent(0) => self.object_store.peel_to_commit(&hash)?,
+                RevisionStep::Parent(n) => {
+                    self.parent(&self.object_store.peel_to_commit(&hash)?, *n)?
+                }
+                RevisionStep::PeelTo(object_type) => self.peel_to(&hash, object_type)?,
+                RevisionStep::PeelTags => self.object_store.peel(&hash)?.0,
+            };
+        }
+
+        match &revision.path {
+            Some(path) => self.lookup_path(&hash, path).map(Some),
+            None => Ok(Some(hash)),
+        }
+    }
+
+    /// Resolve `<rev>`, `A..B` or `A...B` to the commits to include and exclude
+    ///
+    /// `A..B` includes B and excludes A; `A...B` includes both and excludes
+    /// their merge bases. This is what `rev-parse` prints (excluded ids with
+    /// a leading `^`) and what `log` walks.
+    pub fn resolve_range(&self, spec: &str) -> crate::Result<RevisionSet> {
+        let require = |revision: &Revision| -> crate::Result<ObjectHash> {
+            self.resolve_revision(revision)?
+                .ok_or_else(|| format!("ambiguous argument '{}': unknown revision", spec).into())
+        };
+
+        Ok(match RevisionRange::parse(spec)? {
+            RevisionRange::Single(revision) => RevisionSet {
+                include: vec![require(&revision)?],
+                exclude: vec![],
+            },
+            RevisionRange::Range { exclude, include } => RevisionSet {
+                include: vec![require(&include)?],
+                exclude: vec![require(&exclude)?],
+            },
+            RevisionRange::SymmetricDifference { left, right } => {
+                let left = require(&left)?;
+                let right = require(&right)?;
+                let exclude = self.merge_bases(&left, &right)?;
+                RevisionSet {
+                    include: vec![right, left],
+                    exclude,
+                }
+            }
+        })
+    }
+
+    /// The best common ancestors of two commits
+    ///
+    /// A common ancestor is "best" when no other common ancestor descends
+    /// from it; criss-cross merges can have more than one.
+    pub fn merge_bases(&self, a: &ObjectHash, b: &ObjectHash) -> crate::Result<Vec<ObjectHash>> {
+        let a = self.object_store.peel_to_commit(a)?;
+        let b = self.object_store.peel_to_commit(b)?;
+
+        let mut flags = HashMap::new();
+        let candidates = self.paint_down_to_common(&a, &[b], &mut flags)?;
+        // A candidate found early may turn out to be below a later one
+        let candidates: Vec<ObjectHash> = candidates
+            .into_iter()
+            .filter(|hash| flags[hash] & STALE == 0)
+            .collect();
+        if candidates.len() < 2 {
+            return Ok(candidates);
+        }
+
+        // Drop candidates that are an ancestor of another candidate
+        let mut redundant = vec![false; candidates.len()];
+        for i in 0..candidates.len() {
+            if redundant[i] {
+                continue;
+            }
+            let others: Vec<usize> = (0..candidates.len())
+                .filter(|&j| j != i && !redundant[j])
+                .collect();
+            let tips: Vec<ObjectHash> = others.iter().map(|&j| candidates[j]).collect();
+            let mut flags = HashMap::new();
+            self.paint_down_to_common(&candidates[i], &tips, &mut flags)?;
+            if flags[&candidates[i]] & PARENT2 != 0 {
+                redundant[i] = true;
+            }
+            for j in others {
+                if flags[&candidates[j]] & PARENT1 != 0 {
+                    redundant[j] = true;
+                }
+            }
+        }
+
+        Ok(candidates
+            .into_iter()
+            .zip(redundant)
+            .filter(|(_, redundant)| !redundant)
+            .map(|(hash, _)| hash)
+            .collect())
+    }
+
+    /// Walk down from `one` and `twos` together, newest commit first, and
+    /// return the commits reached from both sides
+    ///
+    /// Commits reached from `one` are painted [`PARENT1`], those reached
+    /// from `twos` [`PARENT2`]. A commit carrying both is a common ancestor,
+    /// and everything below it is painted [`STALE`] as well: it can only be
+    /// a worse merge base. The walk stops once only stale commits are left,
+    /// so it never goes much deeper than the merge bases themselves.
+    fn paint_down_to_common(
+        &self,
+        one: &ObjectHash,
+        twos: &[ObjectHash],
+        flags: &mut HashMap<ObjectHash, u8>,
+    ) -> crate::Result<Vec<ObjectHash>> {
+        let mut queue = PaintQueue::default();
+        *flags.entry(*one).or_default() |= PARENT1;
+        queue.push(self.object_store, *one)?;
+        for two in twos {
+            *flags.entry(*two).or_default() |= PARENT2;
+            queue.push(self.object_store, *two)?;
+        }
+
+        let mut result = Vec::new();
+        while queue.has_unstale(flags) {
+            let Some((hash, parents)) = queue.pop() else {
+                break;
+            };
+
+            let mut paint = flags[&hash] & (PARENT1 | PARENT2 | STALE);
+            if paint == PARENT1 | PARENT2 {
+                let commit_flags = flags.get_mut(&hash).unwrap();
+                if *commit_flags & RESULT == 0 {
+                    *commit_flags |= RESULT;
+                    result.push(hash);
+                }
+                // Mark the parents of a found common ancestor stale
+                paint |= STALE;
+            }
+
+            for parent in parents {
+                let parent_flags = flags.entry(parent).or_default();
+                if *parent_flags & paint == paint {
+                    continue;
+                }
+                *parent_flags |= paint;
+                queue.push(self.object_store, parent)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Every commit reachable from `tips`, the tips included, following all parents
+    pub fn ancestors(&self, tips: &[ObjectHash]) -> crate::Result<HashSet<ObjectHash>> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<ObjectHash> = tips.iter().cloned().collect();
+
+        while let Some(hash) = queue.pop_front() {
+            if !seen.insert(hash) {
+                continue;
+            }
+            if let GitObject::Commit(commit) = self.object_store.load_object(&hash)? {
+                queue.extend(commit.parents);
+            }
+        }
+
+        Ok(seen)
+    }
+
+    /// The shortest prefix of `hash`, at least `min_len` long, naming only it
+    pub fn abbreviate(&self, hash: &ObjectHash, min_len: usize) -> crate::Result<String> {
+        let full = hash.to_hex();
+        let others: Vec<ObjectHash> = self
+            .object_store
+            .list_objects()?
+            .into_iter()
+            .filter(|other| other != hash)
+            .collect();
+
+        let mut len = min_len.clamp(MIN_ABBREV_LEN, full.len());
+        while len < full.len()
+            && others
+                .iter()
+                .any(|other| other.starts_with_hex(&full[..len]))
+        {
+            len += 1;
+        }
+
+        Ok(full[..len].to_string())
+    }
+
+    /// The `n`-th (1-based) parent of a commit
+    fn parent(&self, commit: &ObjectHash, n: usize) -> crate::Result<ObjectHash> {
+        match self.object_store.load_object(commit)? {
+            GitObject::Commit(object) => object
+                .parents
+                .get(n - 1)
+                .cloned()
+                .ok_or_else(|| format!("commit {} has no parent #{}", commit, n).into()),
+            other => Err(format!("{} is a {}, not a commit", commit, other.object_type()).into()),
+        }
+    }
+
+    /// Peel tags (and a commit to its tree) until reaching `object_type`
+    fn peel_to(&self, hash: &ObjectHash, object_type: &GitObjectType) -> crate::Result<ObjectHash> {
+        let mut current = *hash;
+        loop {
+            let object = self.object_store.load_object(&current)?;
+            if object.object_type() == *object_type {
+                return Ok(current);
+            }
+            current = match object {
+                GitObject::Tag(tag) => tag.object,
+                GitObject::Commit(commit) if *object_type == GitObjectType::Tree => commit.tree,
+                other => {
+                    return Err(format!(
+                        "{}: expected {} type, but the object dereferences to {} type",
+                        hash,
+                        object_type,
+                        other.object_type()
+                    )
+                    .into())
+                }
+            };
+        }
+    }
+
+    /// Find `path` inside the tree of a tree-ish; an empty path is the tree itself
+    fn lookup_path(&self, treeish: &ObjectHash, path: &str) -> crate::Result<ObjectHash> {
+        let mut current = self.peel_to(treeish, &GitObjectType::Tree)?;
+
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            let tree = match self.object_store.load_object(&current)? {
+                GitObject::Tree(tree) => tree,
+                _ => return Err(format!("path '{}' does not exist", path).into()),
+            };
+            current = tree
+                .entries
+                .into_iter()
+                .find(|entry| entry.name == component)
+                .map(|entry| entry.hash)
+                .ok_or_else(|| format!("path '{}' does not exist", path))?;
+        }
+
+        Ok(current)
+    }
+
+    /// Resolve a bare object name (no steps) to an id that exists in the database
+    ///
+    /// Returns `Ok(None)` when nothing matches and an error when an
+    /// abbreviated id matches more than one object.
+    pub fn resolve_name(&self, name: &str) -> crate::Result<Option<ObjectHash>> {
+        let format = self.object_store.format();
+        let is_hex = name.bytes().all(|b| b.is_ascii_hexdigit());
+
+        if is_hex && name.len() == format.hex_len() {
+            let hash = ObjectHash::from_hex(name)?;
+            return Ok(self.object_store.object_exists(&hash).then_some(hash));
+        }
+
+        if name == "HEAD" {
+            return self.ref_store.get_head();
+        }
+
+        let refs = self.ref_store.list_all_refs()?;
+        for candidate in [
+            name.to_string(),
+            format!("refs/{}", name),
+            format!("refs/tags/{}", name),
+            format!("refs/heads/{}", name),
+            format!("refs/remotes/{}", name),
+        ] {
+            if let Some((_, hash)) = refs.iter().find(|(ref_name, _)| *ref_name == candidate) {
+                return Ok(Some(*hash));
+            }
+        }
+
+        if is_hex && name.len() >= MIN_ABBREV_LEN && name.len() < format.hex_len() {
+            let mut matches = self
+                .object_store
+                .list_objects()?
+                .into_iter()
+                .filter(|hash| hash.starts_with_hex(name));
+            let first = matches.next();
+            if matches.next().is_some() {
+                return Err(format!("short object id {} is ambiguous", name).into());
+            }
+            return Ok(first);
+        }
+
+        Ok(None)
+    }
+}
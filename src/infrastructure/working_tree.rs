@@ -0,0 +1,526 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::domain::index::GitIndex;
+use crate::domain::objects::*;
+use crate::infrastructure::object_store::ObjectStore;
+use crate::infrastructure::ref_store::RefStore;
+
+/// What [`WorkingTree::refresh_index`] found
+#[derive(Debug, Default)]
+pub struct IndexRefresh {
+    /// Mode and id of every tracked path that exists in the working tree
+    pub files: HashMap<PathBuf, (FileMode, ObjectHash)>,
+    /// Whether entries were refreshed or smudged, so the index is worth saving
+    pub changed: bool,
+}
+
+/// Working Tree Access
+///
+/// Translates between files on disk and the (mode, blob) pairs Git stores in
+/// the index and in trees. Everything is looked at with `lstat`, never
+/// following symlinks:
+///
+/// ```text
+/// on disk                         mode     object
+/// regular file                    100644   blob with the file content
+/// file with an exec bit (u+x)     100755   blob with the file content
+/// symlink  link -> ../target      120000   blob containing "../target"
+/// directory with its own .git     160000   commit hash of its HEAD (gitlink)
+/// ```
+///
+/// Checking out reverses this: symlinks are recreated as symlinks, exec
+/// bits are restored, and gitlinks become empty directories (the
+/// submodule's own content lives in another repository).
+pub struct WorkingTree {
+    root: PathBuf,
+}
+
+impl WorkingTree {
+    /// Create a working tree rooted at the repository root
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Git mode of a path, or None if it does not exist
+    ///
+    /// Plain directories are reported as `Directory`; directories that are
+    /// repositories themselves are `Gitlink`.
+    pub fn file_mode(&self, path: &Path) -> std::io::Result<Option<FileMode>> {
+        let path = self.root.join(path);
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let file_type = metadata.file_type();
+        let mode = if file_type.is_symlink() {
+            FileMode::Symlink
+        } else if file_type.is_dir() {
+            // The repository's own root is not a submodule of itself
+            if path != self.root && Self::nested_git_dir(&path).is_some() {
+                FileMode::Gitlink
+            } else {
+                FileMode::Directory
+            }
+        } else if Self::is_executable(&metadata) {
+            FileMode::Executable
+        } else {
+            FileMode::Regular
+        };
+
+        Ok(Some(mode))
+    }
+
+    /// Store a path's content in the object store, returning its mode and id
+    pub fn store_path(
+        &self,
+        object_store: &ObjectStore,
+        path: &Path,
+    ) -> crate::Result<(FileMode, ObjectHash)> {
+        self.read_path(object_store, path, true)
+    }
+
+    /// Calculate the mode and id a path would be staged with, without storing it
+    pub fn hash_path(
+        &self,
+        object_store: &ObjectStore,
+        path: &Path,
+    ) -> crate::Result<(FileMode, ObjectHash)> {
+        self.read_path(object_store, path, false)
+    }
+
+    fn read_path(
+        &self,
+        object_store: &ObjectStore,
+        path: &Path,
+        store: bool,
+    ) -> crate::Result<(FileMode, ObjectHash)> {
+        let mode = self
+            .file_mode(path)?
+            .ok_or_else(|| format!("{} does not exist", path.display()))?;
+        let full_path = self.root.join(path);
+
+        let hash = match mode {
+            FileMode::Regular | FileMode::Executable if store => {
+                object_store.store_blob_from_file(&full_path)?
+            }
+            FileMode::Regular | FileMode::Executable => object_store.hash_file(&full_path)?,
+            FileMode::Symlink => {
+                // The blob holds the link target, not what it points at
+                let blob = GitObject::Blob(BlobObject::new(Self::read_link_target(&full_path)?));
+                if store {
+                    object_store.store_object(&blob)?
+                } else {
+                    object_store.hash_object(&blob)?
+                }
+            }
+            FileMode::Gitlink => Self::submodule_head(&full_path)?,
+            FileMode::Directory => {
+                return Err(format!("{} is a directory", path.display()).into());
+            }
+        };
+
+        Ok((mode, hash))
+    }
+
+    /// Find the mode and id of every tracked path, reading only the files
+    /// whose stat data no longer matches their index entry
+    ///
+    /// ```text
+    /// stat matches, entry older than the index ──▶ trust the entry, no read
+    /// otherwise ──▶ hash the file
+    ///     same mode and id        ──▶ store the new stat data in the entry
+    ///     differs, racily clean   ──▶ smudge the entry (size 0), so its
+    ///                                 stat data can never match again
+    /// ```
+    ///
+    /// Smudging matters once the index is saved: the entry then predates
+    /// the new index file and would otherwise be trusted despite the change.
+    /// Entries marked assume-valid or skip-worktree are trusted without
+    /// looking at the file. Gitlinks are always resolved, as a submodule's
+    /// HEAD can move without its directory changing.
+    pub fn refresh_index(
+        &self,
+        object_store: &ObjectStore,
+        index: &mut GitIndex,
+    ) -> crate::Result<IndexRefresh> {
+        let mut refresh = IndexRefresh::default();
+        let timestamp = index.timestamp;
+
+        for (path, entry) in index.entries.iter_mut() {
+            if entry.assume_valid || entry.skip_worktree {
+                refresh.files.insert(path.clone(), (entry.mode, entry.hash));
+                continue;
+            }
+
+            let metadata = match fs::symlink_metadata(self.root.join(path)) {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let stat_matches = entry.mode != FileMode::Gitlink && entry.stat_matches(&metadata);
+            let racily_clean = entry.is_racily_clean(timestamp);
+            if stat_matches && !racily_clean {
+                refresh.files.insert(path.clone(), (entry.mode, entry.hash));
+                continue;
+            }
+
+            // A file replaced by a directory is gone as far as the index knows
+            let Some(mode) = self.file_mode(path)? else {
+                continue;
+            };
+            if mode == FileMode::Directory {
+                continue;
+            }
+            let (mode, hash) = self.hash_path(object_store, path)?;
+
+            if (mode, hash) == (entry.mode, entry.hash) {
+                if entry.mode != FileMode::Gitlink && (!stat_matches || racily_clean) {
+                    entry.refresh_stat(&metadata);
+                    refresh.changed = true;
+                }
+            } else if stat_matches && entry.size != 0 {
+                entry.size = 0;
+                refresh.changed = true;
+            }
+            refresh.files.insert(path.clone(), (mode, hash));
+        }
+
+        Ok(refresh)
+    }
+
+    /// Delete a tracked path, and the directories that leaves empty
+    ///
+    /// Git does not track directories, so an empty one has nothing left to
+    /// keep. A nested repository is left in place, as its content is not
+    /// tracked here, and so is a directory that now stands where the file was.
+    pub fn remove_path(&self, path: &Path) -> crate::Result<()> {
+        let full_path = self.root.join(path);
+        match self.file_mode(path)? {
+            Some(FileMode::Regular | FileMode::Executable | FileMode::Symlink) => {
+                fs::remove_file(&full_path)?
+            }
+            _ => return Ok(()),
+        }
+
+        let mut dir = full_path.parent();
+        while let Some(current) = dir {
+            if current == self.root || fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+        Ok(())
+    }
+
+    /// The target a symlink points at, as stored in its blob
+    pub fn link_target(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        Self::read_link_target(&self.root.join(path))
+    }
+
+    /// Write one entry to the working tree
+    pub fn checkout_entry(
+        &self,
+        object_store: &ObjectStore,
+        path: &Path,
+        mode: FileMode,
+        hash: &ObjectHash,
+    ) -> crate::Result<()> {
+        let full_path = self.root.join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Replace whatever is there; a symlink must not be written through
+        if let Ok(existing) = fs::symlink_metadata(&full_path) {
+            if existing.is_dir() && mode != FileMode::Gitlink {
+                fs::remove_dir_all(&full_path)?;
+            } else if !existing.is_dir() {
+                fs::remove_file(&full_path)?;
+            }
+        }
+
+        match mode {
+            FileMode::Regular | FileMode::Executable => {
+                object_store.write_blob_to_file(hash, &full_path)?;
+                Self::set_executable(&full_path, mode == FileMode::Executable)?;
+            }
+            FileMode::Symlink => {
+                let target = match object_store.load_object(hash)? {
+                    GitObject::Blob(blob) => blob.content,
+                    _ => return Err(format!("Object {} is not a blob", hash).into()),
+                };
+                Self::create_symlink(&target, &full_path)?;
+            }
+            // Submodule content is checked out by the submodule itself
+            FileMode::Gitlink => fs::create_dir_all(&full_path)?,
+            FileMode::Directory => {
+                return Err(format!("Cannot check out tree {} as a file", hash).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write every entry of a tree (recursively) to the working tree
+    pub fn checkout_tree(
+        &self,
+        object_store: &ObjectStore,
+        tree_hash: &ObjectHash,
+    ) -> crate::Result<usize> {
+        let entries = object_store.flatten_tree(tree_hash)?;
+        for (path, entry) in &entries {
+            self.checkout_entry(object_store, path, entry.mode, &entry.hash)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// The git directory of a nested repository (`.git` dir or `gitdir:` file)
+    fn nested_git_dir(dir: &Path) -> Option<PathBuf> {
+        for name in [".git", ".git-rs"] {
+            let candidate = dir.join(name);
+            if candidate.is_dir() {
+                return Some(candidate);
+            }
+            if let Ok(content) = fs::read_to_string(&candidate) {
+                if let Some(git_dir) = content.trim().strip_prefix("gitdir:") {
+                    return Some(dir.join(git_dir.trim()));
+                }
+            }
+        }
+        None
+    }
+
+    /// The commit a submodule's HEAD points at
+    fn submodule_head(dir: &Path) -> crate::Result<ObjectHash> {
+        let git_dir = Self::nested_git_dir(dir)
+            .ok_or_else(|| format!("{} is not a repository", dir.display()))?;
+        RefStore::new(git_dir)
+            .get_head()?
+            .ok_or_else(|| format!("Submodule {} has no commits yet", dir.display()).into())
+    }
+
+    #[cfg(unix)]
+    fn is_executable(metadata: &fs::Metadata) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o100 != 0
+    }
+
+    #[cfg(not(unix))]
+    fn is_executable(_metadata: &fs::Metadata) -> bool {
+        false
+    }
+
+    #[cfg(unix)]
+    fn set_executable(path: &Path, executable: bool) -> std::io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = if executable { 0o755 } else { 0o644 };
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+
+    #[cfg(not(unix))]
+    fn set_executable(_path: &Path, _executable: bool) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn read_link_target(path: &Path) -> std::io::Result<Vec<u8>> {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(fs::read_link(path)?.as_os_str().as_bytes().to_vec())
+    }
+
+    #[cfg(not(unix))]
+    fn read_link_target(path: &Path) -> std::io::Result<Vec<u8>> {
+        Ok(fs::read_link(path)?
+            .to_string_lossy()
+            .into_owned()
+            .into_bytes())
+    }
+
+    #[cfg(unix)]
+    fn create_symlink(target: &[u8], path: &Path) -> std::io::Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+        std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(target), path)
+    }
+
+    /// Without symlink support the link is checked out as a plain file
+    /// holding the target, like Git does with `core.symlinks=false`
+    #[cfg(not(unix))]
+    fn create_symlink(target: &[u8], path: &Path) -> std::io::Result<()> {
+        fs::write(path, target)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::domain::index::IndexEntry;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_modes_of_working_tree_entries() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("plain.txt"), "plain\n").unwrap();
+        fs::write(root.join("run.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("missing-target", root.join("dangling")).unwrap();
+        fs::create_dir_all(root.join("dir")).unwrap();
+
+        let tree = WorkingTree::new(root.to_path_buf());
+        let mode = |name: &str| tree.file_mode(Path::new(name)).unwrap();
+        assert_eq!(mode("plain.txt"), Some(FileMode::Regular));
+        assert_eq!(mode("run.sh"), Some(FileMode::Executable));
+        assert_eq!(mode("dangling"), Some(FileMode::Symlink));
+        assert_eq!(mode("dir"), Some(FileMode::Directory));
+        assert_eq!(mode("nothing"), None);
+
+        // A symlink is stored as a blob holding its target
+        let store = ObjectStore::new(root.join("objects"));
+        let (link_mode, link_hash) = tree.store_path(&store, Path::new("dangling")).unwrap();
+        assert_eq!(link_mode, FileMode::Symlink);
+        assert_eq!(
+            link_hash,
+            ObjectFormat::Sha1.hash_object("blob", b"missing-target")
+        );
+    }
+
+    #[test]
+    fn test_refresh_index_reads_only_changed_files() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let tree = WorkingTree::new(root.to_path_buf());
+        let store = ObjectStore::new(root.join("objects"));
+        for (name, content) in [("same.txt", "same\n"), ("edited.txt", "new\n")] {
+            fs::write(root.join(name), content).unwrap();
+        }
+        fs::write(root.join("touched.txt"), "touched\n").unwrap();
+
+        let blob = |content: &str| ObjectFormat::Sha1.hash_object("blob", content.as_bytes());
+        let staged = |name: &str, content: &str| {
+            let metadata = fs::symlink_metadata(root.join(name)).unwrap();
+            IndexEntry::from_file_metadata(PathBuf::from(name), blob(content), &metadata)
+        };
+        let mut index = GitIndex::new();
+        index.add_entry(staged("same.txt", "same\n"));
+        // Stat data of the file on disk, content it no longer has: only
+        // reading the file can tell
+        index.add_entry(staged("edited.txt", "old\n"));
+        let mut touched = staged("touched.txt", "touched\n");
+        touched.mtime = IndexEntry::timestamp(1_000_000_000, 0);
+        index.add_entry(touched);
+        index.add_entry(IndexEntry::new(
+            PathBuf::from("deleted.txt"),
+            blob("gone\n"),
+            5,
+            FileMode::Regular,
+        ));
+        let state =
+            |refresh: &IndexRefresh, name: &str| refresh.files.get(Path::new(name)).copied();
+        let entry = |index: &GitIndex, name: &str| index.entries[Path::new(name)].clone();
+
+        // Written long after the files: matching stat data is trusted
+        let later = Some(IndexEntry::timestamp(4_000_000_000, 0));
+        index.timestamp = later;
+        let refresh = tree.refresh_index(&store, &mut index).unwrap();
+        assert_eq!(
+            state(&refresh, "edited.txt"),
+            Some((FileMode::Regular, blob("old\n")))
+        );
+        assert_eq!(state(&refresh, "deleted.txt"), None);
+        assert!(refresh.changed);
+        let metadata = fs::symlink_metadata(root.join("touched.txt")).unwrap();
+        assert!(entry(&index, "touched.txt").stat_matches(&metadata));
+
+        // Racily clean: every file is read, and the modified one is
+        // smudged so it stays detectable once the index is saved again
+        index.timestamp = Some(IndexEntry::timestamp(0, 0));
+        let refresh = tree.refresh_index(&store, &mut index).unwrap();
+        assert_eq!(
+            state(&refresh, "edited.txt"),
+            Some((FileMode::Regular, blob("new\n")))
+        );
+        assert_eq!(
+            state(&refresh, "same.txt"),
+            Some((FileMode::Regular, blob("same\n")))
+        );
+        assert_eq!(entry(&index, "edited.txt").size, 0);
+
+        index.timestamp = later;
+        let refresh = tree.refresh_index(&store, &mut index).unwrap();
+        assert_eq!(
+            state(&refresh, "edited.txt"),
+            Some((FileMode::Regular, blob("new\n")))
+        );
+        assert!(!refresh.changed);
+    }
+
+    #[test]
+    fn test_checkout_restores_symlinks_and_exec_bits() {
+        let source_dir = tempdir().unwrap();
+        let source = WorkingTree::new(source_dir.path().to_path_buf());
+        let store = ObjectStore::new(source_dir.path().join("objects"));
+
+        fs::write(source_dir.path().join("run.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(
+            source_dir.path().join("run.sh"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("run.sh", source_dir.path().join("link")).unwrap();
+
+        let mut tree = TreeObject::new();
+        for name in ["run.sh", "link"] {
+            let (mode, hash) = source.store_path(&store, Path::new(name)).unwrap();
+            tree.add_entry(TreeEntry::new(mode, name.to_string(), hash));
+        }
+        let gitlink = ObjectHash::from_hex("0123456789abcdef0123456789abcdef01234567").unwrap();
+        tree.add_entry(TreeEntry::new(
+            FileMode::Gitlink,
+            "vendor".to_string(),
+            gitlink,
+        ));
+        let tree_hash = store.store_object(&GitObject::Tree(tree)).unwrap();
+
+        let target_dir = tempdir().unwrap();
+        let target = WorkingTree::new(target_dir.path().to_path_buf());
+        assert_eq!(target.checkout_tree(&store, &tree_hash).unwrap(), 3);
+
+        let script = fs::metadata(target_dir.path().join("run.sh")).unwrap();
+        assert_eq!(script.permissions().mode() & 0o777, 0o755);
+        assert_eq!(
+            fs::read_link(target_dir.path().join("link")).unwrap(),
+            PathBuf::from("run.sh")
+        );
+        assert!(target_dir.path().join("vendor").is_dir());
+        assert_eq!(
+            target.file_mode(Path::new("link")).unwrap(),
+            Some(FileMode::Symlink)
+        );
+    }
+
+    #[test]
+    fn test_nested_repository_is_a_gitlink() {
+        let temp_dir = tempdir().unwrap();
+        let sub_git = temp_dir.path().join("sub/.git");
+        fs::create_dir_all(sub_git.join("refs/heads")).unwrap();
+        fs::write(sub_git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        fs::write(sub_git.join("refs/heads/main"), format!("{}\n", commit)).unwrap();
+
+        let tree = WorkingTree::new(temp_dir.path().to_path_buf());
+        let store = ObjectStore::new(temp_dir.path().join("objects"));
+        assert_eq!(
+            tree.hash_path(&store, Path::new("sub")).unwrap(),
+            (FileMode::Gitlink, ObjectHash::from_hex(commit).unwrap())
+        );
+    }
+}
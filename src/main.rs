@@ -1,6 +1,18 @@
 use clap::{Parser, Subcommand};
+use git_rs::application::add::AddOptions;
+use git_rs::application::cat_file::{BatchMode, CatFileMode};
+use git_rs::application::clone::CloneOptions;
+use git_rs::application::fsck::FsckOptions;
+use git_rs::application::hash_object::HashObjectOptions;
+use git_rs::application::mv::MvOptions;
+use git_rs::application::repack::RepackOptions;
+use git_rs::application::rev_parse::RevParseOptions;
+use git_rs::application::rm::RmOptions;
+use git_rs::application::tag::TagOptions;
 use git_rs::cli::GitCommand;
+use git_rs::domain::objects::{GitObjectType, ObjectFormat};
 use git_rs::domain::repository::GitCompatMode;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "git-rs")]
@@ -22,12 +34,49 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize a new Git repository
-    Init,
+    Init {
+        /// Hash algorithm for object ids (sha1 or sha256)
+        #[arg(long, default_value = "sha1")]
+        object_format: ObjectFormat,
+    },
     /// Add files to the staging area
     Add {
-        /// Files to add
+        /// Stage new, modified and deleted files; everything when no files are given
+        #[arg(short = 'A', long, conflicts_with = "update")]
+        all: bool,
+        /// Stage modified and deleted tracked files only
+        #[arg(short, long)]
+        update: bool,
+        /// Allow adding otherwise ignored files
+        #[arg(short, long)]
+        force: bool,
+        /// Files or directories to add
         files: Vec<String>,
     },
+    /// Remove files from the working tree and the index
+    Rm {
+        /// Only remove from the index, keeping the files
+        #[arg(long)]
+        cached: bool,
+        /// Allow recursive removal when a directory is given
+        #[arg(short = 'r')]
+        recursive: bool,
+        /// Remove even if the files have staged or local changes
+        #[arg(short, long)]
+        force: bool,
+        /// Files or directories to remove
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Move or rename files and directories in the working tree and the index
+    Mv {
+        /// Overwrite an existing destination file
+        #[arg(short, long)]
+        force: bool,
+        /// Sources followed by the destination
+        #[arg(required = true, num_args = 2..)]
+        paths: Vec<String>,
+    },
     /// Create a new commit
     Commit {
         /// Commit message
@@ -46,6 +95,12 @@ enum Commands {
         url: String,
         /// Directory name
         directory: Option<String>,
+        /// Borrow objects from a local repository instead of copying them
+        #[arg(long, value_name = "REPO")]
+        reference: Option<PathBuf>,
+        /// Borrow all objects from the local source repository
+        #[arg(short = 's', long)]
+        shared: bool,
     },
     /// Show repository status
     Status,
@@ -54,6 +109,127 @@ enum Commands {
         /// Number of commits to show
         #[arg(short = 'n', long)]
         count: Option<usize>,
+        /// Revision or range to start from (e.g. main~2, v1.0..HEAD)
+        revision: Option<String>,
+    },
+    /// Pack loose objects into a pack file
+    Repack {
+        /// Pack all objects, including those already packed
+        #[arg(short = 'a')]
+        all: bool,
+        /// Delete loose objects and old packs made redundant by the new pack
+        #[arg(short = 'd')]
+        delete: bool,
+        /// Number of objects to consider as delta bases
+        #[arg(long, default_value_t = 10)]
+        window: usize,
+        /// Maximum delta chain length
+        #[arg(long, default_value_t = 50)]
+        depth: usize,
+    },
+    /// Clean up the object database (repack everything into one pack)
+    Gc,
+    /// Show the type, size or content of repository objects
+    #[command(group = clap::ArgGroup::new("mode").required(true))]
+    CatFile {
+        /// Show the object type
+        #[arg(short = 't', group = "mode")]
+        show_type: bool,
+        /// Show the object size
+        #[arg(short = 's', group = "mode")]
+        size: bool,
+        /// Exit with zero status if the object exists, print nothing
+        #[arg(short = 'e', group = "mode")]
+        exists: bool,
+        /// Pretty-print the object content
+        #[arg(short = 'p', group = "mode")]
+        pretty: bool,
+        /// Print id, type, size and content for each object named on stdin
+        #[arg(long, group = "mode")]
+        batch: bool,
+        /// Print id, type and size for each object named on stdin
+        #[arg(long, group = "mode")]
+        batch_check: bool,
+        /// Object name (any revision, e.g. HEAD~1, v1.0^{tree}, HEAD:README)
+        #[arg(required_unless_present_any = ["batch", "batch_check"])]
+        object: Option<String>,
+    },
+    /// Resolve revision expressions to object ids
+    RevParse {
+        /// Fail unless exactly one single revision is given
+        #[arg(long)]
+        verify: bool,
+        /// Abbreviate ids (to at least 7 digits, or the given number)
+        #[arg(long, num_args = 0..=1, default_missing_value = "7", require_equals = true)]
+        short: Option<usize>,
+        /// Revisions (e.g. HEAD~2, main^2, v1.0^{tree}, HEAD:README, A..B)
+        #[arg(required = true)]
+        revisions: Vec<String>,
+    },
+    /// Compute the object id of file contents, optionally storing them
+    HashObject {
+        /// Write the object into the object database
+        #[arg(short = 'w')]
+        write: bool,
+        /// Read the content from standard input
+        #[arg(long)]
+        stdin: bool,
+        /// Object type (blob, tree, commit or tag)
+        #[arg(short = 't', default_value = "blob")]
+        object_type: GitObjectType,
+        /// Files to hash
+        #[arg(required_unless_present = "stdin")]
+        files: Vec<String>,
+    },
+    /// Create a tree object from the current index
+    WriteTree,
+    /// Create a commit object for a tree
+    CommitTree {
+        /// Tree to commit
+        tree: String,
+        /// Parent commit (repeat for merges)
+        #[arg(short = 'p')]
+        parents: Vec<String>,
+        /// Commit message (repeat for more paragraphs)
+        #[arg(short = 'm', required = true)]
+        messages: Vec<String>,
+    },
+    /// Point a reference at an object
+    UpdateRef {
+        /// Reference to update (HEAD or a name under refs/)
+        reference: String,
+        /// Object to point the reference at
+        new_value: String,
+        /// Value the reference must currently have (all zeros: must not exist)
+        old_value: Option<String>,
+    },
+    /// Verify object integrity and connectivity
+    Fsck {
+        /// Write dangling commits to refs/lost-found/
+        #[arg(long)]
+        lost_found: bool,
+    },
+    /// Create, list or delete tags
+    Tag {
+        /// Tag name (lists tags when omitted)
+        name: Option<String>,
+        /// Object to tag (default: HEAD)
+        target: Option<String>,
+        /// Create an annotated tag object
+        #[arg(short = 'a')]
+        annotate: bool,
+        /// Tag message (implies -a)
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Delete the named tag
+        #[arg(short, long, requires = "name")]
+        delete: bool,
+        /// Replace an existing tag
+        #[arg(short, long)]
+        force: bool,
+        /// List tags
+        #[arg(short, long)]
+        list: bool,
     },
 }
 
@@ -68,15 +244,169 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     match cli.command {
-        Commands::Init => GitCommand::init_with_compat(git_compat)?,
-        Commands::Add { files } => GitCommand::add_with_compat(&files, git_compat)?,
+        Commands::Init { object_format } => {
+            GitCommand::init_with_compat(object_format, git_compat)?
+        }
+        Commands::Add {
+            all,
+            update,
+            force,
+            files,
+        } => GitCommand::add_with_compat(
+            &files,
+            AddOptions {
+                all,
+                update,
+                force,
+                ..AddOptions::default()
+            },
+            git_compat,
+        )?,
+        Commands::Rm {
+            cached,
+            recursive,
+            force,
+            paths,
+        } => GitCommand::rm_with_compat(
+            &paths,
+            RmOptions {
+                cached,
+                recursive,
+                force,
+            },
+            git_compat,
+        )?,
+        Commands::Mv { force, paths } => {
+            GitCommand::mv_with_compat(&paths, MvOptions { force }, git_compat)?
+        }
         Commands::Commit { message } => GitCommand::commit_with_compat(&message, git_compat)?,
         Commands::Diff { cached } => GitCommand::diff_with_compat(cached, git_compat)?,
-        Commands::Clone { url, directory } => {
-            GitCommand::clone_with_compat(&url, directory.as_deref(), git_compat)?
+        Commands::Clone {
+            url,
+            directory,
+            reference,
+            shared,
+        } => {
+            let options = CloneOptions {
+                reference,
+                shared,
+                ..Default::default()
+            };
+            GitCommand::clone_with_compat(&url, directory.as_deref(), options, git_compat)?
         }
         Commands::Status => GitCommand::status_with_compat(git_compat)?,
-        Commands::Log { count } => GitCommand::log_with_compat(count, git_compat)?,
+        Commands::Log { count, revision } => {
+            GitCommand::log_with_compat(count, revision, git_compat)?
+        }
+        Commands::Repack {
+            all,
+            delete,
+            window,
+            depth,
+        } => {
+            let options = RepackOptions {
+                all,
+                delete_redundant: delete,
+                window,
+                depth,
+            };
+            GitCommand::repack_with_compat(options, git_compat)?
+        }
+        Commands::Gc => GitCommand::gc_with_compat(git_compat)?,
+        Commands::CatFile {
+            show_type,
+            size,
+            exists,
+            pretty,
+            batch,
+            batch_check,
+            object,
+        } => {
+            if batch || batch_check {
+                let mode = if batch {
+                    BatchMode::Contents
+                } else {
+                    BatchMode::Check
+                };
+                GitCommand::cat_file_batch_with_compat(mode, git_compat)?
+            } else {
+                let mode = if show_type {
+                    CatFileMode::Type
+                } else if size {
+                    CatFileMode::Size
+                } else if exists {
+                    CatFileMode::Exists
+                } else {
+                    debug_assert!(pretty);
+                    CatFileMode::Pretty
+                };
+                GitCommand::cat_file_with_compat(object.as_deref().unwrap_or(""), mode, git_compat)?
+            }
+        }
+        Commands::RevParse {
+            verify,
+            short,
+            revisions,
+        } => GitCommand::rev_parse_with_compat(
+            &revisions,
+            RevParseOptions { verify, short },
+            git_compat,
+        )?,
+        Commands::HashObject {
+            write,
+            stdin,
+            object_type,
+            files,
+        } => GitCommand::hash_object_with_compat(
+            &files,
+            stdin,
+            HashObjectOptions { object_type, write },
+            git_compat,
+        )?,
+        Commands::WriteTree => GitCommand::write_tree_with_compat(git_compat)?,
+        Commands::CommitTree {
+            tree,
+            parents,
+            messages,
+        } => GitCommand::commit_tree_with_compat(&tree, &parents, &messages, git_compat)?,
+        Commands::UpdateRef {
+            reference,
+            new_value,
+            old_value,
+        } => GitCommand::update_ref_with_compat(
+            &reference,
+            &new_value,
+            old_value.as_deref(),
+            git_compat,
+        )?,
+        Commands::Fsck { lost_found } => {
+            GitCommand::fsck_with_compat(FsckOptions { lost_found }, git_compat)?
+        }
+        Commands::Tag {
+            name,
+            target,
+            annotate,
+            message,
+            delete,
+            force,
+            list,
+        } => {
+            let options = TagOptions {
+                annotate,
+                message,
+                force,
+            };
+            match name {
+                Some(name) if delete => GitCommand::tag_delete_with_compat(&name, git_compat)?,
+                Some(name) if !list => GitCommand::tag_create_with_compat(
+                    &name,
+                    target.as_deref(),
+                    options,
+                    git_compat,
+                )?,
+                _ => GitCommand::tag_list_with_compat(git_compat)?,
+            }
+        }
     }
 
     Ok(())
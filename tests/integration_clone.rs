@@ -71,6 +71,7 @@ mod clone_tests {
             bare: true,
             depth: Some(10),
             progress: false,
+            ..Default::default()
         };
 
         assert_eq!(options.branch, Some("develop".to_string()));